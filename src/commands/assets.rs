@@ -2,6 +2,10 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
+use rayon::prelude::*;
+use log::{debug, error, info, warn};
+use crate::utils::output::SUCCESS;
+use super::generate::{GenError, GenOptions, DEFAULT_INDENT};
 
 #[derive(Debug, Deserialize, Serialize)]
 struct PubspecYaml {
@@ -16,6 +20,20 @@ struct FlutterSection {
     assets: Vec<String>,
 }
 
+/// Top-level class of assets.gen.dart unless configured otherwise
+pub const DEFAULT_ASSETS_CLASS_NAME: &str = "Assets";
+
+/// What an asset generation run produced
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AssetStats {
+    /// Asset constants generated
+    pub assets: usize,
+    /// Asset paths declared in pubspec.yaml that don't exist under the project
+    pub missing: Vec<String>,
+    /// Path of the written assets.gen.dart (exported by the --barrel file)
+    pub outputs: Vec<PathBuf>,
+}
+
 impl Default for FlutterSection {
     fn default() -> Self {
         Self {
@@ -25,14 +43,14 @@ impl Default for FlutterSection {
 }
 
 pub fn generate_assets_from_path(project_path: &str) {
-    println!("Generating assets from {}", project_path);
+    info!("Generating assets from {}", project_path);
     
     // Load pubspec.yaml
     let pubspec_path = format!("{}/pubspec.yaml", project_path);
     let pubspec_content = match fs::read_to_string(&pubspec_path) {
         Ok(content) => content,
         Err(e) => {
-            eprintln!("Error reading {}: {}", pubspec_path, e);
+            error!("Error reading {}: {}", pubspec_path, e);
             return;
         }
     };
@@ -41,125 +59,245 @@ pub fn generate_assets_from_path(project_path: &str) {
     let pubspec: PubspecYaml = match serde_yaml::from_str(&pubspec_content) {
         Ok(pubspec) => pubspec,
         Err(e) => {
-            eprintln!("Error parsing pubspec.yaml: {}", e);
+            error!("Error parsing pubspec.yaml: {}", e);
             return;
         }
     };
     
     // Collect asset files
-    let asset_files = collect_asset_files_from_project(&pubspec.flutter.assets, project_path);
+    report_missing_asset_paths(&pubspec.flutter.assets, project_path);
+    let asset_files = collect_asset_files_from_project(&pubspec.flutter.assets, project_path, &[]);
     
     // Generate Dart class
-    let dart_code = generate_dart_assets_class(&asset_files);
+    let dart_code = generate_dart_assets_class(&asset_files, DEFAULT_ASSETS_CLASS_NAME);
     
     // Create output directory
     let output_dir = format!("{}/lib/gen", project_path);
     let output_path = Path::new(&output_dir);
     if let Err(e) = fs::create_dir_all(output_path) {
-        eprintln!("Error creating output directory: {}", e);
+        error!("Error creating output directory: {}", e);
         return;
     }
     
     // Write to file
     let output_file_path = format!("{}/assets.gen.dart", output_dir).replace("//", "/");
-    if let Err(e) = fs::write(&output_file_path, dart_code) {
-        eprintln!("Error writing assets.gen.dart: {}", e);
+    if let Err(e) = fs::write(&output_file_path, super::generate::finish_generated_code(&dart_code, DEFAULT_INDENT)) {
+        error!("Error writing assets.gen.dart: {}", e);
         return;
     }
     
-    println!("Generated assets.gen.dart with {} asset constants", asset_files.len());
+    info!(target: SUCCESS, "Generated assets.gen.dart with {} asset constants", asset_files.len());
 }
 
 // FlutterGen-like behavior: explore based on pubspec.yaml assets configuration
-pub fn generate_assets_with_paths(_assets_path: &str, output_path: &str) {
-    println!("Generating assets from pubspec.yaml to {}", output_path);
-    
-    // Load pubspec.yaml from current directory
-    let pubspec_content = match fs::read_to_string("pubspec.yaml") {
-        Ok(content) => content,
-        Err(e) => {
-            eprintln!("Error reading pubspec.yaml: {}", e);
-            return;
-        }
+// Returns the number of asset constants generated and the declared paths that were not found
+// pubspec.yaml is read from the project root (--root, or found from the current directory),
+// which relative output paths are resolved against too
+pub fn generate_assets_with_paths(_assets_path: &str, output_path: &str, exclude: &[String], class_name: &str, options: &GenOptions) -> Result<AssetStats, GenError> {
+    let current_dir = std::env::current_dir().map_err(|e| GenError::io(".", e))?;
+    generate_assets_from_dir(&current_dir, output_path, exclude, class_name, options)
+}
+
+// generate_assets_with_paths run from current_dir
+fn generate_assets_from_dir(current_dir: &Path, output_path: &str, exclude: &[String], class_name: &str, options: &GenOptions) -> Result<AssetStats, GenError> {
+    let project_root = match &options.project_root {
+        Some(project_root) => Some(project_root.clone()),
+        None => project_root_elsewhere(current_dir),
     };
+    match project_root {
+        Some(project_root) => {
+            let output_path = project_root.join(output_path).to_string_lossy().to_string();
+            generate_assets_for_project(&project_root, &output_path, exclude, class_name, options)
+        }
+        None => generate_assets_for_project(Path::new("."), output_path, exclude, class_name, options),
+    }
+}
+
+// The Flutter project root when it isn't current_dir itself (e.g. when running from lib/src)
+fn project_root_elsewhere(current_dir: &Path) -> Option<PathBuf> {
+    let project_root = super::generate::find_flutter_project_root(current_dir).ok()?;
+    (project_root != current_dir).then_some(project_root)
+}
+
+// Read pubspec.yaml and the assets it declares from project_root
+fn generate_assets_for_project(project_root: &Path, output_path: &str, exclude: &[String], class_name: &str, options: &GenOptions) -> Result<AssetStats, GenError> {
+    info!("Generating assets from pubspec.yaml to {}", output_path);
+    
+    // Load pubspec.yaml from the project root
+    let pubspec_path = project_root.join("pubspec.yaml");
+    let pubspec_content = fs::read_to_string(&pubspec_path).map_err(|e| GenError::io(&pubspec_path, e))?;
     
     // Parse YAML
-    let pubspec: PubspecYaml = match serde_yaml::from_str(&pubspec_content) {
-        Ok(pubspec) => pubspec,
-        Err(e) => {
-            eprintln!("Error parsing pubspec.yaml: {}", e);
-            return;
-        }
-    };
+    let pubspec: PubspecYaml = serde_yaml::from_str(&pubspec_content).map_err(|e| GenError::Parse {
+        path: pubspec_path.clone(),
+        message: e.to_string(),
+    })?;
     
     // Collect asset files using pubspec.yaml assets configuration
-    // Use the current directory as project root
-    let project_root = ".";
-    let asset_files = collect_asset_files_from_project(&pubspec.flutter.assets, project_root);
+    let missing = report_missing_asset_paths(&pubspec.flutter.assets, &project_root.to_string_lossy());
+    let asset_files = collect_asset_files_from_project(&pubspec.flutter.assets, &project_root.to_string_lossy(), exclude);
     
     // Generate Dart class
-    let dart_code = generate_dart_assets_class(&asset_files);
+    let dart_code = generate_dart_assets_class(&asset_files, class_name);
     
     // Create output directory
-    let output_path_buf = Path::new(output_path);
-    if let Err(e) = fs::create_dir_all(output_path_buf) {
-        eprintln!("Error creating output directory: {}", e);
-        return;
-    }
+    fs::create_dir_all(output_path).map_err(|e| GenError::io(output_path, e))?;
     
     // Write to file
     let output_file_path = format!("{}/assets.gen.dart", output_path).replace("//", "/");
-    if let Err(e) = fs::write(&output_file_path, dart_code) {
-        eprintln!("Error writing assets.gen.dart: {}", e);
-        return;
+    fs::write(&output_file_path, super::generate::finish_generated_code(&dart_code, options.indent)).map_err(|e| GenError::io(&output_file_path, e))?;
+    
+    super::generate::format_dart_files(&[PathBuf::from(&output_file_path)], options);
+    info!(target: SUCCESS, "Generated assets.gen.dart with {} asset constants", asset_files.len());
+
+    if let Some(manifest_path) = &options.asset_manifest {
+        fs::write(manifest_path, asset_manifest(&asset_files, project_root)).map_err(|e| GenError::io(manifest_path, e))?;
+        info!(target: SUCCESS, "Generated: {}", manifest_path.display());
     }
+    Ok(AssetStats { assets: asset_files.len(), missing, outputs: vec![PathBuf::from(&output_file_path)] })
+}
+
+/// One JSON object per asset and line: its key, category (None for `assets/<file>`), type and size in bytes
+fn asset_manifest(asset_files: &[String], project_root: &Path) -> String {
+    asset_files
+        .iter()
+        .map(|asset_file| {
+            let entry = serde_json::json!({
+                "key": asset_file,
+                "category": get_asset_category(asset_file),
+                "type": get_asset_type(asset_file),
+                "size": fs::metadata(project_root.join(asset_file)).map(|metadata| metadata.len()).ok(),
+            });
+            format!("{}\n", entry)
+        })
+        .collect()
+}
+
+// Warn about each declared asset path that doesn't exist; typos in pubspec.yaml would otherwise
+// only show up as runtime asset-not-found errors
+fn report_missing_asset_paths(asset_paths: &[String], project_path: &str) -> Vec<String> {
+    let missing = missing_asset_paths(asset_paths, project_path);
+    for path in &missing {
+        warn!("asset path not found: {}", path);
+    }
+    if !missing.is_empty() {
+        warn!("{} of {} asset paths declared in pubspec.yaml were not found", missing.len(), asset_paths.len());
+    }
+    missing
+}
+
+fn collect_asset_files_from_project(asset_paths: &[String], project_path: &str, exclude: &[String]) -> Vec<String> {
+    // Walk each configured path in parallel, then sort once for deterministic output
+    let mut asset_files: Vec<String> = asset_paths
+        .par_iter()
+        .flat_map_iter(|path| collect_asset_files_from_path(path, project_path))
+        .filter(|asset_file| !is_excluded_asset(asset_file, exclude))
+        .collect();
     
-    println!("Generated assets.gen.dart with {} asset constants", asset_files.len());
+    asset_files.sort();
+    asset_files
 }
 
-fn collect_asset_files_from_project(asset_paths: &[String], project_path: &str) -> Vec<String> {
+/// Asset paths declared in pubspec.yaml that don't exist under the project
+fn missing_asset_paths(asset_paths: &[String], project_path: &str) -> Vec<String> {
+    asset_paths
+        .iter()
+        .filter(|path| !Path::new(project_path).join(path).exists())
+        .cloned()
+        .collect()
+}
+
+/// Whether an asset path (e.g. "assets/raw/logo.psd") matches one of the exclude globs.
+/// Patterns without '/' match the file name ("*.psd"); others match the whole path ("assets/raw/**").
+fn is_excluded_asset(asset_file: &str, exclude: &[String]) -> bool {
+    matches_any_glob(asset_file, exclude)
+}
+
+/// Whether a '/'-separated path matches one of the globs, with the same rules as asset excludes
+pub(crate) fn matches_any_glob(path: &str, patterns: &[String]) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    patterns.iter().any(|pattern| {
+        let target = if pattern.contains('/') { path } else { file_name };
+        glob_to_regex(pattern).map(|re| re.is_match(target)).unwrap_or(false)
+    })
+}
+
+// `**` matches across directories, `*` within one path segment and `?` a single character
+fn glob_to_regex(pattern: &str) -> Option<regex::Regex> {
+    let mut regex_pattern = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" also matches zero directories
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex_pattern.push_str("(?:.*/)?");
+                } else {
+                    regex_pattern.push_str(".*");
+                }
+            }
+            '*' => regex_pattern.push_str("[^/]*"),
+            '?' => regex_pattern.push_str("[^/]"),
+            _ => regex_pattern.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    regex_pattern.push('$');
+    regex::Regex::new(&regex_pattern).ok()
+}
+
+// Files for a single pubspec.yaml asset entry (a file or a directory walked recursively)
+fn collect_asset_files_from_path(path: &str, project_path: &str) -> Vec<String> {
     let mut asset_files = Vec::new();
     
-    for path in asset_paths {
-        // Only process paths that start with "assets/"
-        if !path.starts_with("assets/") {
-            continue;
-        }
-        
-        let full_path = format!("{}/{}", project_path, path);
-        let path_buf = PathBuf::from(&full_path);
-        
-        if path_buf.is_file() {
-            // Single file case
-            asset_files.push(path.to_string());
-        } else if path_buf.is_dir() {
-            // Directory case, recursively search
-            for entry in WalkDir::new(&path_buf).into_iter().filter_map(|e| e.ok()) {
-                if entry.file_type().is_file() {
-                    if let Some(relative_path) = entry.path().strip_prefix(&path_buf).ok() {
-                        let asset_path = format!("{}/{}", path, relative_path.to_string_lossy());
-                        asset_files.push(asset_path);
-                    }
+    // Only process paths that start with "assets/"
+    if !path.starts_with("assets/") {
+        return asset_files;
+    }
+    
+    let full_path = format!("{}/{}", project_path, path);
+    let path_buf = PathBuf::from(&full_path);
+    
+    if path_buf.is_file() {
+        // Single file case
+        asset_files.push(path.to_string());
+    } else if path_buf.is_dir() {
+        // Directory case, recursively search
+        for entry in asset_dir_entries(&path_buf) {
+            if entry.file_type().is_file() {
+                if let Ok(relative_path) = entry.path().strip_prefix(&path_buf) {
+                    let asset_path = format!("{}/{}", path.trim_end_matches('/'), relative_path.to_string_lossy());
+                    asset_files.push(asset_path);
                 }
             }
         }
     }
     
-    asset_files.sort();
     asset_files
 }
 
+// Entries under an asset directory. Symlinks are listed but not followed (a link back up the
+// tree would loop forever), and the `.symlinks` directories of vendored plugins are skipped.
+fn asset_dir_entries(dir: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".symlinks")
+        .filter_map(|e| e.ok())
+}
+
 // New function: configurable paths
 fn collect_asset_files_from_paths(asset_paths: &[String], assets_base_path: &str) -> Vec<String> {
     let mut asset_files = Vec::new();
     
-    println!("Debug: Processing {} asset paths", asset_paths.len());
+    debug!("Processing {} asset paths", asset_paths.len());
     
     for path in asset_paths {
-        println!("Debug: Checking path: {}", path);
+        debug!("Checking path: {}", path);
         // Only process paths that start with "assets/"
         if !path.starts_with("assets/") {
-            println!("Debug: Skipping non-asset path: {}", path);
+            debug!("Skipping non-asset path: {}", path);
             continue;
         }
         // If path starts with assets/, do not add assets_base_path
@@ -169,32 +307,33 @@ fn collect_asset_files_from_paths(asset_paths: &[String], assets_base_path: &str
             format!("{}/{}", assets_base_path, path)
         };
         let path_buf = PathBuf::from(&full_path);
-        println!("Debug: Full path: {}", full_path);
-        println!("Debug: Path exists: {}", path_buf.exists());
-        println!("Debug: Is file: {}", path_buf.is_file());
-        println!("Debug: Is dir: {}", path_buf.is_dir());
+        debug!("Full path: {}", full_path);
+        debug!("Path exists: {}", path_buf.exists());
+        debug!("Is file: {}", path_buf.is_file());
+        debug!("Is dir: {}", path_buf.is_dir());
         if path_buf.is_file() {
-            println!("Debug: Adding file: {}", path);
+            debug!("Adding file: {}", path);
             asset_files.push(path.to_string());
         } else if path_buf.is_dir() {
-            println!("Debug: Searching directory: {}", path);
-            for entry in WalkDir::new(&path_buf).into_iter().filter_map(|e| e.ok()) {
+            debug!("Searching directory: {}", path);
+            for entry in asset_dir_entries(&path_buf) {
                 if entry.file_type().is_file() {
                     if let Some(relative_path) = entry.path().strip_prefix(&path_buf).ok() {
-                        let asset_path = format!("{}/{}", path, relative_path.to_string_lossy());
-                        println!("Debug: Found file in dir: {}", asset_path);
+                        let asset_path = format!("{}/{}", path.trim_end_matches('/'), relative_path.to_string_lossy());
+                        debug!("Found file in dir: {}", asset_path);
                         asset_files.push(asset_path);
                     }
                 }
             }
         }
     }
-    println!("Debug: Total asset files found: {}", asset_files.len());
+    debug!("Total asset files found: {}", asset_files.len());
     asset_files.sort();
     asset_files
 }
 
-fn generate_dart_assets_class(asset_files: &[String]) -> String {
+// `class_name` names the top-level class; the category classes stay `$Assets...Gen`
+fn generate_dart_assets_class(asset_files: &[String], class_name: &str) -> String {
     let mut dart_code = String::new();
     
     // Header - match flutter_gen exactly
@@ -203,6 +342,7 @@ fn generate_dart_assets_class(asset_files: &[String]) -> String {
     dart_code.push_str("/// *****************************************************\n");
     dart_code.push_str("///  FlutterGen\n");
     dart_code.push_str("/// *****************************************************\n\n");
+    dart_code.push_str(&format!("{}\n", super::GENERATOR_SIGNATURE));
     dart_code.push_str("// coverage:ignore-file\n");
     dart_code.push_str("// ignore_for_file: type=lint\n");
     dart_code.push_str("// ignore_for_file: deprecated_member_use,directives_ordering,implicit_dynamic_list_literal,unnecessary_import\n\n");
@@ -210,11 +350,19 @@ fn generate_dart_assets_class(asset_files: &[String]) -> String {
     
     // Group assets by category
     let mut categorized_assets: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+    // Files directly under assets/ become members of the main class
+    let mut top_level_assets: Vec<String> = Vec::new();
     
     for asset_file in asset_files {
         let normalized = asset_file.replace("//", "/");
+        if asset_file_to_constant_name_camel_case(&normalized).is_empty() {
+            info!("Skipping {}: file name can't be turned into a Dart identifier", normalized);
+            continue;
+        }
         if let Some(category) = get_asset_category(&normalized) {
             categorized_assets.entry(category.to_string()).or_insert_with(Vec::new).push(normalized);
+        } else if is_top_level_asset(&normalized) {
+            top_level_assets.push(normalized);
         }
     }
     
@@ -224,8 +372,7 @@ fn generate_dart_assets_class(asset_files: &[String]) -> String {
         dart_code.push_str(&format!("class {} {{\n", class_name));
         dart_code.push_str(&format!("  const {}();\n\n", class_name));
         
-        for file in files {
-            let constant_name = asset_file_to_constant_name_camel_case(file);
+        for (file, constant_name) in files.iter().zip(asset_constant_names(files)) {
             let asset_type = get_asset_type(file);
             
             match asset_type {
@@ -244,30 +391,48 @@ fn generate_dart_assets_class(asset_files: &[String]) -> String {
             }
         }
         
-        // Add values list
+        // Add values list (a single const list, so reading `values` doesn't allocate)
         dart_code.push_str("\n  /// List of all assets\n");
-        let asset_type = get_asset_type(&files[0]);
-        match asset_type {
-            "image" => {
-                dart_code.push_str(&format!("  List<AssetGenImage> get values => [{}];\n", 
-                    files.iter().map(|f| asset_file_to_constant_name_camel_case(f)).collect::<Vec<_>>().join(", ")));
-            },
-            _ => {
-                dart_code.push_str(&format!("  List<String> get values => [{}];\n", 
-                    files.iter().map(|f| asset_file_to_constant_name_camel_case(f)).collect::<Vec<_>>().join(", ")));
-            }
-        }
+        // A category mixing images with other files lists both, as flutter_gen does
+        let image_count = files.iter().filter(|f| get_asset_type(f) == "image").count();
+        let element_type = if image_count == files.len() {
+            "AssetGenImage"
+        } else if image_count == 0 {
+            "String"
+        } else {
+            "dynamic"
+        };
+        let values = files
+            .iter()
+            .map(|f| if get_asset_type(f) == "image" { format!("AssetGenImage('{}')", f) } else { format!("'{}'", f) })
+            .collect::<Vec<_>>()
+            .join(", ");
+        dart_code.push_str(&format!("  static const List<{}> _values = [{}];\n", element_type, values));
+        dart_code.push_str(&format!("  List<{}> get values => _values;\n", element_type));
         
         dart_code.push_str("}\n\n");
     }
     
     // Generate main Assets class
-    dart_code.push_str("class Assets {\n");
-    dart_code.push_str("  const Assets._();\n\n");
+    dart_code.push_str(&format!("class {} {{\n", class_name));
+    dart_code.push_str(&format!("  const {}._();\n\n", class_name));
     
     for category in categorized_assets.keys() {
-        let class_name = format!("$Assets{}Gen", capitalize_first(category));
-        dart_code.push_str(&format!("  static const {} {} = {}();\n", class_name, category, class_name));
+        let category_class_name = format!("$Assets{}Gen", capitalize_first(category));
+        dart_code.push_str(&format!("  static const {} {} = {}();\n", category_class_name, category, category_class_name));
+    }
+    
+    for (file, mut constant_name) in top_level_assets.iter().zip(asset_constant_names(&top_level_assets)) {
+        // assets/images.png next to assets/images/ would clash with the category member
+        if categorized_assets.contains_key(&constant_name) {
+            constant_name.push_str("File");
+        }
+        dart_code.push_str(&format!("\n  /// File path: {}\n", file));
+        if get_asset_type(file) == "image" {
+            dart_code.push_str(&format!("  static const AssetGenImage {} = AssetGenImage('{}');\n", constant_name, file));
+        } else {
+            dart_code.push_str(&format!("  static const String {} = '{}';\n", constant_name, file));
+        }
     }
     
     dart_code.push_str("}\n\n");
@@ -374,10 +539,19 @@ fn get_asset_category(asset_file: &str) -> Option<&str> {
     None
 }
 
+/// `assets/<file>`, which has no category directory
+fn is_top_level_asset(asset_file: &str) -> bool {
+    asset_file
+        .strip_prefix("assets/")
+        .is_some_and(|rest| !rest.is_empty() && !rest.contains('/'))
+}
+
 fn get_asset_type(asset_file: &str) -> &str {
     if let Some(extension) = asset_file.split('.').last() {
         match extension.to_lowercase().as_str() {
-            "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" => "image",
+            // Formats Flutter's Image.asset decodes on every platform. AVIF and HEIC aren't among them,
+            // so they stay plain paths like any other data file.
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "bmp" | "wbmp" | "svg" => "image",
             "ttf" | "otf" | "woff" | "woff2" => "font",
             _ => "data"
         }
@@ -401,57 +575,84 @@ fn asset_file_to_constant_name_camel_case(asset_file: &str) -> String {
     // Convert file path to camelCase constant name (flutter_gen style)
     // Example: "assets/images/logo.png" -> "logo"
     // Example: "assets/fonts/Roboto-Regular.ttf" -> "robotoRegular"
-    
-    // Remove "assets/" prefix if present
-    let path_without_assets = if asset_file.starts_with("assets/") {
-        &asset_file[7..] // Skip "assets/"
-    } else {
-        asset_file
-    };
-    
-    let mut constant_name = String::new();
-    
-    for part in path_without_assets.split('/') {
-        if !part.is_empty() {
-            // Remove file extension
-            let part_without_ext = if let Some(dot_pos) = part.rfind('.') {
-                &part[..dot_pos]
-            } else {
-                part
-            };
-            
-            // Convert to camelCase
-            let mut chars = part_without_ext.chars();
-            if let Some(first_char) = chars.next() {
-                constant_name.push(first_char.to_lowercase().next().unwrap());
-                constant_name.extend(chars.map(|c| {
-                    if c == '-' || c == '_' {
-                        ' ' // Replace with space to be removed
-                    } else {
-                        c
-                    }
-                }));
-            }
+    // Example: "assets/images/2x.png" -> "images2x" (identifiers can't start with a digit)
+    // Returns an empty string when the file name has no usable characters (e.g. "@@.png")
+    let file_name = asset_file.rsplit('/').next().unwrap_or(asset_file);
+    valid_dart_identifier(camel_case_identifier(&[file_stem(file_name)]), asset_file)
+}
+
+// Dart reserved words that can't be used as getter names
+const DART_RESERVED_WORDS: [&str; 33] = [
+    "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+    "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+    "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void",
+    "while", "with",
+];
+
+/// Prefix identifiers that start with a digit or are reserved words with the asset's category
+fn valid_dart_identifier(name: String, asset_file: &str) -> String {
+    if name.is_empty() {
+        return name;
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) || DART_RESERVED_WORDS.contains(&name.as_str()) {
+        let prefix = get_asset_category(asset_file).unwrap_or("asset");
+        return camel_case_identifier(&[prefix, &name]);
+    }
+    name
+}
+
+/// Getter names for the files of one category, in the same order.
+/// Files whose names collide (e.g. icons/logo.png and brand/logo.png) are prefixed with
+/// their parent directories (iconsLogo, brandLogo), falling back to a numeric suffix.
+fn asset_constant_names(files: &[String]) -> Vec<String> {
+    let base_names: Vec<String> = files.iter().map(|f| asset_file_to_constant_name_camel_case(f)).collect();
+    let mut names: Vec<String> = files.iter().zip(&base_names).map(|(file, base_name)| {
+        if base_names.iter().filter(|n| *n == base_name).count() == 1 {
+            return base_name.clone();
+        }
+        // Directories below assets/<category>/ plus the file stem
+        let segments: Vec<&str> = file.split('/').filter(|s| !s.is_empty()).collect();
+        let skip = if segments.first() == Some(&"assets") { 2 } else { 0 };
+        let mut parts: Vec<&str> = segments.iter().skip(skip).copied().collect();
+        if let Some(last) = parts.last_mut() {
+            *last = file_stem(last);
+        }
+        valid_dart_identifier(camel_case_identifier(&parts), file)
+    }).collect();
+    
+    // Still colliding (e.g. logo.png and logo.svg in the same directory): number them
+    let mut seen: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for name in names.iter_mut() {
+        let count = seen.entry(name.clone()).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            *name = format!("{}{}", name, count);
         }
     }
-    
-    // Convert kebab-case to camelCase (e.g., "Roboto-Regular" -> "robotoRegular")
+    names
+}
+
+fn file_stem(file_name: &str) -> &str {
+    match file_name.rfind('.') {
+        Some(dot_pos) if dot_pos > 0 => &file_name[..dot_pos],
+        _ => file_name,
+    }
+}
+
+/// Join path parts into a camelCase identifier, treating '-', '_' and other separators as word breaks
+fn camel_case_identifier(parts: &[&str]) -> String {
     let mut result = String::new();
-    let mut capitalize_next = false;
-    
-    for ch in constant_name.chars() {
-        if ch == ' ' {
-            capitalize_next = true;
-        } else {
-            if capitalize_next {
-                result.push(ch.to_uppercase().next().unwrap());
-                capitalize_next = false;
+    for word in parts.iter().flat_map(|part| part.split(|c: char| !c.is_alphanumeric())) {
+        let mut chars = word.chars();
+        if let Some(first_char) = chars.next() {
+            if result.is_empty() {
+                result.extend(first_char.to_lowercase());
             } else {
-                result.push(ch);
+                result.extend(first_char.to_uppercase());
             }
+            result.push_str(chars.as_str());
         }
     }
-    
     result
 }
 
@@ -461,6 +662,25 @@ mod tests {
     use tempfile::TempDir;
     use std::fs;
 
+    #[test]
+    fn test_missing_asset_paths_are_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join("assets/images")).unwrap();
+        fs::write(project_path.join("assets/data.json"), "{}").unwrap();
+        fs::write(
+            project_path.join("pubspec.yaml"),
+            "name: app\nflutter:\n  assets:\n    - assets/images/\n    - assets/imgaes/\n    - assets/data.json\n    - assets/missing.json\n",
+        ).unwrap();
+        
+        let options = GenOptions { project_root: Some(project_path.to_path_buf()), ..GenOptions::default() };
+        let stats = generate_assets_with_paths("assets", "lib/gen", &[], DEFAULT_ASSETS_CLASS_NAME, &options).unwrap();
+        assert_eq!(stats.missing, vec!["assets/imgaes/".to_string(), "assets/missing.json".to_string()]);
+        // Missing paths are reported, not fatal
+        assert_eq!(stats.assets, 1);
+        assert!(fs::read_to_string(project_path.join("lib/gen/assets.gen.dart")).unwrap().contains("'assets/data.json'"));
+    }
+
     #[test]
     fn test_collect_asset_files_from_project() {
         // Create a temporary directory for testing
@@ -481,14 +701,154 @@ mod tests {
             "assets/data.json".to_string(),
         ];
         
-        let asset_files = collect_asset_files_from_project(&asset_paths, project_path.to_str().unwrap());
+        let asset_files = collect_asset_files_from_project(&asset_paths, project_path.to_str().unwrap(), &[]);
         
         // Check that we have the expected files
         assert!(asset_files.len() >= 2);
-        assert!(asset_files.contains(&"assets/images//logo.png".to_string()));
+        assert!(asset_files.contains(&"assets/images/logo.png".to_string()));
         assert!(asset_files.contains(&"assets/data.json".to_string()));
     }
 
+    #[test]
+    fn test_generate_assets_for_project_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        fs::create_dir_all(project_path.join("assets/images")).unwrap();
+        fs::write(project_path.join("assets/images/logo.png"), "fake image").unwrap();
+        fs::write(project_path.join("pubspec.yaml"), "name: app\nflutter:\n  assets:\n    - assets/images/\n").unwrap();
+        let output_path = project_path.join("lib/gen");
+        
+        let stats = generate_assets_for_project(project_path, &output_path.to_string_lossy(), &[], DEFAULT_ASSETS_CLASS_NAME, &GenOptions::default()).unwrap();
+        assert_eq!(stats, AssetStats { assets: 1, missing: Vec::new(), outputs: vec![output_path.join("assets.gen.dart")] });
+        let generated = fs::read_to_string(output_path.join("assets.gen.dart")).unwrap();
+        // Asset paths stay relative to the project root
+        assert!(generated.contains("'assets/images/logo.png'"));
+    }
+
+    #[test]
+    fn test_project_root_found_from_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let src_dir = project_path.join("lib/src");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(project_path.join("pubspec.yaml"), "name: app\n").unwrap();
+        
+        assert_eq!(project_root_elsewhere(&src_dir), Some(project_path.to_path_buf()));
+        assert_eq!(project_root_elsewhere(project_path), None);
+    }
+
+    #[test]
+    fn test_generate_assets_from_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let src_dir = project_path.join("lib/src");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(project_path.join("assets/images")).unwrap();
+        fs::write(project_path.join("assets/images/logo.png"), "fake image").unwrap();
+        fs::write(project_path.join("pubspec.yaml"), "name: app\nflutter:\n  assets:\n    - assets/images/\n").unwrap();
+        
+        let stats = generate_assets_from_dir(&src_dir, "lib/gen", &[], DEFAULT_ASSETS_CLASS_NAME, &GenOptions::default()).unwrap();
+        assert_eq!(stats.assets, 1);
+        // The output path is relative to the project root, not lib/src
+        let generated = fs::read_to_string(project_path.join("lib/gen/assets.gen.dart")).unwrap();
+        assert!(generated.contains("'assets/images/logo.png'"));
+        assert!(!src_dir.join("lib").exists());
+    }
+
+    #[test]
+    fn test_collect_asset_files_with_exclude() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let images_dir = project_path.join("assets/images");
+        let raw_dir = project_path.join("assets/raw");
+        fs::create_dir_all(&images_dir).unwrap();
+        fs::create_dir_all(&raw_dir).unwrap();
+        fs::write(images_dir.join("logo.png"), "fake image").unwrap();
+        fs::write(images_dir.join("logo.psd"), "fake design").unwrap();
+        fs::write(raw_dir.join("scan.png"), "fake image").unwrap();
+        
+        let asset_paths = vec!["assets/images/".to_string(), "assets/raw/".to_string()];
+        let project_path = project_path.to_str().unwrap();
+        
+        let asset_files = collect_asset_files_from_project(&asset_paths, project_path, &["*.psd".to_string()]);
+        assert_eq!(asset_files, vec!["assets/images/logo.png".to_string(), "assets/raw/scan.png".to_string()]);
+        
+        let asset_files = collect_asset_files_from_project(&asset_paths, project_path, &["*.psd".to_string(), "assets/raw/**".to_string()]);
+        assert_eq!(asset_files, vec!["assets/images/logo.png".to_string()]);
+    }
+
+    #[test]
+    fn test_collect_asset_files_parallel_matches_serial() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_path = temp_dir.path();
+        let mut asset_paths = Vec::new();
+        for category in ["images", "icons", "fonts", "data"] {
+            for sub_dir in 0..5 {
+                let dir = project_path.join("assets").join(category).join(format!("set{}", sub_dir));
+                fs::create_dir_all(&dir).unwrap();
+                for i in 0..100 {
+                    fs::write(dir.join(format!("file_{}.png", i)), "x").unwrap();
+                }
+            }
+            asset_paths.push(format!("assets/{}/", category));
+        }
+        
+        let project_path = project_path.to_str().unwrap();
+        let start = std::time::Instant::now();
+        let parallel = collect_asset_files_from_project(&asset_paths, project_path, &[]);
+        debug!("Parallel walk of {} files took {:?}", parallel.len(), start.elapsed());
+        
+        let mut serial: Vec<String> = asset_paths
+            .iter()
+            .flat_map(|path| collect_asset_files_from_path(path, project_path))
+            .collect();
+        serial.sort();
+        
+        assert_eq!(parallel.len(), 2000);
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_asset_manifest_lists_assets_as_json_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path();
+        fs::create_dir_all(project_root.join("assets/images")).unwrap();
+        fs::create_dir_all(project_root.join("assets/fonts")).unwrap();
+        fs::write(project_root.join("assets/images/logo.png"), [0u8; 16]).unwrap();
+        fs::write(project_root.join("assets/fonts/Roboto.ttf"), [0u8; 4]).unwrap();
+        fs::write(project_root.join("assets/config.json"), "{}").unwrap();
+        let asset_files = vec![
+            "assets/images/logo.png".to_string(),
+            "assets/fonts/Roboto.ttf".to_string(),
+            "assets/config.json".to_string(),
+        ];
+
+        let manifest = asset_manifest(&asset_files, project_root);
+        let entries: Vec<serde_json::Value> = manifest.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], serde_json::json!({"key": "assets/images/logo.png", "category": "images", "type": "image", "size": 16}));
+        assert_eq!(entries[1], serde_json::json!({"key": "assets/fonts/Roboto.ttf", "category": "fonts", "type": "font", "size": 4}));
+        assert_eq!(entries[2], serde_json::json!({"key": "assets/config.json", "category": null, "type": "data", "size": 2}));
+    }
+
+    #[test]
+    fn test_top_level_assets_are_generated() {
+        let asset_files = vec![
+            "assets/logo.png".to_string(),
+            "assets/config.json".to_string(),
+            "assets/images.png".to_string(),
+            "assets/images/icon.png".to_string(),
+        ];
+        
+        let dart_code = generate_dart_assets_class(&asset_files, DEFAULT_ASSETS_CLASS_NAME);
+        let main_class = dart_code.split("class Assets {").nth(1).unwrap().split("\n}\n").next().unwrap();
+        
+        assert!(main_class.contains("static const AssetGenImage logo = AssetGenImage('assets/logo.png');"));
+        assert!(main_class.contains("static const String config = 'assets/config.json';"));
+        assert!(main_class.contains("static const $AssetsImagesGen images = $AssetsImagesGen();"));
+        assert!(main_class.contains("static const AssetGenImage imagesFile = AssetGenImage('assets/images.png');"));
+    }
+    
     #[test]
     fn test_generate_dart_assets_class() {
         let asset_files = vec![
@@ -496,7 +856,7 @@ mod tests {
             "assets/data/sample.json".to_string(),
         ];
         
-        let dart_code = generate_dart_assets_class(&asset_files);
+        let dart_code = generate_dart_assets_class(&asset_files, DEFAULT_ASSETS_CLASS_NAME);
         
         assert!(dart_code.contains("class Assets"));
         assert!(dart_code.contains("class $AssetsImagesGen"));
@@ -504,6 +864,57 @@ mod tests {
         assert!(dart_code.contains("AssetGenImage"));
         assert!(dart_code.contains("assets/images/logo.png"));
         assert!(dart_code.contains("assets/data/sample.json"));
+        assert!(dart_code.contains(&format!("// SuperFastGen v{}", env!("CARGO_PKG_VERSION"))));
+    }
+
+    #[test]
+    fn test_generate_dart_assets_class_const_values() {
+        let asset_files = vec![
+            "assets/images/logo.png".to_string(),
+            "assets/data/sample.json".to_string(),
+        ];
+        
+        let dart_code = generate_dart_assets_class(&asset_files, DEFAULT_ASSETS_CLASS_NAME);
+        
+        assert!(dart_code.contains("static const List<AssetGenImage> _values = [AssetGenImage('assets/images/logo.png')];"));
+        assert!(dart_code.contains("List<AssetGenImage> get values => _values;"));
+        assert!(dart_code.contains("static const List<String> _values = ['assets/data/sample.json'];"));
+    }
+
+    #[test]
+    fn test_generate_dart_assets_class_mixed_category_values() {
+        let asset_files = vec![
+            "assets/misc/icon.png".to_string(),
+            "assets/misc/config.json".to_string(),
+        ];
+        
+        let dart_code = generate_dart_assets_class(&asset_files, DEFAULT_ASSETS_CLASS_NAME);
+        
+        assert!(dart_code.contains("  AssetGenImage get icon => const AssetGenImage('assets/misc/icon.png');"));
+        assert!(dart_code.contains("  String get config => 'assets/misc/config.json';"));
+        assert!(dart_code.contains("static const List<dynamic> _values = [AssetGenImage('assets/misc/icon.png'), 'assets/misc/config.json'];"));
+        assert!(dart_code.contains("List<dynamic> get values => _values;"));
+    }
+
+    #[test]
+    fn test_get_asset_type_image_formats() {
+        assert_eq!(get_asset_type("assets/images/photo.bmp"), "image");
+        assert_eq!(get_asset_type("assets/images/photo.WEBP"), "image");
+        assert_eq!(get_asset_type("assets/images/photo.avif"), "data");
+        assert_eq!(get_asset_type("assets/images/photo.heic"), "data");
+
+        let dart_code = generate_dart_assets_class(&["assets/misc/photo.bmp".to_string(), "assets/misc/blob.bin".to_string()], DEFAULT_ASSETS_CLASS_NAME);
+        assert!(dart_code.contains("  AssetGenImage get photo => const AssetGenImage('assets/misc/photo.bmp');"));
+        assert!(dart_code.contains("  String get blob => 'assets/misc/blob.bin';"));
+    }
+
+    #[test]
+    fn test_generate_dart_assets_class_custom_name() {
+        let dart_code = generate_dart_assets_class(&["assets/images/logo.png".to_string()], "MyAssets");
+        
+        assert!(dart_code.contains("class MyAssets {\n  const MyAssets._();\n"));
+        assert!(dart_code.contains("  static const $AssetsImagesGen images = $AssetsImagesGen();"));
+        assert!(!dart_code.contains("class Assets {"));
     }
 
     #[test]
@@ -520,6 +931,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_asset_constant_names_disambiguate_collisions() {
+        let files = vec![
+            "assets/images/icons/logo.png".to_string(),
+            "assets/images/brand/logo.png".to_string(),
+            "assets/images/banner.png".to_string(),
+        ];
+        assert_eq!(asset_constant_names(&files), vec!["iconsLogo", "brandLogo", "banner"]);
+        
+        let files = vec![
+            "assets/images/logo.png".to_string(),
+            "assets/images/logo.svg".to_string(),
+        ];
+        assert_eq!(asset_constant_names(&files), vec!["logo", "logo2"]);
+        
+        let dart_code = generate_dart_assets_class(&[
+            "assets/images/icons/logo.png".to_string(),
+            "assets/images/brand/logo.png".to_string(),
+        ], DEFAULT_ASSETS_CLASS_NAME);
+        assert!(dart_code.contains("AssetGenImage get iconsLogo =>"));
+        assert!(dart_code.contains("AssetGenImage get brandLogo =>"));
+    }
+
+    #[test]
+    fn test_asset_constant_names_are_valid_identifiers() {
+        assert_eq!(asset_file_to_constant_name_camel_case("assets/images/2x.png"), "images2x");
+        assert_eq!(asset_file_to_constant_name_camel_case("assets/images/123.png"), "images123");
+        assert_eq!(asset_file_to_constant_name_camel_case("assets/images/.hidden.png"), "hidden");
+        assert_eq!(asset_file_to_constant_name_camel_case("assets/data/class.json"), "dataClass");
+        assert_eq!(asset_file_to_constant_name_camel_case("assets/images/@@.png"), "");
+        
+        let dart_code = generate_dart_assets_class(&[
+            "assets/images/2x.png".to_string(),
+            "assets/images/@@.png".to_string(),
+        ], DEFAULT_ASSETS_CLASS_NAME);
+        assert!(dart_code.contains("AssetGenImage get images2x =>"));
+        assert!(!dart_code.contains("assets/images/@@.png"));
+    }
+
     #[test]
     fn test_parse_pubspec_yaml() {
         let yaml_content = r#"
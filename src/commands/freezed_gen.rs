@@ -2,6 +2,8 @@
 
 use std::path::{Path, PathBuf};
 use std::fs;
+use log::debug;
+use super::generate::{absolute_path, dart_generators, relative_import, to_pascal_case, DartGenerator, GenOptions, ANNOTATION_GAP};
 
 #[derive(Clone, Debug)]
 pub struct DartClass {
@@ -10,6 +12,14 @@ pub struct DartClass {
     pub file_path: PathBuf,
 }
 
+impl DartClass {
+    /// Base of the generated symbols (`_$User`, `_$$UserImplImpl`, `_$UserFromJson`): the name without
+    /// leading underscores, so a private `class _Internal with _$Internal` gets `_$Internal...` like with freezed
+    pub fn symbol_name(&self) -> &str {
+        self.name.trim_start_matches('_')
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DartField {
     pub name: String,
@@ -17,6 +27,8 @@ pub struct DartField {
     pub is_named: bool, // Added
     pub has_default: bool, // Added for @Default annotation
     pub default_value: Option<String>, // Added for @Default annotation value
+    pub omit_if_null: bool, // @JsonKey(includeIfNull: false): toJson leaves out the key when null
+    pub converter: Option<String>, // `@ColorConverter()`: JsonConverter instance used for this field, e.g. `const ColorConverter()`
 }
 
 #[derive(Clone, Debug)]
@@ -26,6 +38,8 @@ pub struct DartFunction {
     pub parameters: Vec<DartField>,
     pub annotations: Vec<String>,
     pub file_path: PathBuf,
+    pub is_getter: bool, // `@riverpod int get count => ...`: read without a call
+    pub source: String, // Declaration text, hashed into the provider's source hash
 }
 
 #[derive(Clone, Debug)]
@@ -34,30 +48,185 @@ pub struct CaseInfo {
     pub fields: Vec<DartField>,
 }
 
+#[derive(Debug, Default)]
 pub struct GenerationResult {
     pub freezed_code: String,
     pub g_dart_code: String,
 }
 
+/// An enum declared in a model file, with the value each constant serializes to
+#[derive(Clone, Debug, PartialEq)]
+pub struct DartEnum {
+    pub name: String,
+    pub values: Vec<DartEnumValue>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DartEnumValue {
+    pub name: String,
+    // Dart literal used in the enum map: the @JsonValue argument, or the quoted constant name
+    pub json_value: String,
+}
+
+/// Options read from `@Freezed(...)` arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreezedOptions {
+    pub to_string: bool,
+    pub equal: bool,
+    pub make_collections_unmodifiable: bool,
+    pub union_value_case: FreezedUnionCase,
+}
+
+impl Default for FreezedOptions {
+    fn default() -> Self {
+        FreezedOptions { to_string: true, equal: true, make_collections_unmodifiable: false, union_value_case: FreezedUnionCase::None }
+    }
+}
+
+/// freezed's `FreezedUnionCase`, applied to a union's discriminator values (`unionValueCase`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreezedUnionCase {
+    None,
+    Kebab,
+    Pascal,
+    Snake,
+    ScreamingSnake,
+}
+
+impl FreezedUnionCase {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().strip_prefix("FreezedUnionCase.")? {
+            "none" => Some(FreezedUnionCase::None),
+            "kebab" => Some(FreezedUnionCase::Kebab),
+            "pascal" => Some(FreezedUnionCase::Pascal),
+            "snake" => Some(FreezedUnionCase::Snake),
+            "screamingSnake" => Some(FreezedUnionCase::ScreamingSnake),
+            _ => None,
+        }
+    }
+
+    /// The discriminator value written for a case, e.g. `firstName` -> `first-name` in kebab case
+    pub fn apply(self, case_name: &str) -> String {
+        let words = || {
+            let mut words: Vec<String> = Vec::new();
+            for ch in case_name.chars() {
+                match words.last_mut() {
+                    Some(word) if !ch.is_uppercase() && ch != '_' => word.push(ch),
+                    _ if ch == '_' => words.push(String::new()),
+                    _ => words.push(ch.to_string()),
+                }
+            }
+            words.retain(|word| !word.is_empty());
+            words
+        };
+        let lower_words = || words().iter().map(|word| word.to_lowercase()).collect::<Vec<_>>();
+        match self {
+            FreezedUnionCase::None => case_name.to_string(),
+            FreezedUnionCase::Kebab => lower_words().join("-"),
+            FreezedUnionCase::Snake => lower_words().join("_"),
+            FreezedUnionCase::ScreamingSnake => lower_words().join("_").to_uppercase(),
+            FreezedUnionCase::Pascal => to_pascal_case(case_name),
+        }
+    }
+}
+
+impl FreezedOptions {
+    /// Read options from the `@Freezed(...)` annotation; `@freezed` keeps the defaults
+    pub fn from_annotations(annotations: &[String]) -> Self {
+        let mut options = FreezedOptions::default();
+        for annotation in annotations.iter().filter(|ann| is_freezed_annotation(ann)) {
+            let Some(args) = annotation_arguments(annotation) else { continue };
+            for arg in args.split(',') {
+                let Some((key, value)) = arg.split_once(':') else { continue };
+                let enabled = value.trim() != "false";
+                match key.trim() {
+                    "toStringOverride" => options.to_string = enabled,
+                    "equal" => options.equal = enabled,
+                    "makeCollectionsUnmodifiable" => options.make_collections_unmodifiable = enabled,
+                    "unionValueCase" => {
+                        if let Some(union_case) = FreezedUnionCase::parse(value) {
+                            options.union_value_case = union_case;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        options
+    }
+}
+
+/// The freezed view that exposes a `List`/`Map`/`Set` field unmodifiably, if the type is a collection
+fn unmodifiable_view_type(ty: &str) -> Option<&'static str> {
+    let ty = ty.trim_end_matches('?');
+    if ty.starts_with("List<") {
+        Some("EqualUnmodifiableListView")
+    } else if ty.starts_with("Map<") {
+        Some("EqualUnmodifiableMapView")
+    } else if ty.starts_with("Set<") {
+        Some("EqualUnmodifiableSetView")
+    } else {
+        None
+    }
+}
+
+// Private backing field plus a getter returning an unmodifiable view (makeCollectionsUnmodifiable)
+fn generate_unmodifiable_field(field: &DartField, code: &mut String) {
+    let view = unmodifiable_view_type(&field.ty).unwrap_or("EqualUnmodifiableListView");
+    code.push_str(&format!("  final {} _{};\n", field.ty, field.name));
+    code.push_str("  @override\n");
+    code.push_str(&format!("  {} get {} {{\n", field.ty, field.name));
+    if field.ty.ends_with('?') {
+        code.push_str(&format!("    final value = _{};\n", field.name));
+        code.push_str("    if (value == null) return null;\n");
+        code.push_str(&format!("    if (_{} is {}) return _{};\n", field.name, view, field.name));
+        code.push_str(&format!("    return {}(value);\n", view));
+    } else {
+        code.push_str(&format!("    if (_{} is {}) return _{};\n", field.name, view, field.name));
+        code.push_str(&format!("    return {}(_{});\n", view, field.name));
+    }
+    code.push_str("  }\n\n");
+}
+
+/// Name of an annotation without its arguments, e.g. `@Freezed(equal: false)` -> `@Freezed`
+pub fn annotation_name(annotation: &str) -> &str {
+    let annotation = annotation.trim();
+    annotation.split('(').next().unwrap_or(annotation).trim()
+}
+
+/// Arguments of an annotation without the surrounding parentheses, if it has any
+pub fn annotation_arguments(annotation: &str) -> Option<&str> {
+    let start = annotation.find('(')?;
+    let end = annotation.rfind(')')?;
+    (end > start).then(|| &annotation[start + 1..end])
+}
+
+/// Whether the annotation is `@freezed` or `@Freezed(...)`
+pub fn is_freezed_annotation(annotation: &str) -> bool {
+    matches!(annotation_name(annotation), "@freezed" | "@Freezed")
+}
+
 // --- Freezed/JsonSerializable code generation functions ---
 
-/// Writes the _privateConstructorUsedError only once per file.
-pub fn generate_freezed_file(file_path: &Path, classes: &[DartClass]) -> Option<GenerationResult> {
+/// Writes the _privateConstructorUsedError only once per file. source_content is the text of file_path.
+/// output_dir is where the generated parts are written; `part of` points from there back to file_path.
+pub fn generate_freezed_file(file_path: &Path, source_content: &str, classes: &[DartClass], output_dir: &Path, options: &GenOptions) -> Option<GenerationResult> {
     let mut freezed_code = String::new();
     let mut g_dart_code = String::new();
 
     // デバッグ: クラス一覧を出力
-    eprintln!("[DEBUG] classes to generate: {:?}", classes.iter().map(|c| &c.name).collect::<Vec<_>>());
+    debug!("classes to generate: {:?}", classes.iter().map(|c| &c.name).collect::<Vec<_>>());
 
     // Add Dart official header comments
     freezed_code.push_str("// coverage:ignore-file\n");
     freezed_code.push_str("// GENERATED CODE - DO NOT MODIFY BY HAND\n");
+    freezed_code.push_str(&format!("{}\n", super::GENERATOR_SIGNATURE));
     freezed_code.push_str("// ignore_for_file: type=lint\n");
     freezed_code.push_str("// ignore_for_file: unused_element, deprecated_member_use, deprecated_member_use_from_same_package, use_function_type_syntax_for_parameters, unnecessary_const, avoid_init_to_null, invalid_override_different_default_values_named, prefer_expression_function_bodies, annotate_overrides, invalid_annotation_target, unnecessary_question_mark\n\n");
     
     // Add part of directive
-    let file_stem = file_path.file_stem().unwrap().to_string_lossy();
-    freezed_code.push_str(&format!("part of '{}';\n\n", format!("{}.dart", file_stem)));
+    let part_of = part_of_path(file_path, output_dir);
+    freezed_code.push_str(&format!("part of '{}';\n\n", part_of));
     
     // Note: imports are not allowed in part files
     
@@ -73,42 +242,56 @@ pub fn generate_freezed_file(file_path: &Path, classes: &[DartClass]) -> Option<
     freezed_code.push_str("final _privateConstructorUsedError = UnsupportedError(\n");
     freezed_code.push_str("    'It seems like you constructed your class using `MyClass._()`. This constructor is only meant to be used by freezed and you are not supposed to need it nor use it.\\nPlease check the documentation here for more information: https://github.com/rrousselGit/freezed#adding-getters-and-methods-to-our-models');\n\n");
 
-    // クラスごとにfreezed_codeとg_dart_codeを分離してpush
+    // Class.fromJson can only be declared in the user's source (a mixin can't add factories);
+    // freezed models without it are generated without JSON serialization
+    for class in classes {
+        if !declares_from_json_factory(source_content, &class.name) {
+            debug!("{} has no fromJson factory; generating it without JSON serialization", class.name);
+        }
+    }
+
+    // Each class goes through every generator that applies to it (the built-in one, then registered ones)
+    let generators = dart_generators();
+    let mut json_code = String::new();
+    let mut processed_classes = std::collections::HashSet::new();
     for class in classes {
-        let class_code = generate_freezed_code(class);
-        eprintln!("[DEBUG] Generated {} bytes for class: {}", class_code.len(), class.name);
-        eprintln!("[DEBUG] Class code preview: {}", &class_code[..class_code.len().min(200)]);
-        // freezed_codeにはクラス定義・mixin・copyWith・抽象クラスのみ
-        freezed_code.push_str(&class_code);
+        if !processed_classes.insert(class.name.clone()) {
+            continue;
+        }
+        let fields = extract_fields_from_dart_class(source_content, &class.name);
+        for generator in generators.iter().filter(|generator| generator.matches(class)) {
+            let result = generator.generate(class, source_content, &fields, options);
+            debug!("{} generated {} bytes for class: {}", generator.annotation(), result.freezed_code.len(), class.name);
+            // freezed_codeにはクラス定義・mixin・copyWith・抽象クラスのみ
+            freezed_code.push_str(&result.freezed_code);
+            json_code.push_str(&result.g_dart_code);
+        }
     }
     
     // Generate .g.dart content
-    let file_stem = file_path.file_stem().unwrap().to_string_lossy();
-    g_dart_code.push_str("// GENERATED CODE - DO NOT MODIFY BY HAND\n\n");
-    g_dart_code.push_str(&format!("part of '{}';\n\n", format!("{}.dart", file_stem)));
+    g_dart_code.push_str("// GENERATED CODE - DO NOT MODIFY BY HAND\n");
+    g_dart_code.push_str(&format!("{}\n\n", super::GENERATOR_SIGNATURE));
+    g_dart_code.push_str(&format!("part of '{}';\n\n", part_of));
     g_dart_code.push_str("// **************************************************************************\n");
     g_dart_code.push_str("// JsonSerializableGenerator\n");
     g_dart_code.push_str("// **************************************************************************\n\n");
-    
-    // Generate JSON serialization code for each class
-    let mut processed_classes = std::collections::HashSet::new();
-    for class in classes {
-        if !processed_classes.contains(&class.name) {
-            eprintln!("[DEBUG] Generating JSON code for class: {}", class.name);
-            g_dart_code.push_str(&generate_json_code(class));
-            processed_classes.insert(class.name.clone());
+    g_dart_code.push_str(&json_code);
+    // Enum maps for enums used by the classes' fields (once per file)
+    let enums = extract_enums_from_dart_source(source_content);
+    for dart_enum in &enums {
+        let is_used = classes.iter().any(|class| {
+            let mut fields = extract_fields_from_dart_class(source_content, &class.name);
+            fields.extend(extract_union_cases_from_dart_class(source_content, &class.name).into_iter().flat_map(|case| case.fields));
+            fields.iter().any(|field| find_enum(field, std::slice::from_ref(dart_enum)).or_else(|| find_enum_list(field, std::slice::from_ref(dart_enum)).map(|(found, _)| found)).is_some())
+        });
+        if is_used {
+            g_dart_code.push_str(&generate_enum_map(dart_enum));
         }
     }
-    // 末尾の空行を1つだけにする
-    while g_dart_code.ends_with("\n\n") {
-        g_dart_code.pop();
-    }
-    if !g_dart_code.ends_with("\n") {
-        g_dart_code.push('\n');
-    }
+    // Indentation and the trailing newline are settled by finish_generated_code when the file is written
     
-    eprintln!("[DEBUG] Generated freezed code preview: {}", &freezed_code[..freezed_code.len().min(500)]);
-    eprintln!("[DEBUG] Total freezed code length: {} bytes", freezed_code.len());
+    debug!("Generated freezed code preview: {}", &freezed_code[..freezed_code.len().min(500)]);
+    debug!("Total freezed code length: {} bytes", freezed_code.len());
     
     Some(GenerationResult {
         freezed_code,
@@ -116,39 +299,79 @@ pub fn generate_freezed_file(file_path: &Path, classes: &[DartClass]) -> Option<
     })
 }
 
-pub fn generate_freezed_code(class: &DartClass) -> String {
-    eprintln!("[DEBUG] generate_freezed_code called for class: {}", class.name);
+/// The built-in generator for `@freezed`/`@Freezed(...)` and `@JsonSerializable` models
+pub struct FreezedGenerator;
+
+impl DartGenerator for FreezedGenerator {
+    fn annotation(&self) -> &str {
+        "@freezed"
+    }
+
+    fn generate(&self, class: &DartClass, source: &str, _fields: &[DartField], options: &GenOptions) -> GenerationResult {
+        if class.annotations.iter().any(|ann| is_freezed_annotation(ann)) {
+            let g_dart_code = if declares_from_json_factory(source, &class.name) { generate_json_code(class, source, options) } else { String::new() };
+            return GenerationResult {
+                freezed_code: generate_freezed_code(class, source, options),
+                g_dart_code,
+            };
+        }
+        // Plain @JsonSerializable classes only get their serialization functions
+        let mut g_dart_code = generate_plain_json_code(class, source, options);
+        if options.copywith {
+            g_dart_code.push_str(&generate_json_copy_with(class, &extract_plain_class_fields(source, &class.name)));
+        }
+        GenerationResult {
+            freezed_code: String::new(),
+            g_dart_code,
+        }
+    }
+
+    fn matches(&self, class: &DartClass) -> bool {
+        class.annotations.iter().any(|ann| is_freezed_annotation(ann) || annotation_name(ann) == "@JsonSerializable")
+    }
+}
+
+pub fn generate_freezed_code(class: &DartClass, source_content: &str, options: &GenOptions) -> String {
+    debug!("generate_freezed_code called for class: {}", class.name);
     let mut code = String::new();
-    let source_content = std::fs::read_to_string(&class.file_path).unwrap_or_default();
-    let union_cases = extract_union_cases_from_dart_class(&source_content, &class.name);
-    let fields = extract_fields_from_dart_class(&source_content, &class.name);
-    eprintln!("[DEBUG] Extracted {} fields for {}", fields.len(), class.name);
-    eprintln!("[DEBUG] Extracted {} union cases for {}", union_cases.len(), class.name);
+    let union_cases = extract_union_cases_from_dart_class(source_content, &class.name);
+    let (fields, is_const) = extract_constructor_from_dart_class(source_content, &class.name);
+    let json = declares_from_json_factory(source_content, &class.name);
+    debug!("Extracted {} fields for {}", fields.len(), class.name);
+    debug!("Extracted {} union cases for {}", union_cases.len(), class.name);
     
 
     
     // Check if this is a union type (sealed class)
     if !union_cases.is_empty() {
         // Generate union type code
-        generate_union_type_code(&mut code, class, &union_cases, &fields);
+        generate_union_type_code(&mut code, class, &union_cases, &fields, source_content, json, options);
     } else {
         // Generate regular class code
-        generate_regular_class_code(class, &fields, &union_cases, &mut code);
+        let asserts = extract_constructor_asserts(source_content, &class.name);
+        generate_regular_class_code(class, &fields, is_const, json, &asserts, &mut code);
     }
     
     code
 }
 
-fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_cases: &[CaseInfo], code: &mut String) {
+// is_const mirrors whether the source factory is `const`; mutable models can't use const constructors
+// json is false for models without a fromJson factory: no JSON functions and no @JsonKey annotations
+// asserts are the `assert(...)` calls declared with @Assert on the factory, run in the impl's initializer list
+fn generate_regular_class_code(class: &DartClass, fields: &[DartField], is_const: bool, json: bool, asserts: &[String], code: &mut String) {
+    let const_keyword = if is_const { "const " } else { "" };
+    let json_key_ignore = if json { JSON_KEY_IGNORE } else { "" };
 
     // Add top-level fromJson function
-    code.push_str(&format!("{} _${}FromJson(Map<String, dynamic> json) {{\n", class.name, class.name));
-    code.push_str(&format!("  return _${}Impl.fromJson(json);\n", class.name));
-    code.push_str("}\n\n");
+    if json {
+        code.push_str(&format!("{} _${}FromJson(Map<String, dynamic> json) {{\n", class.name, class.symbol_name()));
+        code.push_str(&format!("  return _${}Impl.fromJson(json);\n", class.symbol_name()));
+        code.push_str("}\n\n");
+    }
     
     // Add mixin _$Event
     code.push_str("/// @nodoc\n");
-    code.push_str(&format!("mixin _${} {{\n", class.name));
+    code.push_str(&format!("mixin _${} {{\n", class.symbol_name()));
     
     // Add getters for all fields
     for field in fields {
@@ -157,25 +380,27 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
     code.push_str("\n");
     
     // Add toJson method
-    code.push_str("  /// Serializes this ");
-    code.push_str(&class.name);
-    code.push_str(" to a JSON map.\n");
-    code.push_str("  Map<String, dynamic> toJson() => throw _privateConstructorUsedError;\n\n");
+    if json {
+        code.push_str("  /// Serializes this ");
+        code.push_str(&class.name);
+        code.push_str(" to a JSON map.\n");
+        code.push_str("  Map<String, dynamic> toJson() => throw _privateConstructorUsedError;\n\n");
+    }
     
     // Add copyWith method
     code.push_str("  /// Create a copy of ");
     code.push_str(&class.name);
     code.push_str("\n");
     code.push_str("  /// with the given fields replaced by the non-null parameter values.\n");
-    code.push_str("  @JsonKey(includeFromJson: false, includeToJson: false)\n");
-    code.push_str(&format!("  ${}CopyWith<{}> get copyWith => throw _privateConstructorUsedError;\n", class.name, class.name));
+    code.push_str(json_key_ignore);
+    code.push_str(&format!("  ${}CopyWith<{}> get copyWith => throw _privateConstructorUsedError;\n", class.symbol_name(), class.name));
     code.push_str("}\n\n");
     
     // Generate $ClassCopyWith abstract class
     code.push_str("/// @nodoc\n");
-    code.push_str(&format!("abstract class ${}CopyWith<$Res> {{\n", class.name));
-    code.push_str(&format!("  factory ${}CopyWith({} value, $Res Function({}) then) =\n", class.name, class.name, class.name));
-    code.push_str(&format!("      _${}CopyWithImpl<$Res, {}>;\n", class.name, class.name));
+    code.push_str(&format!("abstract class ${}CopyWith<$Res> {{\n", class.symbol_name()));
+    code.push_str(&format!("  factory ${}CopyWith({} value, $Res Function({}) then) =\n", class.symbol_name(), class.name, class.name));
+    code.push_str(&format!("      _${}CopyWithImpl<$Res, {}>;\n", class.symbol_name(), class.name));
     code.push_str("  @useResult\n");
     code.push_str(&format!("  $Res call({{"));
     for field in fields {
@@ -186,9 +411,9 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
     
     // Generate _$ClassCopyWithImpl class
     code.push_str("/// @nodoc\n");
-    code.push_str(&format!("class _${}CopyWithImpl<$Res, $Val extends {}>\n", class.name, class.name));
-    code.push_str(&format!("    implements ${}CopyWith<$Res> {{\n", class.name));
-    code.push_str(&format!("  _${}CopyWithImpl(this._value, this._then);\n", class.name));
+    code.push_str(&format!("class _${}CopyWithImpl<$Res, $Val extends {}>\n", class.symbol_name(), class.name));
+    code.push_str(&format!("    implements ${}CopyWith<$Res> {{\n", class.symbol_name()));
+    code.push_str(&format!("  _${}CopyWithImpl(this._value, this._then);\n", class.symbol_name()));
     code.push_str("\n");
     code.push_str("  // ignore: unused_field\n");
     code.push_str("  final $Val _value;\n");
@@ -235,10 +460,10 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
     
     // Generate _$$$ClassImplImplCopyWith abstract class
     code.push_str("/// @nodoc\n");
-    code.push_str(&format!("abstract class _$$${}ImplImplCopyWith<$Res> implements ${}CopyWith<$Res> {{\n", class.name, class.name));
-    code.push_str(&format!("  factory _$$${}ImplImplCopyWith(\n", class.name));
-    code.push_str(&format!("          _$${}ImplImpl value, $Res Function(_$${}ImplImpl) then) =\n", class.name, class.name));
-    code.push_str(&format!("      __$$${}ImplImplCopyWithImpl<$Res>;\n", class.name));
+    code.push_str(&format!("abstract class _$$${}ImplImplCopyWith<$Res> implements ${}CopyWith<$Res> {{\n", class.symbol_name(), class.symbol_name()));
+    code.push_str(&format!("  factory _$$${}ImplImplCopyWith(\n", class.symbol_name()));
+    code.push_str(&format!("          _$${}ImplImpl value, $Res Function(_$${}ImplImpl) then) =\n", class.symbol_name(), class.symbol_name()));
+    code.push_str(&format!("      __$$${}ImplImplCopyWithImpl<$Res>;\n", class.symbol_name()));
     code.push_str("  @override\n");
     code.push_str("  @useResult\n");
     code.push_str(&format!("  $Res call({{"));
@@ -260,11 +485,11 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
     
     // Generate __$$$ClassImplImplCopyWithImpl class
     code.push_str("/// @nodoc\n");
-    code.push_str(&format!("class __$$${}ImplImplCopyWithImpl<$Res>\n", class.name));
-    code.push_str(&format!("    extends _${}CopyWithImpl<$Res, _$${}ImplImpl>\n", class.name, class.name));
-    code.push_str(&format!("    implements _$$${}ImplImplCopyWith<$Res> {{\n", class.name));
-    code.push_str(&format!("  __$$${}ImplImplCopyWithImpl(\n", class.name));
-    code.push_str(&format!("      _$${}ImplImpl _value, $Res Function(_$${}ImplImpl) _then)\n", class.name, class.name));
+    code.push_str(&format!("class __$$${}ImplImplCopyWithImpl<$Res>\n", class.symbol_name()));
+    code.push_str(&format!("    extends _${}CopyWithImpl<$Res, _$${}ImplImpl>\n", class.symbol_name(), class.symbol_name()));
+    code.push_str(&format!("    implements _$$${}ImplImplCopyWith<$Res> {{\n", class.symbol_name()));
+    code.push_str(&format!("  __$$${}ImplImplCopyWithImpl(\n", class.symbol_name()));
+    code.push_str(&format!("      _$${}ImplImpl _value, $Res Function(_$${}ImplImpl) _then)\n", class.symbol_name(), class.symbol_name()));
     code.push_str("      : super(_value, _then);\n");
     code.push_str("\n");
     code.push_str("  /// Create a copy of ");
@@ -288,15 +513,17 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
         code.push_str(&format!("\n    {} {} = {},", field_type, field.name, default_value));
     }
     code.push_str("\n  }) {\n");
-    code.push_str(&format!("    return _then(_$${}ImplImpl(\n", class.name));
+    code.push_str(&format!("    return _then(_$${}ImplImpl(\n", class.symbol_name()));
     for field in fields {
+        // Positional fields are passed without a label
+        let label = if field.is_named { format!("{}: ", field.name) } else { String::new() };
         if field.ty.ends_with('?') {
-            code.push_str(&format!("      {}: freezed == {}\n", field.name, field.name));
+            code.push_str(&format!("      {}freezed == {}\n", label, field.name));
             code.push_str(&format!("          ? _value.{}\n", field.name));
             code.push_str(&format!("          : {} // ignore: cast_nullable_to_non_nullable\n", field.name));
             code.push_str(&format!("              as {},\n", field.ty));
         } else {
-            code.push_str(&format!("      {}: null == {}\n", field.name, field.name));
+            code.push_str(&format!("      {}null == {}\n", label, field.name));
             code.push_str(&format!("          ? _value.{}\n", field.name));
             code.push_str(&format!("          : {} // ignore: cast_nullable_to_non_nullable\n", field.name));
             code.push_str(&format!("              as {},\n", field.ty));
@@ -309,121 +536,152 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
     // Generate _$$ClassImplImpl class
     code.push_str("/// @nodoc\n");
     code.push_str("@JsonSerializable()\n");
-    code.push_str(&format!("class _$${}ImplImpl implements _${}Impl {{\n", class.name, class.name));
+    code.push_str(&format!("class _$${}ImplImpl implements _${}Impl {{\n", class.symbol_name(), class.symbol_name()));
     
+    let options = FreezedOptions::from_annotations(&class.annotations);
+    // Collections stored privately and exposed as unmodifiable views; the initializer list keeps the constructor const
+    let wrapped = |field: &DartField| options.make_collections_unmodifiable && unmodifiable_view_type(&field.ty).is_some();
+    let constructor_param = |field: &DartField| {
+        if wrapped(field) {
+            format!("final {} {}", field.ty, field.name)
+        } else {
+            format!("this.{}", field.name)
+        }
+    };
+
     // Generate constructor
-    code.push_str(&format!("  const _$${}ImplImpl(\n", class.name));
-    code.push_str("      {");
-    for field in fields {
+    let positional_fields: Vec<&DartField> = fields.iter().filter(|f| !f.is_named).collect();
+    let named_fields: Vec<&DartField> = fields.iter().filter(|f| f.is_named).collect();
+    code.push_str(&format!("  {}_$${}ImplImpl(\n", const_keyword, class.symbol_name()));
+    code.push_str("      ");
+    let positional_params: Vec<String> = positional_fields.iter().map(|f| constructor_param(f)).collect();
+    code.push_str(&positional_params.join(", "));
+    if !named_fields.is_empty() {
+        if !positional_fields.is_empty() {
+            code.push_str(", ");
+        }
+        code.push('{');
+    }
+    for field in &named_fields {
+        let param = constructor_param(field);
         if field.ty.ends_with('?') {
-            code.push_str(&format!("this.{},", field.name));
+            code.push_str(&format!("{},", param));
         } else if field.has_default {
             if let Some(default_val) = &field.default_value {
-                // For list fields with default, use const
-                if field.ty.contains("List<") && default_val == "[]" {
-                    code.push_str(&format!("this.{} = const {},", field.name, default_val));
-                } else {
-                    code.push_str(&format!("this.{} = {},", field.name, default_val));
-                }
+                code.push_str(&format!("{} = {},", param, const_default_value(default_val)));
             } else {
-                code.push_str(&format!("this.{},", field.name));
+                code.push_str(&format!("{},", param));
             }
         } else {
-            code.push_str(&format!("required this.{},", field.name));
+            code.push_str(&format!("required {},", param));
         }
     }
-    code.push_str("});\n\n");
+    if !named_fields.is_empty() {
+        code.push('}');
+    }
+    code.push(')');
+    let initializers: Vec<String> = asserts.iter().cloned()
+        .chain(fields.iter().filter(|f| wrapped(f)).map(|f| format!("_{} = {}", f.name, f.name)))
+        .collect();
+    if !initializers.is_empty() {
+        code.push_str(&format!("\n      : {}", initializers.join(",\n        ")));
+    }
+    code.push_str(";\n\n");
     
     // fromJson factory
-    code.push_str(&format!("  factory _$${}ImplImpl.fromJson(Map<String, dynamic> json) =>\n", class.name));
-    code.push_str(&format!("      _$${}ImplImplFromJson(json);\n\n", class.name));
+    if json {
+        code.push_str(&format!("  factory _$${}ImplImpl.fromJson(Map<String, dynamic> json) =>\n", class.symbol_name()));
+        code.push_str(&format!("      _$${}ImplImplFromJson(json);\n\n", class.symbol_name()));
+    }
     
     // Generate fields
     for field in fields {
+        if wrapped(field) {
+            generate_unmodifiable_field(field, code);
+            continue;
+        }
         code.push_str(&format!("  @override\n"));
         code.push_str(&format!("  final {} {};\n", field.ty, field.name));
     }
     code.push_str("\n");
-    
+
     // toString method
-    code.push_str("  @override\n");
-    code.push_str("  String toString() {\n");
-    let field_names: Vec<String> = fields.iter().map(|f| format!("{}: ${}", f.name, f.name)).collect();
-    code.push_str(&format!("    return '{}({})';\n", class.name, field_names.join(", ")));
-    code.push_str("  }\n\n");
-    
-    // equality operator
-    code.push_str("  @override\n");
-    code.push_str("  bool operator ==(Object other) {\n");
-    code.push_str("    return identical(this, other) ||\n");
-    code.push_str(&format!("        (other.runtimeType == runtimeType &&\n"));
-    code.push_str(&format!("            other is _$${}ImplImpl &&\n", class.name));
-    for field in fields {
-        if field.name == "tags" {
-            code.push_str(&format!("            const DeepCollectionEquality().equals(other.tags, tags) &&\n"));
-        } else if field.name == "attendees" {
-            code.push_str(&format!("            const DeepCollectionEquality()\n"));
-            code.push_str(&format!("                .equals(other.attendees, attendees) &&\n"));
-        } else {
-            code.push_str(&format!("            (identical(other.{}, {}) || other.{} == {}) &&\n", field.name, field.name, field.name, field.name));
-        }
-    }
-    // Remove the last " &&" and add closing parenthesis
-    if code.ends_with(" &&\n") {
-        code.truncate(code.len() - 4);
+    if options.to_string {
+        code.push_str("  @override\n");
+        code.push_str("  String toString() {\n");
+        let field_names: Vec<String> = fields.iter().map(|f| format!("{}: ${}", f.name, f.name)).collect();
+        code.push_str(&format!("    return '{}({})';\n", class.name, field_names.join(", ")));
+        code.push_str("  }\n\n");
     }
-    code.push_str(");\n");
-    code.push_str("  }\n\n");
     
-    // hashCode
-    code.push_str("  @JsonKey(includeFromJson: false, includeToJson: false)\n");
-    code.push_str("  @override\n");
-    code.push_str("  int get hashCode => Object.hash(\n");
-    code.push_str("      runtimeType,\n");
-    for field in fields {
-        if field.name == "tags" {
-            code.push_str(&format!("      const DeepCollectionEquality().hash(tags),\n"));
-        } else if field.name == "attendees" {
-            code.push_str(&format!("      const DeepCollectionEquality().hash(attendees),\n"));
-        } else {
-            code.push_str(&format!("      {},\n", field.name));
+    // equality operator and hashCode
+    if options.equal {
+        code.push_str("  @override\n");
+        code.push_str("  bool operator ==(Object other) {\n");
+        code.push_str("    return identical(this, other) ||\n");
+        code.push_str(&format!("        (other.runtimeType == runtimeType &&\n"));
+        let mut clauses = vec![format!("other is _$${}ImplImpl", class.symbol_name())];
+        for field in fields {
+            if is_collection_type(&field.ty) {
+                clauses.push(format!("const DeepCollectionEquality().equals(other.{}, {})", field.name, field.name));
+            } else {
+                clauses.push(format!("(identical(other.{}, {}) || other.{} == {})", field.name, field.name, field.name, field.name));
+            }
         }
+        code.push_str("            ");
+        code.push_str(&clauses.join(" &&\n            "));
+        code.push_str(");\n");
+        code.push_str("  }\n\n");
+    
+        // hashCode
+        code.push_str(json_key_ignore);
+        code.push_str("  @override\n");
+        code.push_str(&hash_code_getter(fields));
     }
-    code.push_str("  );\n\n");
     
     // copyWith method
     code.push_str("  /// Create a copy of ");
     code.push_str(&class.name);
     code.push_str("\n");
     code.push_str("  /// with the given fields replaced by the non-null parameter values.\n");
-    code.push_str("  @JsonKey(includeFromJson: false, includeToJson: false)\n");
+    code.push_str(json_key_ignore);
     code.push_str("  @override\n");
     code.push_str("  @pragma('vm:prefer-inline')\n");
-    code.push_str(&format!("  _$$${}ImplImplCopyWith<_$${}ImplImpl> get copyWith =>\n", class.name, class.name));
-    code.push_str(&format!("      __$$${}ImplImplCopyWithImpl<_$${}ImplImpl>(this, _$identity);\n", class.name, class.name));
-    code.push_str("\n");
-    code.push_str("  @override\n");
-    code.push_str("  Map<String, dynamic> toJson() {\n");
-    code.push_str(&format!("    return _$${}ImplImplToJson(\n", class.name));
-    code.push_str("      this,\n");
-    code.push_str("    );\n");
-    code.push_str("  }\n");
+    code.push_str(&format!("  _$$${}ImplImplCopyWith<_$${}ImplImpl> get copyWith =>\n", class.symbol_name(), class.symbol_name()));
+    code.push_str(&format!("      __$$${}ImplImplCopyWithImpl<_$${}ImplImpl>(this, _$identity);\n", class.symbol_name(), class.symbol_name()));
+    if json {
+        code.push_str("\n");
+        code.push_str("  @override\n");
+        code.push_str("  Map<String, dynamic> toJson() {\n");
+        code.push_str(&format!("    return _$${}ImplImplToJson(\n", class.symbol_name()));
+        code.push_str("      this,\n");
+        code.push_str("    );\n");
+        code.push_str("  }\n");
+    }
     code.push_str("}\n\n");
     
     // Generate abstract class _$EventImpl
-    code.push_str(&format!("abstract class _${}Impl implements {} {{\n", class.name, class.name));
-    code.push_str(&format!("  const factory _${}Impl(\n", class.name));
-    code.push_str("    {\n");
-    for field in fields {
-        if field.ty.ends_with('?') || field.has_default {
-            code.push_str(&format!("      final {} {},\n", field.ty, field.name));
-        } else {
-            code.push_str(&format!("      required final {} {},\n", field.ty, field.name));
+    code.push_str(&format!("abstract class _${}Impl implements {} {{\n", class.symbol_name(), class.name));
+    code.push_str(&format!("  {}factory _${}Impl(\n", const_keyword, class.symbol_name()));
+    for field in &positional_fields {
+        code.push_str(&format!("    final {} {},\n", field.ty, field.name));
+    }
+    if !named_fields.is_empty() {
+        code.push_str("    {\n");
+        for field in &named_fields {
+            if field.ty.ends_with('?') || field.has_default {
+                code.push_str(&format!("      final {} {},\n", field.ty, field.name));
+            } else {
+                code.push_str(&format!("      required final {} {},\n", field.ty, field.name));
+            }
         }
+        code.push_str("    }\n");
+    }
+    code.push_str(&format!("  ) = _$${}ImplImpl;\n\n", class.symbol_name()));
+    if json {
+        code.push_str(&format!("  factory _${}Impl.fromJson(Map<String, dynamic> json) =\n", class.symbol_name()));
+        code.push_str(&format!("      _$${}ImplImpl.fromJson;\n\n", class.symbol_name()));
     }
-    code.push_str(&format!("    }}\n  ) = _$${}ImplImpl;\n\n", class.name));
-    code.push_str(&format!("  factory _${}Impl.fromJson(Map<String, dynamic> json) =\n", class.name));
-    code.push_str(&format!("      _$${}ImplImpl.fromJson;\n\n", class.name));
     
     for field in fields {
         code.push_str(&format!("  @override\n"));
@@ -436,114 +694,65 @@ fn generate_regular_class_code(class: &DartClass, fields: &[DartField], _union_c
     code.push_str("\n");
     code.push_str("  /// with the given fields replaced by the non-null parameter values.\n");
     code.push_str("  @override\n");
-    code.push_str("  @JsonKey(includeFromJson: false, includeToJson: false)\n");
-    code.push_str(&format!("  _$$${}ImplImplCopyWith<_$${}ImplImpl> get copyWith =>\n", class.name, class.name));
+    code.push_str(json_key_ignore);
+    code.push_str(&format!("  _$$${}ImplImplCopyWith<_$${}ImplImpl> get copyWith =>\n", class.symbol_name(), class.symbol_name()));
     code.push_str("      throw _privateConstructorUsedError;\n");
     code.push_str("}\n\n");
     
 
 }
 
-fn generate_union_type_code(code: &mut String, class: &DartClass, union_cases: &[CaseInfo], fields: &[DartField]) {
+fn generate_union_type_code(code: &mut String, class: &DartClass, union_cases: &[CaseInfo], fields: &[DartField], source_content: &str, json: bool, options: &GenOptions) {
+    let json_key_ignore = if json { JSON_KEY_IGNORE } else { "" };
+    let union_value_case = FreezedOptions::from_annotations(&class.annotations).union_value_case;
     // Generate mixin with all the required methods
     code.push_str("/// @nodoc\n");
-    code.push_str(&format!("mixin _${} {{\n", class.name));
-    
-    // Generate when method
-    code.push_str("  @optionalTypeArgs\n");
-    code.push_str("  TResult when<TResult extends Object?>({\n");
-    for case in union_cases {
-        if case.fields.is_empty() {
-            code.push_str(&format!("    required TResult Function() {},\n", case.case_name));
-        } else {
-            let params: Vec<String> = case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-            code.push_str(&format!("    required TResult Function({}) {},\n", params.join(", "), case.case_name));
-        }
-    }
-    code.push_str("  }) => throw _privateConstructorUsedError;\n");
-    
-    // Generate whenOrNull method
-    code.push_str("  @optionalTypeArgs\n");
-    code.push_str("  TResult? whenOrNull<TResult extends Object?>({\n");
-    for case in union_cases {
-        if case.fields.is_empty() {
-            code.push_str(&format!("    TResult? Function()? {},\n", case.case_name));
-        } else {
-            let params: Vec<String> = case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-            code.push_str(&format!("    TResult? Function({})? {},\n", params.join(", "), case.case_name));
-        }
-    }
-    code.push_str("  }) => throw _privateConstructorUsedError;\n");
-    
-    // Generate maybeWhen method
-    code.push_str("  @optionalTypeArgs\n");
-    code.push_str("  TResult maybeWhen<TResult extends Object?>({\n");
-    for case in union_cases {
-        if case.fields.is_empty() {
-            code.push_str(&format!("    TResult Function()? {},\n", case.case_name));
-        } else {
-            let params: Vec<String> = case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-            code.push_str(&format!("    TResult Function({})? {},\n", params.join(", "), case.case_name));
-        }
-    }
-    code.push_str("    required TResult orElse(),\n");
-    code.push_str("  }) => throw _privateConstructorUsedError;\n");
+    code.push_str(&format!("mixin _${} {{\n", class.symbol_name()));
     
-    // Generate map method
-    code.push_str("  @optionalTypeArgs\n");
-    code.push_str("  TResult map<TResult extends Object?>({\n");
-    for case in union_cases {
-        let case_class_name = format!("{}{}", class.name, to_pascal_case(&case.case_name));
-        code.push_str(&format!("    required TResult Function({}) {},\n", case_class_name, case.case_name));
+    for method in PATTERN_METHODS {
+        code.push_str("  @optionalTypeArgs\n");
+        code.push_str(&pattern_method_signature(&class.name, method, union_cases));
+        code.push_str(" => throw _privateConstructorUsedError;\n");
     }
-    code.push_str("  }) => throw _privateConstructorUsedError;\n");
-    
-    // Generate mapOrNull method
-    code.push_str("  @optionalTypeArgs\n");
-    code.push_str("  TResult? mapOrNull<TResult extends Object?>({\n");
-    for case in union_cases {
-        let case_class_name = format!("{}{}", class.name, to_pascal_case(&case.case_name));
-        code.push_str(&format!("    TResult? Function({})? {},\n", case_class_name, case.case_name));
+    code.push('\n');
+    let shared_fields = union_shared_fields(source_content, &class.name);
+    for field in &shared_fields {
+        code.push_str(&format!("  {} get {} => throw _privateConstructorUsedError;\n", field.ty, field.name));
     }
-    code.push_str("  }) => throw _privateConstructorUsedError;\n");
     
-    // Generate maybeMap method
-    code.push_str("  @optionalTypeArgs\n");
-    code.push_str("  TResult maybeMap<TResult extends Object?>({\n");
-    for case in union_cases {
-        let case_class_name = format!("{}{}", class.name, to_pascal_case(&case.case_name));
-        code.push_str(&format!("    TResult Function({})? {},\n", case_class_name, case.case_name));
+    if json {
+        code.push_str("  /// Serializes this ");
+        code.push_str(&class.name);
+        code.push_str(" to a JSON map.\n");
+        code.push_str("  Map<String, dynamic> toJson() => throw _privateConstructorUsedError;\n");
     }
-    code.push_str("    required TResult orElse(),\n");
-    code.push_str("  }) => throw _privateConstructorUsedError;\n\n");
-    
-    code.push_str("  /// Serializes this ");
-    code.push_str(&class.name);
-    code.push_str(" to a JSON map.\n");
-    code.push_str("  Map<String, dynamic> toJson() => throw _privateConstructorUsedError;\n");
     code.push_str("}\n\n");
     
     // Generate toJson implementation for union types
-    code.push_str("/// @nodoc\n");
-    code.push_str(&format!("extension {}Extension on {} {{\n", class.name, class.name));
-    code.push_str("  Map<String, dynamic> toJson() => when(\n");
-    for case in union_cases {
-        code.push_str(&format!("    {}: (", case.case_name));
-        if case.fields.is_empty() {
-            code.push_str(") => <String, dynamic>{\n");
-            code.push_str(&format!("      'type': '{}',\n", case.case_name));
-        } else {
-            let params: Vec<String> = case.fields.iter().map(|f| f.name.clone()).collect();
-            code.push_str(&format!("{}) => <String, dynamic>{{\n", params.join(", ")));
-            code.push_str(&format!("      'type': '{}',\n", case.case_name));
-            for field in &case.fields {
-                code.push_str(&format!("      '{}': {},\n", field.name, field.name));
+    let explicit_to_json = has_explicit_to_json(&class.annotations);
+    let enums = extract_enums_from_dart_source(source_content);
+    if json {
+        code.push_str("/// @nodoc\n");
+        code.push_str(&format!("extension {}Extension on {} {{\n", class.name, class.name));
+        code.push_str("  Map<String, dynamic> toJson() => when(\n");
+        for case in union_cases {
+            code.push_str(&format!("    {}: (", case.case_name));
+            if case.fields.is_empty() {
+                code.push_str(") => <String, dynamic>{\n");
+                code.push_str(&format!("      'type': '{}',\n", union_value_case.apply(&case.case_name)));
+            } else {
+                let params: Vec<String> = case.fields.iter().map(|f| f.name.clone()).collect();
+                code.push_str(&format!("{}) => <String, dynamic>{{\n", params.join(", ")));
+                code.push_str(&format!("      'type': '{}',\n", union_value_case.apply(&case.case_name)));
+                for field in &case.fields {
+                    code.push_str(&format!("      {}'{}': {},\n", to_json_entry_condition(field, &field.name), field.name, to_json_conversion(field, &field.name, &enums, explicit_to_json, options)));
+                }
             }
+            code.push_str("    },\n");
         }
-        code.push_str("    },\n");
+        code.push_str("  );\n");
+        code.push_str("}\n\n");
     }
-    code.push_str("  );\n");
-    code.push_str("}\n\n");
     
     // Union types don't have copyWith - skip copyWith generation
     
@@ -585,8 +794,11 @@ fn generate_union_type_code(code: &mut String, class: &DartClass, union_cases: &
             }
         }
         
-        // Generate fields
+        // Generate fields; only the getters shared by every case override one of the union's
         for field in &case.fields {
+            if shared_fields.iter().any(|shared| shared.name == field.name) {
+                code.push_str("  @override\n");
+            }
             code.push_str(&format!("  {} get {};\n", field.ty, field.name));
         }
         if !case.fields.is_empty() {
@@ -615,16 +827,19 @@ fn generate_union_type_code(code: &mut String, class: &DartClass, union_cases: &
             code.push_str("  });\n\n");
         }
         
-        // Fields
+        // Fields, each implementing a getter of the case class
         if !case.fields.is_empty() {
             for field in &case.fields {
+                code.push_str("  @override\n");
                 code.push_str(&format!("  final {} {};\n", field.ty, field.name));
             }
             code.push_str("\n");
         }
         
-        // $type field
-        code.push_str(&format!("  String get $type => '{}';\n\n", case.case_name));
+        // $type field (skipped when the case type is also a standalone @JsonSerializable model)
+        if !is_case_type_standalone_serializable(source_content, &class.name, &case.case_name, &case_class_name) {
+            code.push_str(&format!("  String get $type => '{}';\n\n", case.case_name));
+        }
         
         // toString method
         code.push_str("  @override\n");
@@ -645,221 +860,137 @@ fn generate_union_type_code(code: &mut String, class: &DartClass, union_cases: &
         code.push_str("  }\n\n");
         
         // hashCode
-        code.push_str("  @JsonKey(includeFromJson: false, includeToJson: false)\n");
+        code.push_str(json_key_ignore);
         code.push_str("  @override\n");
         code.push_str("  int get hashCode => runtimeType.hashCode;\n\n");
         
         // Union/sealed型のcaseにはcopyWith getterを出力しない
         
-        // when method implementation
-        code.push_str("  @override\n");
-        code.push_str("  @optionalTypeArgs\n");
-        code.push_str("  TResult when<TResult extends Object?>({\n");
-        for other_case in union_cases {
-            if other_case.case_name == case.case_name {
-                if other_case.fields.is_empty() {
-                    code.push_str(&format!("    required TResult Function() {},\n", other_case.case_name));
-                } else {
-                    let params: Vec<String> = other_case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-                    code.push_str(&format!("    required TResult Function({}) {},\n", params.join(", "), other_case.case_name));
-                }
-            } else {
-                if other_case.fields.is_empty() {
-                    code.push_str(&format!("    required TResult Function() {},\n", other_case.case_name));
-                } else {
-                    let params: Vec<String> = other_case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-                    code.push_str(&format!("    required TResult Function({}) {},\n", params.join(", "), other_case.case_name));
-                }
-            }
-        }
-        code.push_str("  }) {\n");
-        if case.fields.is_empty() {
-            code.push_str(&format!("    return {}();\n", case.case_name));
-        } else {
-            let field_names: Vec<String> = case.fields.iter().map(|f| f.name.clone()).collect();
-            code.push_str(&format!("    return {}({});\n", case.case_name, field_names.join(", ")));
-        }
-        code.push_str("  }\n\n");
-        
-        // whenOrNull method implementation
-        code.push_str("  @override\n");
-        code.push_str("  @optionalTypeArgs\n");
-        code.push_str("  TResult? whenOrNull<TResult extends Object?>({\n");
-        for other_case in union_cases {
-            if other_case.case_name == case.case_name {
-                if other_case.fields.is_empty() {
-                    code.push_str(&format!("    TResult? Function()? {},\n", other_case.case_name));
-                } else {
-                    let params: Vec<String> = other_case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-                    code.push_str(&format!("    TResult? Function({})? {},\n", params.join(", "), other_case.case_name));
-                }
-            } else {
-                if other_case.fields.is_empty() {
-                    code.push_str(&format!("    TResult? Function()? {},\n", other_case.case_name));
-                } else {
-                    let params: Vec<String> = other_case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-                    code.push_str(&format!("    TResult? Function({})? {},\n", params.join(", "), other_case.case_name));
-                }
-            }
-        }
-        code.push_str("  }) {\n");
-        if case.fields.is_empty() {
-            code.push_str(&format!("    return {}?.call();\n", case.case_name));
-        } else {
-            let field_names: Vec<String> = case.fields.iter().map(|f| f.name.clone()).collect();
-            code.push_str(&format!("    return {}?.call({});\n", case.case_name, field_names.join(", ")));
-        }
-        code.push_str("  }\n\n");
-        
-        // maybeWhen method implementation
-        code.push_str("  @override\n");
-        code.push_str("  @optionalTypeArgs\n");
-        code.push_str("  TResult maybeWhen<TResult extends Object?>({\n");
-        for other_case in union_cases {
-            if other_case.case_name == case.case_name {
-                if other_case.fields.is_empty() {
-                    code.push_str(&format!("    TResult Function()? {},\n", other_case.case_name));
-                } else {
-                    let params: Vec<String> = other_case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-                    code.push_str(&format!("    TResult Function({})? {},\n", params.join(", "), other_case.case_name));
-                }
+        // when/map family, with signatures identical to the mixin's declarations
+        for method in PATTERN_METHODS {
+            let callback = &case.case_name;
+            let args = if method.contains("map") || method.contains("Map") {
+                "this".to_string()
             } else {
-                if other_case.fields.is_empty() {
-                    code.push_str(&format!("    TResult Function()? {},\n", other_case.case_name));
-                } else {
-                    let params: Vec<String> = other_case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect();
-                    code.push_str(&format!("    TResult Function({})? {},\n", params.join(", "), other_case.case_name));
+                case.fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>().join(", ")
+            };
+            code.push_str("  @override\n");
+            code.push_str("  @optionalTypeArgs\n");
+            code.push_str(&pattern_method_signature(&class.name, method, union_cases));
+            code.push_str(" {\n");
+            match method {
+                "when" | "map" => code.push_str(&format!("    return {}({});\n", callback, args)),
+                "whenOrNull" | "mapOrNull" => code.push_str(&format!("    return {}?.call({});\n", callback, args)),
+                _ => {
+                    code.push_str(&format!("    if ({} != null) {{\n", callback));
+                    code.push_str(&format!("      return {}({});\n", callback, args));
+                    code.push_str("    }\n");
+                    code.push_str("    return orElse();\n");
                 }
             }
+            code.push_str("  }\n\n");
         }
-        code.push_str("    required TResult orElse(),\n");
-        code.push_str("  }) {\n");
-        code.push_str(&format!("    if ({} != null) {{\n", case.case_name));
-        if case.fields.is_empty() {
-            code.push_str(&format!("      return {}();\n", case.case_name));
-        } else {
-            let field_names: Vec<String> = case.fields.iter().map(|f| f.name.clone()).collect();
-            code.push_str(&format!("      return {}({});\n", case.case_name, field_names.join(", ")));
-        }
-        code.push_str("    }\n");
-        code.push_str("    return orElse();\n");
-        code.push_str("  }\n\n");
-        
-        // map method implementation
-        code.push_str("  @override\n");
-        code.push_str("  @optionalTypeArgs\n");
-        code.push_str("  TResult map<TResult extends Object?>({\n");
-        for other_case in union_cases {
-            let other_case_class_name = format!("{}{}", class.name, to_pascal_case(&other_case.case_name));
-            if other_case.case_name == case.case_name {
-                code.push_str(&format!("    required TResult Function({}) {},\n", impl_class_name, other_case.case_name));
-            } else {
-                code.push_str(&format!("    required TResult Function({}) {},\n", other_case_class_name, other_case.case_name));
-            }
-        }
-        code.push_str("  }) {\n");
-        code.push_str(&format!("    return {}(this);\n", case.case_name));
-        code.push_str("  }\n\n");
-        
-        // mapOrNull method implementation
-        code.push_str("  @override\n");
-        code.push_str("  @optionalTypeArgs\n");
-        code.push_str("  TResult? mapOrNull<TResult extends Object?>({\n");
-        for other_case in union_cases {
-            let other_case_class_name = format!("{}{}", class.name, to_pascal_case(&other_case.case_name));
-            if other_case.case_name == case.case_name {
-                code.push_str(&format!("    TResult? Function({})? {},\n", impl_class_name, other_case.case_name));
-            } else {
-                code.push_str(&format!("    TResult? Function({})? {},\n", other_case_class_name, other_case.case_name));
-            }
-        }
-        code.push_str("  }) {\n");
-        code.push_str(&format!("    return {}?.call(this);\n", case.case_name));
-        code.push_str("  }\n\n");
-        
-        // maybeMap method implementation
-        code.push_str("  @override\n");
-        code.push_str("  @optionalTypeArgs\n");
-        code.push_str("  TResult maybeMap<TResult extends Object?>({\n");
-        for other_case in union_cases {
-            let other_case_class_name = format!("{}{}", class.name, to_pascal_case(&other_case.case_name));
-            if other_case.case_name == case.case_name {
-                code.push_str(&format!("    TResult Function({})? {},\n", impl_class_name, other_case.case_name));
-            } else {
-                code.push_str(&format!("    TResult Function({})? {},\n", other_case_class_name, other_case.case_name));
-            }
-        }
-        code.push_str("    required TResult orElse(),\n");
-        code.push_str("  }) {\n");
-        code.push_str(&format!("    if ({} != null) {{\n", case.case_name));
-        code.push_str(&format!("      return {}(this);\n", case.case_name));
-        code.push_str("    }\n");
-        code.push_str("    return orElse();\n");
-        code.push_str("  }\n\n");
         
         // toJson method for union cases
-        code.push_str("  @override\n");
-        code.push_str("  Map<String, dynamic> toJson() {\n");
-        code.push_str(&format!("    return <String, dynamic>{{\n"));
-        code.push_str(&format!("      'type': '{}',\n", case.case_name));
-        for field in &case.fields {
-            code.push_str(&format!("      '{}': {},\n", field.name, field.name));
+        if json {
+            code.push_str("  @override\n");
+            code.push_str("  Map<String, dynamic> toJson() {\n");
+            code.push_str(&format!("    return <String, dynamic>{{\n"));
+            code.push_str(&format!("      'type': '{}',\n", union_value_case.apply(&case.case_name)));
+            for field in &case.fields {
+                code.push_str(&format!("      '{}': {},\n", field.name, to_json_conversion(field, &field.name, &enums, explicit_to_json, options)));
+            }
+            code.push_str("    };\n");
+            code.push_str("  }\n");
         }
-        code.push_str("    };\n");
-        code.push_str("  }\n");
         code.push_str("}\n\n");
         
         // Union/sealed型のcaseにはcopyWithクラスを出力しない
     }
 }
 
-fn to_pascal_case(s: &str) -> String {
-    if s.is_empty() {
-        return String::new();
-    }
-    let mut result = String::new();
-    let mut capitalize_next = true;
-    
-    for c in s.chars() {
-        if c == '_' || c == '-' {
-            capitalize_next = true;
-        } else if capitalize_next {
-            result.push(c.to_ascii_uppercase());
-            capitalize_next = false;
+const JSON_KEY_IGNORE: &str = "  @JsonKey(includeFromJson: false, includeToJson: false)\n";
+
+const PATTERN_METHODS: [&str; 6] = ["when", "whenOrNull", "maybeWhen", "map", "mapOrNull", "maybeMap"];
+
+// Signature (up to the closing `})`) of a union's when/map method. The mixin and every case
+// implementation share it, so overrides always match the declarations: `when`/`map` take
+// required callbacks, `*OrNull` nullable ones returning TResult?, and `maybe*` nullable
+// callbacks plus a required `orElse`.
+fn pattern_method_signature(class_name: &str, method: &str, union_cases: &[CaseInfo]) -> String {
+    let (return_type, required, nullable) = match method {
+        "when" | "map" => ("TResult", "required ", ""),
+        "whenOrNull" | "mapOrNull" => ("TResult?", "", "?"),
+        _ => ("TResult", "", "?"),
+    };
+    let maps_case_class = method.contains("map") || method.contains("Map");
+    let mut signature = format!("  {} {}<TResult extends Object?>({{\n", return_type, method);
+    for case in union_cases {
+        let params = if maps_case_class {
+            format!("{}{}", class_name, to_pascal_case(&case.case_name))
         } else {
-            result.push(c);
-        }
+            case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect::<Vec<_>>().join(", ")
+        };
+        signature.push_str(&format!("    {}{} Function({}){} {},\n", required, return_type, params, nullable, case.case_name));
     }
-    
-    result
+    if method.starts_with("maybe") {
+        signature.push_str("    required TResult orElse(),\n");
+    }
+    signature.push_str("  })");
+    signature
 }
 
+/// Whether the source declares `factory Class.fromJson(...)`, which the generated `_$ClassFromJson` backs
+pub fn declares_from_json_factory(source_content: &str, class_name: &str) -> bool {
+    let pattern = format!(r"factory\s+{}\.fromJson\s*\(", regex::escape(class_name));
+    regex::Regex::new(&pattern).map(|re| re.is_match(source_content)).unwrap_or(false)
+}
 
+/// Returns true when a union case's type (the generated case class or the user's
+/// redirect target, e.g. `= Success;`) is declared elsewhere as its own @JsonSerializable class.
+fn is_case_type_standalone_serializable(source_content: &str, class_name: &str, case_name: &str, case_class_name: &str) -> bool {
+    let mut candidates = vec![case_class_name.to_string()];
+    let redirect_pattern = format!(r"(?s)\b{}\.{}\s*\(.*?\)\s*=\s*(\w+)\s*;", regex::escape(class_name), regex::escape(case_name));
+    if let Ok(re) = regex::Regex::new(&redirect_pattern) {
+        if let Some(cap) = re.captures(source_content) {
+            candidates.push(cap[1].to_string());
+        }
+    }
+    candidates.iter().any(|name| {
+        let pattern = format!(r"@JsonSerializable(?:\([^)]*\))?{}class\s+{}\b", ANNOTATION_GAP, regex::escape(name));
+        regex::Regex::new(&pattern).map(|re| re.is_match(source_content)).unwrap_or(false)
+    })
+}
 
 // FromJson/ToJsonの本体・閉じカッコも2スペースに統一
-pub fn generate_json_code(class: &DartClass) -> String {
+pub fn generate_json_code(class: &DartClass, source_content: &str, options: &GenOptions) -> String {
     let mut code = String::new();
-    let source_content = std::fs::read_to_string(&class.file_path).unwrap_or_default();
-    let fields = extract_fields_from_dart_class(&source_content, &class.name);
-    let union_cases = extract_union_cases_from_dart_class(&source_content, &class.name);
+    let fields = extract_fields_from_dart_class(source_content, &class.name);
+    let union_cases = extract_union_cases_from_dart_class(source_content, &class.name);
+    let enums = extract_enums_from_dart_source(source_content);
     if !union_cases.is_empty() {
         // Generate union type FromJson function
-        let from_json_fn = format!("_${}FromJson", class.name);
+        let from_json_fn = format!("_${}FromJson", class.symbol_name());
         code.push_str(&format!("{} {}(\n", class.name, from_json_fn));
         code.push_str("  Map<String, dynamic> json,\n");
         code.push_str(") {\n");
         code.push_str("  switch (json['type'] as String) {\n");
+        let union_value_case = FreezedOptions::from_annotations(&class.annotations).union_value_case;
         
         for case in &union_cases {
-            code.push_str(&format!("    case '{}':\n", case.case_name));
+            code.push_str(&format!("    case '{}':\n", union_value_case.apply(&case.case_name)));
             if case.fields.is_empty() {
                 code.push_str(&format!("      return {}.{}();\n", class.name, case.case_name));
             } else {
                 code.push_str(&format!("      return {}.{}(\n", class.name, case.case_name));
                 for field in &case.fields {
-                    let field_conversion = get_field_conversion(field);
+                    let field_conversion = from_json_conversion(field, &enums, options);
                     let formatted_conversion = format_long_expression(&field_conversion);
-                    code.push_str(&format!("        {}: {},\n", field.name, formatted_conversion));
+                    if field.is_named {
+                        code.push_str(&format!("        {}: {},\n", field.name, formatted_conversion));
+                    } else {
+                        code.push_str(&format!("        {},\n", formatted_conversion));
+                    }
                 }
                 code.push_str("      );\n");
             }
@@ -869,43 +1000,130 @@ pub fn generate_json_code(class: &DartClass) -> String {
         code.push_str("  }\n");
         code.push_str("}\n\n");
     } else {
-        let impl_class = format!("_$${}ImplImpl", class.name);
-        let from_json_fn = format!("_$${}ImplImplFromJson", class.name);
-        let to_json_fn = format!("_$${}ImplImplToJson", class.name);
-        
-        // FromJson - JsonSerializableGenerator style
-        code.push_str(&format!("{} {}(\n", impl_class, from_json_fn));
-        code.push_str("  Map<String, dynamic> json,\n");
-        code.push_str(&format!(") => {}(\n", impl_class));
-        for field in &fields {
-            let field_conversion = get_field_conversion(field);
-            let formatted_conversion = format_long_expression(&field_conversion);
+        let impl_class = format!("_$${}ImplImpl", class.symbol_name());
+        push_json_functions(&mut code, &impl_class, &impl_class, &fields, &enums, &class.annotations, options);
+    }
+    code
+}
+
+/// `_$ClassFromJson`/`_$ClassToJson` for a hand-written @JsonSerializable class, with none of
+/// freezed's mixin/copyWith/impl scaffolding. Fields come from its `final` fields and constructor.
+pub fn generate_plain_json_code(class: &DartClass, source_content: &str, options: &GenOptions) -> String {
+    let mut code = String::new();
+    let fields = extract_plain_class_fields(source_content, &class.name);
+    let enums = extract_enums_from_dart_source(source_content);
+    push_json_functions(&mut code, &class.name, &format!("_${}", class.symbol_name()), &fields, &enums, &class.annotations, options);
+    code
+}
+
+// `{prefix}FromJson` building target_class from a JSON map and `{prefix}ToJson` turning it back into one
+fn push_json_functions(code: &mut String, target_class: &str, prefix: &str, fields: &[DartField], enums: &[DartEnum], annotations: &[String], options: &GenOptions) {
+    // FromJson - JsonSerializableGenerator style
+    code.push_str(&format!("{} {}FromJson(\n", target_class, prefix));
+    code.push_str("  Map<String, dynamic> json,\n");
+    code.push_str(&format!(") => {}(\n", target_class));
+    for field in fields {
+        let field_conversion = from_json_conversion(field, enums, options);
+        let formatted_conversion = format_long_expression(&field_conversion);
+        if field.is_named {
             code.push_str(&format!("  {}: {},\n", field.name, formatted_conversion));
+        } else {
+            code.push_str(&format!("  {},\n", formatted_conversion));
         }
-        code.push_str(");\n\n");
-        
-        // ToJson - JsonSerializableGenerator style
-        code.push_str(&format!("Map<String, dynamic> {}(\n", to_json_fn));
-        code.push_str(&format!("  {} instance,\n", impl_class));
-        code.push_str(") => <String, dynamic>{\n");
-        for field in &fields {
-            let field_conversion = get_to_json_field_conversion(field);
-            code.push_str(&format!("  '{}': {},\n", field.name, field_conversion));
-        }
-        code.push_str("};\n\n");
     }
-    code
+    code.push_str(");\n\n");
+    
+    // ToJson - JsonSerializableGenerator style
+    code.push_str(&format!("Map<String, dynamic> {}ToJson(\n", prefix));
+    code.push_str(&format!("  {} instance,\n", target_class));
+    code.push_str(") => <String, dynamic>{\n");
+    let explicit_to_json = has_explicit_to_json(annotations);
+    for field in fields {
+        let accessor = format!("instance.{}", field.name);
+        let field_conversion = to_json_conversion(field, &accessor, enums, explicit_to_json, options);
+        code.push_str(&format!("  {}'{}': {},\n", to_json_entry_condition(field, &format!("instance.{}", field.name)), field.name, field_conversion));
+    }
+    code.push_str("};\n\n");
 }
 
-fn get_to_json_field_conversion(field: &DartField) -> String {
-    let field_name = &field.name;
+// Collection-if guarding a toJson map entry: a null field marked `@JsonKey(includeIfNull: false)` is left out
+fn to_json_entry_condition(field: &DartField, accessor: &str) -> String {
+    if field.omit_if_null && field.ty.ends_with('?') {
+        format!("if ({} != null) ", accessor)
+    } else {
+        String::new()
+    }
+}
+
+// fromJson expression of a field: its @JsonKey converter, enum map or immutable collection
+// (with `generate.immutable_collections`), otherwise the conversion for its type
+fn from_json_conversion(field: &DartField, enums: &[DartEnum], options: &GenOptions) -> String {
+    converter_from_json_conversion(field)
+        .or_else(|| enum_from_json_conversion(field, enums))
+        .or_else(|| immutable_collection_from_json(field).filter(|_| options.immutable_collections))
+        .unwrap_or_else(|| get_field_conversion(field))
+}
+
+// toJson counterpart of from_json_conversion for the field read through `accessor`
+// (`instance.name`, or a local variable bound by `when` in union toJson)
+fn to_json_conversion(field: &DartField, accessor: &str, enums: &[DartEnum], explicit_to_json: bool, options: &GenOptions) -> String {
+    converter_to_json_conversion(field, accessor)
+        .or_else(|| enum_to_json_conversion(field, accessor, enums))
+        .or_else(|| immutable_collection_to_json(field, accessor).filter(|_| options.immutable_collections))
+        .unwrap_or_else(|| get_to_json_value_conversion(field, accessor, explicit_to_json))
+}
+
+// Conversion of a field's value read through `accessor` for its type alone
+fn get_to_json_value_conversion(field: &DartField, accessor: &str, explicit_to_json: bool) -> String {
     let field_type = &field.ty;
-    
     match field_type.as_str() {
-        "DateTime" => format!("instance.{}.toIso8601String()", field_name),
-        "DateTime?" => format!("instance.{}?.toIso8601String()", field_name),
-        _ => format!("instance.{}", field_name),
+        "DateTime" => format!("{}.toIso8601String()", accessor),
+        "DateTime?" => format!("{}?.toIso8601String()", accessor),
+        "Duration" => format!("{}.inMicroseconds", accessor),
+        "Duration?" => format!("{}?.inMicroseconds", accessor),
+        "Uri" | "BigInt" => format!("{}.toString()", accessor),
+        "Uri?" | "BigInt?" => format!("{}?.toString()", accessor),
+        _ if explicit_to_json => explicit_to_json_conversion(field_type, accessor).unwrap_or_else(|| accessor.to_string()),
+        _ => accessor.to_string(),
+    }
+}
+
+// explicitToJson: call toJson() on nested models (and on each element of a list of models)
+fn explicit_to_json_conversion(field_type: &str, accessor: &str) -> Option<String> {
+    let (base_type, op) = match field_type.strip_suffix('?') {
+        Some(base_type) => (base_type, "?."),
+        None => (field_type, "."),
+    };
+    if let Some(element_type) = base_type.strip_prefix("List<").and_then(|t| t.strip_suffix('>')) {
+        if !is_model_type(element_type) {
+            return None;
+        }
+        let element = if element_type.ends_with('?') { "e?.toJson()" } else { "e.toJson()" };
+        return Some(format!("{}{}map((e) => {}).toList()", accessor, op, element));
     }
+    is_model_type(base_type).then(|| format!("{}{}toJson()", accessor, op))
+}
+
+// A user-defined model type, i.e. anything that is not a Dart core/collection type
+fn is_model_type(ty: &str) -> bool {
+    const CORE_TYPES: [&str; 15] = [
+        "String", "int", "double", "num", "bool", "dynamic", "Object", "DateTime",
+        "Duration", "Uri", "BigInt", "List", "Map", "Set", "Iterable",
+    ];
+    let ty = ty.trim().trim_end_matches('?');
+    ty.starts_with(|c: char| c.is_ascii_uppercase())
+        && ty.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !CORE_TYPES.contains(&ty)
+}
+
+/// Whether the class is annotated with `@JsonSerializable(explicitToJson: true)`
+fn has_explicit_to_json(annotations: &[String]) -> bool {
+    annotations.iter()
+        .filter(|ann| annotation_name(ann) == "@JsonSerializable")
+        .filter_map(|ann| annotation_arguments(ann))
+        .flat_map(|args| args.split(','))
+        .filter_map(|arg| arg.split_once(':'))
+        .any(|(key, value)| key.trim() == "explicitToJson" && value.trim() == "true")
 }
 
 fn format_long_expression(expr: &str) -> String {
@@ -940,18 +1158,24 @@ fn format_long_expression(expr: &str) -> String {
 fn get_field_conversion(field: &DartField) -> String {
     let field_name = &field.name;
     let field_type = &field.ty;
+    if let Some(conversion) = empty_collection_default_conversion(field) {
+        return conversion;
+    }
     match field_type.as_str() {
         "DateTime" => format!("DateTime.parse(json['{}'] as String)", field_name),
         "DateTime?" => format!("json['{}'] == null\n          ? null\n          : DateTime.parse(json['{}'] as String)", field_name, field_name),
+        "int" if field.has_default => format!("(json['{}'] as num?)?.toInt() ?? {}", field_name, field.default_value.as_deref().unwrap_or("0")),
+        "double" if field.has_default => format!("(json['{}'] as num?)?.toDouble() ?? {}", field_name, field.default_value.as_deref().unwrap_or("0")),
+        "num" if field.has_default => format!("json['{}'] as num? ?? {}", field_name, field.default_value.as_deref().unwrap_or("0")),
         "int" => format!("(json['{}'] as num).toInt()", field_name),
         "int?" => format!("(json['{}'] as num?)?.toInt()", field_name),
-        "List<String>" => {
-            if field.has_default && field.default_value.as_deref() == Some("[]") {
-                format!("(json['{}'] as List<dynamic>?)\n          ?.map((e) => e as String)\n          .toList() ??\n      const []", field_name)
-            } else {
-                format!("(json['{}'] as List<dynamic>?)?.map((e) => e as String).toList()", field_name)
-            }
-        }
+        "Duration" => format!("Duration(microseconds: (json['{}'] as num).toInt())", field_name),
+        "Duration?" => format!("json['{}'] == null\n          ? null\n          : Duration(microseconds: (json['{}'] as num).toInt())", field_name, field_name),
+        "Uri" => format!("Uri.parse(json['{}'] as String)", field_name),
+        "Uri?" => format!("json['{}'] == null ? null : Uri.parse(json['{}'] as String)", field_name, field_name),
+        "BigInt" => format!("BigInt.parse(json['{}'] as String)", field_name),
+        "BigInt?" => format!("json['{}'] == null ? null : BigInt.parse(json['{}'] as String)", field_name, field_name),
+        "List<String>" => format!("(json['{}'] as List<dynamic>?)?.map((e) => e as String).toList()", field_name),
         "List<String>?" => format!("(json['{}'] as List<dynamic>?)?.map((e) => e as String).toList()", field_name),
         "String" => {
             if field.has_default {
@@ -972,279 +1196,1051 @@ fn get_field_conversion(field: &DartField) -> String {
             }
         }
         _ => {
-            if field_type.ends_with('?') {
-                format!("json['{}'] as {}", field_name, field_type)
-            } else {
-                format!("json['{}'] as {}", field_name, field_type)
+            // Collections with a non-empty default keep the plain cast (see empty_collection_default_conversion)
+            let fallback = field.has_default && !field_type.ends_with('?') && !is_collection_type(field_type);
+            match field.default_value.as_deref().filter(|_| fallback) {
+                Some(default_value) => format!("json['{}'] as {}? ?? {}", field_name, field_type, default_value.trim()),
+                None => format!("json['{}'] as {}", field_name, field_type),
             }
         }
     }
 }
 
+// A `@Default` value as a constructor parameter default, which must be constant:
+// collection literals and constructor calls get `const`, enum values and literals are kept as is
+fn const_default_value(default_value: &str) -> String {
+    let default_value = default_value.trim();
+    let constructor_call = regex::Regex::new(r"^[A-Z]\w*(?:<[^(]*>)?(?:\.\w+)?\(").unwrap();
+    if default_value.starts_with("const ") {
+        default_value.to_string()
+    } else if default_value.starts_with(['[', '{', '<']) || constructor_call.is_match(default_value) {
+        format!("const {}", default_value)
+    } else {
+        default_value.to_string()
+    }
+}
+
+// `List`/`Set`/`Map` fields with an empty `@Default` fall back to an empty collection on a missing key
+fn empty_collection_default_conversion(field: &DartField) -> Option<String> {
+    let default_value = field.default_value.as_deref()?.trim();
+    if !field.has_default || field.ty.ends_with('?') {
+        return None;
+    }
+    let default_value = default_value.strip_prefix("const").unwrap_or(default_value).trim();
+    // Drop explicit type arguments as in `<String>[]`
+    let default_value = match default_value.strip_prefix('<') {
+        Some(rest) => rest.rsplit_once('>')?.1.trim(),
+        None => default_value,
+    };
+    let field_name = &field.name;
+    let (collection, args) = field.ty.split_once('<')?;
+    let args = split_generic_arguments(args.strip_suffix('>')?);
+    match (collection, args.as_slice(), default_value) {
+        ("List", [element], "[]") => Some(format!(
+            "(json['{}'] as List<dynamic>?)\n          ?.map((e) => {})\n          .toList() ??\n      const []",
+            field_name,
+            element_from_json_conversion(element)
+        )),
+        ("Set", [element], "{}") => Some(format!(
+            "(json['{}'] as List<dynamic>?)\n          ?.map((e) => {})\n          .toSet() ??\n      const {{}}",
+            field_name,
+            element_from_json_conversion(element)
+        )),
+        ("Map", [key, value], "{}") => Some(format!(
+            "(json['{}'] as Map<String, dynamic>?)\n          ?.map((k, e) => MapEntry(k as {}, {})) ??\n      const {{}}",
+            field_name,
+            key,
+            element_from_json_conversion(value)
+        )),
+        _ => None,
+    }
+}
+
+// Dart's `Object.hash` takes at most this many arguments
+const OBJECT_HASH_MAX_ARGS: usize = 20;
+
+// `hashCode` over runtimeType and every field, hashing collections by content.
+// Beyond Object.hash's argument limit the values go through `Object.hashAll` instead.
+fn hash_code_getter(fields: &[DartField]) -> String {
+    let mut values = vec!["runtimeType".to_string()];
+    for field in fields {
+        if is_collection_type(&field.ty) {
+            values.push(format!("const DeepCollectionEquality().hash({})", field.name));
+        } else {
+            values.push(field.name.clone());
+        }
+    }
+    let mut code = String::new();
+    if values.len() == 1 {
+        // Object.hash needs at least two arguments
+        code.push_str("  int get hashCode => runtimeType.hashCode;\n\n");
+    } else if values.len() <= OBJECT_HASH_MAX_ARGS {
+        code.push_str("  int get hashCode => Object.hash(\n");
+        for value in &values {
+            code.push_str(&format!("      {},\n", value));
+        }
+        code.push_str("  );\n\n");
+    } else {
+        code.push_str("  int get hashCode => Object.hashAll([\n");
+        for value in &values {
+            code.push_str(&format!("        {},\n", value));
+        }
+        code.push_str("      ]);\n\n");
+    }
+    code
+}
+
+// Dart's mutable collections compare by identity, so equality goes through DeepCollectionEquality.
+// IList/ISet/IMap are value types and use `==` like everything else.
+fn is_collection_type(ty: &str) -> bool {
+    let base = ty.trim_end_matches('?');
+    let name = base.split_once('<').map_or(base, |(name, _)| name).trim();
+    matches!(name, "List" | "Map" | "Set" | "Iterable")
+}
+
+// fast_immutable_collections' `IList<T>`/`ISet<T>`/`IMap<K, V>`: the collection name and its type arguments
+fn immutable_collection_type(ty: &str) -> Option<(&str, Vec<String>)> {
+    let (collection, args) = ty.trim_end_matches('?').split_once('<')?;
+    let args = split_generic_arguments(args.strip_suffix('>')?);
+    match (collection, args.len()) {
+        ("IList" | "ISet", 1) | ("IMap", 2) => Some((collection, args)),
+        _ => None,
+    }
+}
+
+// Build an immutable collection from the decoded JSON list/map, e.g. `IList(... .map((e) => e as String))`
+fn immutable_collection_from_json(field: &DartField) -> Option<String> {
+    let (collection, args) = immutable_collection_type(&field.ty)?;
+    let field_name = &field.name;
+    let conversion = if collection == "IMap" {
+        format!(
+            "IMap((json['{}'] as Map<String, dynamic>).map((k, e) => MapEntry(k as {}, {})))",
+            field_name,
+            args[0],
+            element_from_json_conversion(&args[1])
+        )
+    } else {
+        format!(
+            "{}((json['{}'] as List<dynamic>).map((e) => {}))",
+            collection,
+            field_name,
+            element_from_json_conversion(&args[0])
+        )
+    };
+    Some(if field.ty.ends_with('?') {
+        format!("json['{}'] == null\n          ? null\n          : {}", field_name, conversion)
+    } else {
+        conversion
+    })
+}
+
+// Back to the mutable collection json_serializable encodes: `instance.tags.unlock`
+fn immutable_collection_to_json(field: &DartField, accessor: &str) -> Option<String> {
+    immutable_collection_type(&field.ty)?;
+    let op = if field.ty.ends_with('?') { "?." } else { "." };
+    Some(format!("{}{}unlock", accessor, op))
+}
+
+// Split generic arguments on top-level commas (`String, List<int>` -> [`String`, `List<int>`])
+fn split_generic_arguments(args: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    for ch in args.chars() {
+        match ch {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    parts.push(current.trim().to_string());
+    parts
+}
+
+// Conversion of a collection element `e` decoded from JSON
+fn element_from_json_conversion(element_type: &str) -> String {
+    match element_type {
+        "int" => "(e as num).toInt()".to_string(),
+        "double" => "(e as num).toDouble()".to_string(),
+        _ => format!("e as {}", element_type),
+    }
+}
+
+/// Extract enums and their serialized values, honoring `@JsonValue(...)` on constants
+pub fn extract_enums_from_dart_source(source_content: &str) -> Vec<DartEnum> {
+    let enum_pattern = regex::Regex::new(r"\benum\s+(\w+)\s*(?:with\s+[\w\s,]+|implements\s+[\w\s,<>]+)?\{").unwrap();
+    let mut enums = Vec::new();
+    for cap in enum_pattern.captures_iter(source_content) {
+        let body_start = cap.get(0).unwrap().end();
+        let Some(body_len) = source_content[body_start..].find('}') else { continue };
+        // Enhanced enums list their constants before the first ';'
+        let body = &source_content[body_start..body_start + body_len];
+        let constants = body.split(';').next().unwrap_or(body);
+        let values = split_enum_constants(constants)
+            .iter()
+            .filter_map(|constant| parse_enum_constant(constant))
+            .collect();
+        enums.push(DartEnum { name: cap[1].to_string(), values });
+    }
+    enums
+}
+
+// Split enum constants on top-level commas, dropping comments
+fn split_enum_constants(constants: &str) -> Vec<String> {
+    let block_comment = regex::Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    let line_comment = regex::Regex::new(r"//[^\n]*").unwrap();
+    let without_comments = block_comment.replace_all(constants, "");
+    let without_comments = line_comment.replace_all(&without_comments, "");
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    for ch in without_comments.chars() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(ch);
+    }
+    items.push(current.trim().to_string());
+    items.into_iter().filter(|item| !item.is_empty()).collect()
+}
+
+fn parse_enum_constant(constant: &str) -> Option<DartEnumValue> {
+    let json_value_pattern = regex::Regex::new(r"@JsonValue\(\s*(.+?)\s*\)\s*").unwrap();
+    let json_value = json_value_pattern.captures(constant).map(|cap| cap[1].to_string());
+    let rest = json_value_pattern.replace(constant, "");
+    // Skip other annotations, then take the identifier (enhanced enum constants may have arguments)
+    let name = rest
+        .split_whitespace()
+        .find(|token| !token.starts_with('@'))?
+        .split('(')
+        .next()?
+        .to_string();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(DartEnumValue {
+        json_value: json_value.unwrap_or_else(|| format!("'{}'", name)),
+        name,
+    })
+}
+
+/// `const _$StatusEnumMap = {...};` mapping each constant to its serialized value
+pub fn generate_enum_map(dart_enum: &DartEnum) -> String {
+    let mut code = String::new();
+    code.push_str(&format!("const _${}EnumMap = {{\n", dart_enum.name));
+    for value in &dart_enum.values {
+        code.push_str(&format!("  {}.{}: {},\n", dart_enum.name, value.name, value.json_value));
+    }
+    code.push_str("};\n\n");
+    code
+}
+
+fn find_enum<'a>(field: &DartField, enums: &'a [DartEnum]) -> Option<&'a DartEnum> {
+    let base_type = field.ty.trim_end_matches('?');
+    enums.iter().find(|dart_enum| dart_enum.name == base_type)
+}
+
+// `List<Status>`/`List<Status?>?`: the enum of the list's elements and whether they are nullable
+fn find_enum_list<'a>(field: &DartField, enums: &'a [DartEnum]) -> Option<(&'a DartEnum, bool)> {
+    let element = field.ty.trim_end_matches('?').strip_prefix("List<")?.strip_suffix('>')?.trim();
+    let dart_enum = enums.iter().find(|dart_enum| dart_enum.name == element.trim_end_matches('?'))?;
+    Some((dart_enum, element.ends_with('?')))
+}
+
+fn enum_list_from_json_conversion(field: &DartField, enums: &[DartEnum]) -> Option<String> {
+    let (dart_enum, nullable_elements) = find_enum_list(field, enums)?;
+    let decode = if nullable_elements { "$enumDecodeNullable" } else { "$enumDecode" };
+    let element = format!("{}(_${}EnumMap, e)", decode, dart_enum.name);
+    if let (false, Some(default_value)) = (field.ty.ends_with('?'), field.default_value.as_deref().filter(|_| field.has_default)) {
+        Some(format!("(json['{}'] as List<dynamic>?)?.map((e) => {}).toList() ?? {}", field.name, element, const_default_value(default_value.trim())))
+    } else if field.ty.ends_with('?') {
+        Some(format!("(json['{}'] as List<dynamic>?)?.map((e) => {}).toList()", field.name, element))
+    } else {
+        Some(format!("(json['{}'] as List<dynamic>).map((e) => {}).toList()", field.name, element))
+    }
+}
+
+fn enum_list_to_json_conversion(field: &DartField, accessor: &str, enums: &[DartEnum]) -> Option<String> {
+    let (dart_enum, nullable_elements) = find_enum_list(field, enums)?;
+    let op = if field.ty.ends_with('?') { "?." } else { "." };
+    let bang = if nullable_elements { "" } else { "!" };
+    Some(format!("{}{}map((e) => _${}EnumMap[e]{}).toList()", accessor, op, dart_enum.name, bang))
+}
+
+fn enum_from_json_conversion(field: &DartField, enums: &[DartEnum]) -> Option<String> {
+    let Some(dart_enum) = find_enum(field, enums) else {
+        return enum_list_from_json_conversion(field, enums);
+    };
+    if let (false, Some(default_value)) = (field.ty.ends_with('?'), field.default_value.as_deref().filter(|_| field.has_default)) {
+        Some(format!("$enumDecodeNullable(_${}EnumMap, json['{}']) ?? {}", dart_enum.name, field.name, default_value.trim()))
+    } else if field.ty.ends_with('?') {
+        Some(format!("$enumDecodeNullable(_${}EnumMap, json['{}'])", dart_enum.name, field.name))
+    } else {
+        Some(format!("$enumDecode(_${}EnumMap, json['{}'])", dart_enum.name, field.name))
+    }
+}
+
+fn converter_from_json_conversion(field: &DartField) -> Option<String> {
+    let converter = field.converter.as_deref()?;
+    if field.ty.ends_with('?') {
+        Some(format!("json['{0}'] == null ? null : {1}.fromJson(json['{0}'])", field.name, converter))
+    } else {
+        Some(format!("{}.fromJson(json['{}'])", converter, field.name))
+    }
+}
+
+fn converter_to_json_conversion(field: &DartField, accessor: &str) -> Option<String> {
+    let converter = field.converter.as_deref()?;
+    if field.ty.ends_with('?') {
+        Some(format!("{0} == null ? null : {1}.toJson({0}!)", accessor, converter))
+    } else {
+        Some(format!("{}.toJson({})", converter, accessor))
+    }
+}
+
+fn enum_to_json_conversion(field: &DartField, accessor: &str, enums: &[DartEnum]) -> Option<String> {
+    let Some(dart_enum) = find_enum(field, enums) else {
+        return enum_list_to_json_conversion(field, accessor, enums);
+    };
+    if field.ty.ends_with('?') {
+        Some(format!("_${}EnumMap[{}]", dart_enum.name, accessor))
+    } else {
+        Some(format!("_${}EnumMap[{}]!", dart_enum.name, accessor))
+    }
+}
+
 pub fn extract_fields_from_dart_class(source_content: &str, class_name: &str) -> Vec<DartField> {
-    eprintln!("[DEBUG] extract_fields_from_dart_class called for {}", class_name);
+    extract_constructor_from_dart_class(source_content, class_name).0
+}
+
+/// Fields of the class's unnamed factory constructor, and whether that factory is declared `const`
+pub fn extract_constructor_from_dart_class(source_content: &str, class_name: &str) -> (Vec<DartField>, bool) {
+    debug!("extract_constructor_from_dart_class called for {}", class_name);
     let mut fields = Vec::new();
+    let mut is_const = true;
     // Find the main constructor for this class
-    let constructor_pattern = format!("const factory {}({{", class_name);
-    if let Some(constructor_start) = source_content.find(&constructor_pattern) {
-        eprintln!("[DEBUG] Found constructor at position {}", constructor_start);
-        // Find the closing brace of the constructor parameters
-        let mut brace_count = 0;
-        let mut in_constructor = false;
-        let mut constructor_content = String::new();
-        for (i, ch) in source_content[constructor_start..].chars().enumerate() {
-            if ch == '{' {
-                brace_count += 1;
-                in_constructor = true;
-            } else if ch == '}' {
-                brace_count -= 1;
-                if in_constructor && brace_count == 0 {
-                    // Found the end of constructor parameters
-                    constructor_content = source_content[constructor_start..constructor_start + i + 1].to_string();
-                    break;
+    let constructor_pattern = regex::Regex::new(&format!(r"(const\s+)?factory\s+{}\s*\(", regex::escape(class_name))).unwrap();
+    if let Some(constructor) = constructor_pattern.captures(source_content) {
+        let constructor_match = constructor.get(0).unwrap();
+        debug!("Found constructor at position {}", constructor_match.start());
+        is_const = constructor.get(1).is_some();
+        let params_start = constructor_match.end();
+        // Find the closing parenthesis of the constructor parameters
+        let params_end = find_closing_paren(&source_content[params_start..]).map(|end| params_start + end);
+        if let Some(params_end) = params_end {
+            let params_content = source_content[params_start..params_end].trim();
+            debug!("Parameters content: {}", params_content);
+            // Named parameters are wrapped in {} and may follow positional ones: `(int a, {required String b})`
+            let (positional, named) = match find_named_params_start(params_content) {
+                Some(start) => {
+                    let named = &params_content[start + 1..];
+                    (&params_content[..start], named.rfind('}').map_or(named, |end| &named[..end]))
+                }
+                None => (params_content, ""),
+            };
+            let params: Vec<(bool, String)> = split_constructor_params(positional).into_iter().map(|param| (false, param))
+                .chain(split_constructor_params(named).into_iter().map(|param| (true, param)))
+                .collect();
+            debug!("Extracted {} parameters", params.len());
+            // Process each parameter
+            for (is_named, param) in params {
+                debug!("Processing parameter: {}", param);
+                if let Some(mut field) = parse_dart_parameter(&param) {
+                    field.is_named = is_named;
+                    debug!("Added field: {} {} (has_default: {})", field.ty, field.name, field.has_default);
+                    fields.push(field);
                 }
             }
         }
-        eprintln!("[DEBUG] Constructor content: {}", constructor_content);
-        // Extract parameters from the constructor content
-        if let Some(start_brace) = constructor_content.find('{') {
-            if let Some(end_brace) = constructor_content.rfind('}') {
-                let params_content = &constructor_content[start_brace + 1..end_brace];
-                eprintln!("[DEBUG] Parameters content: {}", params_content);
-                // Split parameters by comma, but be careful with nested braces and comments
-                let mut params = Vec::new();
-                let mut current_param = String::new();
-                let mut brace_count = 0;
-                let mut paren_count = 0;
-                let mut in_comment = false;
-                let mut comment_type = None; // '//' or '/*'
-                for ch in params_content.chars() {
-                    match ch {
-                        '{' => {
-                            if !in_comment {
-                                brace_count += 1;
-                            }
-                            current_param.push(ch);
-                        }
-                        '}' => {
-                            if !in_comment {
-                                brace_count -= 1;
-                            }
-                            current_param.push(ch);
-                        }
-                        '(' => {
-                            if !in_comment {
-                                paren_count += 1;
-                            }
-                            current_param.push(ch);
-                        }
-                        ')' => {
-                            if !in_comment {
-                                paren_count -= 1;
-                            }
-                            current_param.push(ch);
-                        }
-                        '/' => {
-                            current_param.push(ch);
-                            // Check for comment start
-                            if !in_comment {
-                                if current_param.ends_with("//") {
-                                    in_comment = true;
-                                    comment_type = Some("//");
-                                } else if current_param.ends_with("/*") {
-                                    in_comment = true;
-                                    comment_type = Some("/*");
-                                }
-                            }
-                        }
-                        '*' => {
-                            current_param.push(ch);
-                            // Check for comment end
-                            if in_comment && comment_type == Some("/*") && current_param.ends_with("*/") {
-                                in_comment = false;
-                                comment_type = None;
-                            }
-                        }
-                        '\n' => {
-                            if in_comment && comment_type == Some("//") {
-                                in_comment = false;
-                                comment_type = None;
-                            }
-                            current_param.push(ch);
-                        }
-                        ',' => {
-                            if brace_count == 0 && paren_count == 0 && !in_comment {
-                                let trimmed = current_param.trim();
-                                if !trimmed.is_empty() {
-                                    params.push(trimmed.to_string());
-                                }
-                                current_param.clear();
-                            } else {
-                                current_param.push(ch);
-                            }
-                        }
-                        _ => current_param.push(ch),
-                    }
+    }
+    debug!("Extracted {} fields for {} (const: {})", fields.len(), class_name, is_const);
+    for field in &fields {
+        debug!("  {} {}", field.ty, field.name);
+    }
+    (fields, is_const)
+}
+
+// A single- or double-quoted Dart string literal
+const DART_STRING_PATTERN: &str = r#"'(?:[^'\\]|\\.)*'|"(?:[^"\\]|\\.)*""#;
+
+/// `@Assert('value > 0', 'message')` annotations on the class's unnamed factory, as `assert(value > 0, 'message')` calls
+fn extract_constructor_asserts(source_content: &str, class_name: &str) -> Vec<String> {
+    let constructor_pattern = regex::Regex::new(&format!(r"(const\s+)?factory\s+{}\s*\(", regex::escape(class_name))).unwrap();
+    let Some(constructor) = constructor_pattern.find(source_content) else {
+        return Vec::new();
+    };
+    // The @Assert annotations directly preceding the factory
+    let annotations_pattern = regex::Regex::new(&format!(r"(?:@Assert\((?:{}|[\s,])*\)\s*)+$", DART_STRING_PATTERN)).unwrap();
+    let Some(annotations) = annotations_pattern.find(&source_content[..constructor.start()]) else {
+        return Vec::new();
+    };
+    let assert_pattern = regex::Regex::new(&format!(r"@Assert\(\s*({0})\s*(?:,\s*({0})\s*)?,?\s*\)", DART_STRING_PATTERN)).unwrap();
+    assert_pattern
+        .captures_iter(annotations.as_str())
+        .map(|captures| {
+            let literal = &captures[1];
+            let quote = &literal[..1];
+            let condition = literal[1..literal.len() - 1].replace(&format!("\\{}", quote), quote);
+            match captures.get(2) {
+                Some(message) => format!("assert({}, {})", condition, message.as_str()),
+                None => format!("assert({})", condition),
+            }
+        })
+        .collect()
+}
+
+/// Tracks whether a character-by-character scan is inside a Dart string literal ('...', "...", r'...')
+#[derive(Default)]
+struct StringScanner {
+    quote: Option<char>,
+    raw: bool,
+    escaped: bool,
+    prev: Option<char>,
+    prev2: Option<char>,
+}
+
+impl StringScanner {
+    /// Feed the next character; true if it belongs to a string literal (quotes included)
+    fn in_string(&mut self, ch: char) -> bool {
+        let in_string = if let Some(quote) = self.quote {
+            if self.escaped {
+                self.escaped = false;
+            } else if ch == '\\' && !self.raw {
+                self.escaped = true;
+            } else if ch == quote {
+                self.quote = None;
+            }
+            true
+        } else if ch == '\'' || ch == '"' {
+            // `r'...'` is raw unless the `r` ends an identifier
+            self.raw = self.prev == Some('r') && !self.prev2.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            self.quote = Some(ch);
+            true
+        } else {
+            false
+        };
+        self.prev2 = self.prev;
+        self.prev = Some(ch);
+        in_string
+    }
+}
+
+// Byte offset of the `)` closing a parenthesis opened just before `text`, skipping string
+// literals and comments, so whatever follows it on the same line (`= _User;`) doesn't matter
+fn find_closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 1;
+    for (i, ch) in code_char_indices(text) {
+        if ch == '(' {
+            depth += 1;
+        } else if ch == ')' {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+// Byte offset of the `{` opening the named parameters in a parameter list (`int a, {required String b}`)
+fn find_named_params_start(params: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, ch) in code_char_indices(params) {
+        match ch {
+            '{' if depth == 0 => return Some(i),
+            '(' | '[' | '<' | '{' => depth += 1,
+            ')' | ']' | '>' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+// Characters of Dart code with their byte offsets, leaving out string literals and comments
+fn code_char_indices(text: &str) -> Vec<(usize, char)> {
+    let mut chars = Vec::new();
+    let mut strings = StringScanner::default();
+    let mut comment = None;
+    let mut prev = None;
+    for (i, ch) in text.char_indices() {
+        match comment {
+            Some('/') => {
+                if ch == '\n' {
+                    comment = None;
+                }
+                continue;
+            }
+            Some(_) => {
+                if prev == Some('*') && ch == '/' {
+                    comment = None;
+                    prev = None;
+                } else {
+                    prev = Some(ch);
                 }
-                // Add the last parameter if it exists
-                let trimmed = current_param.trim();
-                if !trimmed.is_empty() {
-                    params.push(trimmed.to_string());
+                continue;
+            }
+            None => {}
+        }
+        if strings.in_string(ch) {
+            prev = None;
+            continue;
+        }
+        if prev == Some('/') && (ch == '/' || ch == '*') {
+            // The `/` opening the comment was already taken as code
+            chars.pop();
+            comment = Some(ch);
+            prev = None;
+            continue;
+        }
+        prev = Some(ch);
+        chars.push((i, ch));
+    }
+    chars
+}
+
+// Byte offset of the first `//` or `/*` comment outside string literals
+fn find_comment_start(text: &str) -> Option<usize> {
+    let mut strings = StringScanner::default();
+    let mut prev = None;
+    for (i, ch) in text.char_indices() {
+        if strings.in_string(ch) {
+            prev = None;
+            continue;
+        }
+        if prev == Some('/') && (ch == '/' || ch == '*') {
+            return Some(i - 1);
+        }
+        prev = Some(ch);
+    }
+    None
+}
+
+/// Split a constructor parameter list on top-level commas, dropping comments
+fn split_constructor_params(params_content: &str) -> Vec<String> {
+    // Split parameters by comma, but be careful with nested braces, generics, strings and comments
+    let mut params = Vec::new();
+    let mut current_param = String::new();
+    let mut brace_count = 0;
+    let mut paren_count = 0;
+    let mut angle_count = 0;
+    let mut in_comment = false;
+    let mut comment_type = None; // '//' or '/*'
+    let mut strings = StringScanner::default();
+    for ch in params_content.chars() {
+        if !in_comment && strings.in_string(ch) {
+            current_param.push(ch);
+            continue;
+        }
+        match ch {
+            '{' => {
+                if !in_comment {
+                    brace_count += 1;
                 }
-                // Post-process parameters to handle multi-line parameters
-                let mut processed_params = Vec::new();
-                for param in params {
-                    let lines: Vec<&str> = param.lines().collect();
-                    let mut processed_param = String::new();
-                    for line in lines {
-                        let trimmed_line = line.trim();
-                        // Skip comment-only lines
-                        if trimmed_line.starts_with("//") || trimmed_line.starts_with("/*") {
-                            continue;
-                        }
-                        // Skip empty lines
-                        if trimmed_line.is_empty() {
-                            continue;
-                        }
-                        // Skip standalone comment words
-                        let comment_words = ["draft", "published", "cancelled", "completed", "pending", "succeeded", "failed"];
-                        if comment_words.iter().any(|&word| trimmed_line == word) {
-                            continue;
-                        }
-                        if !processed_param.is_empty() {
-                            processed_param.push(' ');
-                        }
-                        processed_param.push_str(trimmed_line);
-                    }
-                    if !processed_param.is_empty() {
-                        processed_params.push(processed_param);
+                current_param.push(ch);
+            }
+            '}' => {
+                if !in_comment {
+                    brace_count -= 1;
+                }
+                current_param.push(ch);
+            }
+            '(' => {
+                if !in_comment {
+                    paren_count += 1;
+                }
+                current_param.push(ch);
+            }
+            ')' => {
+                if !in_comment {
+                    paren_count -= 1;
+                }
+                current_param.push(ch);
+            }
+            '<' => {
+                if !in_comment {
+                    angle_count += 1;
+                }
+                current_param.push(ch);
+            }
+            '>' => {
+                if !in_comment && angle_count > 0 {
+                    angle_count -= 1;
+                }
+                current_param.push(ch);
+            }
+            '/' => {
+                current_param.push(ch);
+                // Check for comment start
+                if !in_comment {
+                    if current_param.ends_with("//") {
+                        in_comment = true;
+                        comment_type = Some("//");
+                    } else if current_param.ends_with("/*") {
+                        in_comment = true;
+                        comment_type = Some("/*");
                     }
+                } else if comment_type == Some("/*") && current_param.ends_with("*/") {
+                    in_comment = false;
+                    comment_type = None;
+                }
+            }
+            '\n' => {
+                if in_comment && comment_type == Some("//") {
+                    in_comment = false;
+                    comment_type = None;
                 }
-                params = processed_params;
-                eprintln!("[DEBUG] Extracted {} parameters", params.len());
-                // Process each parameter
-                for param in params {
-                    eprintln!("[DEBUG] Processing parameter: {}", param);
-                    if let Some(field) = parse_dart_parameter(&param) {
-                        let field_clone = field.clone();
-                        fields.push(field);
-                        eprintln!("[DEBUG] Added field: {} {} (has_default: {})", field_clone.ty, field_clone.name, field_clone.has_default);
+                current_param.push(ch);
+            }
+            ',' => {
+                if brace_count == 0 && paren_count == 0 && angle_count == 0 && !in_comment {
+                    let trimmed = current_param.trim();
+                    if !trimmed.is_empty() {
+                        params.push(trimmed.to_string());
                     }
+                    current_param.clear();
+                } else {
+                    current_param.push(ch);
+                }
+            }
+            _ => current_param.push(ch),
+        }
+    }
+    // Add the last parameter if it exists
+    let trimmed = current_param.trim();
+    if !trimmed.is_empty() {
+        params.push(trimmed.to_string());
+    }
+    // Post-process parameters to handle multi-line parameters
+    let mut processed_params = Vec::new();
+    for param in params {
+        let mut processed_param = String::new();
+        for line in param.lines() {
+            let trimmed_line = line.trim();
+            // Skip comment-only lines
+            if trimmed_line.starts_with("//") || trimmed_line.starts_with("/*") {
+                continue;
+            }
+            // Skip empty lines
+            if trimmed_line.is_empty() {
+                continue;
+            }
+            if !processed_param.is_empty() {
+                processed_param.push(' ');
+            }
+            processed_param.push_str(trimmed_line);
+        }
+        if !processed_param.is_empty() {
+            processed_params.push(processed_param);
+        }
+    }
+    processed_params
+}
+
+/// Parse a single constructor parameter such as `@Default(0) int count` or `required String name`.
+/// Shared by all constructor/union-case extraction paths so they agree on field metadata.
+pub fn parse_dart_parameter(param: &str) -> Option<DartField> {
+    let param = param.trim();
+    // Skip comments and empty parameters
+    if param.starts_with("//") || param.starts_with("/*") || param.is_empty() {
+        return None;
+    }
+    // Skip lines that are just comments or comment fragments
+    if param.chars().all(|c| c.is_whitespace() || c == '/') {
+        return None;
+    }
+    // Clean up the parameter by removing any trailing comments
+    let param = match find_comment_start(param) {
+        Some(comment_start) => &param[..comment_start],
+        None => param,
+    };
+    let mut param = param.trim().trim_end_matches(',').trim().to_string();
+    if param.is_empty() {
+        return None;
+    }
+    // Check if this is a named parameter (contains ':' or is in a named parameter context)
+    let is_named = param.contains(':') || param.contains('{') || param.contains('}');
+    param = param.trim_start_matches('{').trim_end_matches('}').trim().to_string();
+    // Remove @Default annotation (its value may itself contain parentheses, also inside strings)
+    let mut default_value = take_annotation_arguments(&mut param, "@Default");
+    if let Some(default_val) = &default_value {
+        debug!("Found @Default annotation: {} = {}", param, default_val);
+    }
+    // json_serializable's `@JsonKey(defaultValue: ...)` supplies the fallback for a missing key just the same
+    let mut omit_if_null = false;
+    if let Some(json_key_args) = take_annotation_arguments(&mut param, "@JsonKey") {
+        let json_key_args = split_top_level_arguments(&json_key_args);
+        let json_key_arg = |key: &str| json_key_args.iter().find_map(|arg| arg.strip_prefix(key).and_then(|rest| rest.trim_start().strip_prefix(':')).map(|value| value.trim().to_string()));
+        if default_value.is_none() {
+            default_value = json_key_arg("defaultValue");
+        }
+        omit_if_null = json_key_arg("includeIfNull").as_deref() == Some("false");
+    }
+    let has_default = default_value.is_some();
+    let converter = take_converter_annotation(&mut param);
+    // Remove required keyword
+    let param = param.strip_prefix("required ").unwrap_or(&param).trim();
+    let (ty, name) = split_type_and_name(param)?;
+    Some(DartField {
+        name,
+        ty,
+        is_named,
+        has_default,
+        default_value,
+        omit_if_null,
+        converter,
+    })
+}
+
+// The name is the trailing identifier and everything before it the type, which may contain
+// spaces inside brackets (`Map<String, List<int>> data`, `void Function(int a) cb`).
+// A positional default (`int count = 0`) is dropped.
+fn split_type_and_name(param: &str) -> Option<(String, String)> {
+    let mut depth = 0;
+    let mut end = param.len();
+    for (i, ch) in param.char_indices() {
+        match ch {
+            '<' | '(' | '[' | '{' => depth += 1,
+            '>' | ')' | ']' | '}' => depth -= 1,
+            '=' if depth == 0 => {
+                end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let declaration = param[..end].trim_end();
+    let name_start = declaration
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let name = &declaration[name_start..];
+    let ty = declaration[..name_start].trim();
+    // The name must start like an identifier and the type can't end mid-expression (e.g. `a.b`)
+    if ty.is_empty() || name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) || ty.ends_with('.') {
+        return None;
+    }
+    Some((ty.to_string(), name.to_string()))
+}
+
+/// Whether a declaration such as `List<int>? xs` has a nullable type: a `?` outside any brackets
+/// (before a default value), so `Map<String, int?> m` is not nullable.
+pub(crate) fn has_nullable_type(declaration: &str) -> bool {
+    let mut depth = 0;
+    for ch in declaration.chars() {
+        match ch {
+            '<' | '(' | '[' | '{' => depth += 1,
+            '>' | ')' | ']' | '}' => depth -= 1,
+            '?' if depth == 0 => return true,
+            '=' if depth == 0 => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+// Remove `@Name(...)` from a parameter and return its arguments. The arguments may themselves
+// contain parentheses, also inside string literals.
+fn take_annotation_arguments(param: &mut String, annotation: &str) -> Option<String> {
+    let open = format!("{}(", annotation);
+    let start = param.find(&open)?;
+    let value_start = start + open.len();
+    let mut depth = 1;
+    let mut strings = StringScanner::default();
+    for (i, ch) in param[value_start..].char_indices() {
+        if strings.in_string(ch) {
+            continue;
+        }
+        if ch == '(' {
+            depth += 1;
+        } else if ch == ')' {
+            depth -= 1;
+            if depth == 0 {
+                let value_end = value_start + i;
+                let args = param[value_start..value_end].trim().to_string();
+                *param = format!("{}{}", &param[..start], &param[value_end + 1..]).trim().to_string();
+                return Some(args);
+            }
+        }
+    }
+    None
+}
+
+// Annotations that never name a JsonConverter
+const NON_CONVERTER_ANNOTATIONS: [&str; 7] = ["Deprecated", "deprecated", "override", "protected", "visibleForTesting", "internal", "experimental"];
+
+// Strip the remaining leading annotations (after @Default/@JsonKey) and return the converter
+// among them: `@ColorConverter()` becomes `const ColorConverter()`, `@colorConverter` stays as is
+fn take_converter_annotation(param: &mut String) -> Option<String> {
+    let annotation_name = regex::Regex::new(r"^@([A-Za-z_$][\w$]*)").unwrap();
+    let mut converter = None;
+    while let Some(captures) = annotation_name.captures(param) {
+        let name = captures[1].to_string();
+        let rest = param[captures[0].len()..].to_string();
+        let instance = if rest.starts_with('(') {
+            let args = take_annotation_arguments(param, &format!("@{}", name))?;
+            format!("const {}({})", name, args)
+        } else {
+            *param = rest.trim().to_string();
+            name.clone()
+        };
+        if !NON_CONVERTER_ANNOTATIONS.contains(&name.as_str()) {
+            converter = Some(instance);
+        }
+    }
+    converter
+}
+
+// Split annotation arguments on commas outside brackets and string literals
+fn split_top_level_arguments(args: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut strings = StringScanner::default();
+    for ch in args.chars() {
+        if !strings.in_string(ch) {
+            match ch {
+                '(' | '[' | '{' | '<' => depth += 1,
+                ')' | ']' | '}' | '>' => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(current.trim().to_string());
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        current.push(ch);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    parts
+}
+
+// Dart 3 unions: `sealed class X` whose `class A extends X` subclasses become cases with their final fields
+fn extract_sealed_subclass_cases(source_content: &str, class_name: &str) -> Vec<CaseInfo> {
+    let class_name_escaped = regex::escape(class_name);
+    let sealed_pattern = regex::Regex::new(&format!(r"\bsealed\s+class\s+{}\b", class_name_escaped)).unwrap();
+    if !sealed_pattern.is_match(source_content) {
+        return Vec::new();
+    }
+    let subclass_pattern = regex::Regex::new(&format!(r"\bclass\s+(\w+)\s+extends\s+{}\b[^{{;]*\{{", class_name_escaped)).unwrap();
+    let mut cases = Vec::new();
+    for cap in subclass_pattern.captures_iter(source_content) {
+        let subclass_name = &cap[1];
+        let body = braced_body(source_content, cap.get(0).unwrap().end());
+        cases.push(CaseInfo { case_name: sealed_case_name(subclass_name, class_name), fields: final_fields_of_class_body(body, subclass_name) });
+    }
+    cases
+}
+
+// Text up to the `}` closing the block that starts at body_start (just after its `{`)
+fn braced_body(source_content: &str, body_start: usize) -> &str {
+    let mut depth = 1;
+    let mut body_end = source_content.len();
+    for (offset, ch) in source_content[body_start..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    body_end = body_start + offset;
+                    break;
                 }
             }
+            _ => {}
         }
     }
-    eprintln!("[DEBUG] Extracted {} fields for {}", fields.len(), class_name);
-    for field in &fields {
-        eprintln!("  {} {}", field.ty, field.name);
+    &source_content[body_start..body_end]
+}
+
+// `final T name;` fields of a hand-written class, named if its constructor takes `{...}`
+fn final_fields_of_class_body(body: &str, class_name: &str) -> Vec<DartField> {
+    let field_pattern = regex::Regex::new(r"^final\s+(.+?)\s+(\w+)\s*;").unwrap();
+    let named_constructor = regex::Regex::new(&format!(r"\b{}\s*\(\s*\{{", regex::escape(class_name))).unwrap();
+    let is_named = named_constructor.is_match(body);
+    body.lines()
+        .filter_map(|line| field_pattern.captures(line.trim()))
+        .map(|field| DartField {
+            name: field[2].to_string(),
+            ty: field[1].to_string(),
+            is_named,
+            has_default: false,
+            default_value: None,
+            omit_if_null: false,
+            converter: None,
+        })
+        .collect()
+}
+
+/// Fields of a plain (non-freezed) class, in the order its positional constructor takes them
+pub fn extract_plain_class_fields(source_content: &str, class_name: &str) -> Vec<DartField> {
+    let class_pattern = regex::Regex::new(&format!(r"\bclass\s+{}\b[^{{;]*\{{", regex::escape(class_name))).unwrap();
+    let Some(class_match) = class_pattern.find(source_content) else { return Vec::new() };
+    let body = braced_body(source_content, class_match.end());
+    let mut fields = final_fields_of_class_body(body, class_name);
+    let positional_constructor = regex::Regex::new(&format!(r"\b{}\s*\(([^{{)]*)\)", regex::escape(class_name))).unwrap();
+    if let Some(cap) = positional_constructor.captures(body) {
+        let order: Vec<&str> = cap[1].split(',').map(|param| param.trim().trim_start_matches("this.")).collect();
+        fields.sort_by_key(|field| order.iter().position(|name| *name == field.name).unwrap_or(usize::MAX));
     }
     fields
 }
 
-fn parse_dart_parameter(param: &str) -> Option<DartField> {
-    let param = param.trim();
-    // Skip comments and empty parameters
-    if param.starts_with("//") || param.starts_with("/*") || param.is_empty() {
-        return None;
+/// `copyWith` for a plain @JsonSerializable class, as an extension in its .g.dart file.
+/// As with freezed's `copyWith`, a nullable field can't be reset to null through it.
+pub fn generate_json_copy_with(class: &DartClass, fields: &[DartField]) -> String {
+    let mut code = format!("extension {}CopyWith on {} {{\n", class.name, class.name);
+    if fields.is_empty() {
+        code.push_str(&format!("  {} copyWith() => this;\n}}\n\n", class.name));
+        return code;
     }
-    // Skip lines that are just comments or comment fragments
-    if param.chars().all(|c| c.is_whitespace() || c == '/') {
-        return None;
+    code.push_str(&format!("  {} copyWith({{\n", class.name));
+    for field in fields {
+        let ty = if field.ty.ends_with('?') { field.ty.clone() } else { format!("{}?", field.ty) };
+        code.push_str(&format!("    {} {},\n", ty, field.name));
     }
-    // Skip standalone comment words like "draft", "published", "cancelled", "completed"
-    let comment_words = ["draft", "published", "cancelled", "completed", "pending", "succeeded", "failed"];
-    if comment_words.iter().any(|&word| param == word) {
-        return None;
+    code.push_str("  }) {\n");
+    code.push_str(&format!("    return {}(\n", class.name));
+    for field in fields {
+        if field.is_named {
+            code.push_str(&format!("      {}: {} ?? this.{},\n", field.name, field.name, field.name));
+        } else {
+            code.push_str(&format!("      {} ?? this.{},\n", field.name, field.name));
+        }
     }
-    // Skip if the parameter contains comment fragments at the beginning
-    if comment_words.iter().any(|&word| param.starts_with(word)) {
-        return None;
+    code.push_str("    );\n");
+    code.push_str("  }\n");
+    code.push_str("}\n\n");
+    code
+}
+
+/// Abstract getters declared on a union class (`String get id;`), i.e. fields shared by every case
+pub fn union_shared_fields(source_content: &str, class_name: &str) -> Vec<DartField> {
+    let class_pattern = regex::Regex::new(&format!(r"\bclass\s+{}\b[^{{;]*\{{", regex::escape(class_name))).unwrap();
+    let Some(class_match) = class_pattern.find(source_content) else { return Vec::new() };
+    let getter_pattern = regex::Regex::new(r"^(?:@override\s+)?(.+?)\s+get\s+(\w+)\s*;").unwrap();
+    braced_body(source_content, class_match.end())
+        .lines()
+        .filter_map(|line| getter_pattern.captures(line.trim()))
+        .map(|getter| DartField {
+            name: getter[2].to_string(),
+            ty: getter[1].to_string(),
+            is_named: true,
+            has_default: false,
+            default_value: None,
+            omit_if_null: false,
+            converter: None,
+        })
+        .collect()
+}
+
+// `ResultSuccess`/`Success` extending `Result` -> `success`
+fn sealed_case_name(subclass_name: &str, class_name: &str) -> String {
+    let name = subclass_name.trim_start_matches('_');
+    let name = match name.strip_prefix(class_name) {
+        Some(rest) if !rest.is_empty() => rest,
+        _ => name,
+    };
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
     }
-    // Parse type, name, and default value
-    let mut ty = String::new();
-    let mut name = String::new();
-    let mut is_named = false;
-    let mut has_default = false;
-    let mut default_value = None;
-    let mut param = param.to_string();
-    // Remove @Default annotation
-    if let Some(default_start) = param.find("@Default(") {
-        if let Some(default_end) = param[default_start..].find(')') {
-            let default_val = &param[default_start + 9..default_start + default_end];
-            has_default = true;
-            default_value = Some(default_val.trim().to_string());
-            eprintln!("[DEBUG] Found @Default annotation: {} = {}", param, default_val);
-            // Remove the @Default(...) part
-            let before = &param[..default_start];
-            let after = &param[default_start + default_end + 1..];
-            param = format!("{}{}", before, after).trim().to_string();
+}
+
+// '(' minus ')' on a line, not counting the ones inside string literals
+fn paren_balance(line: &str) -> i32 {
+    let mut balance = 0;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(_), '\\') => { chars.next(); }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, '(') => balance += 1,
+            (None, ')') => balance -= 1,
+            _ => {}
         }
     }
-    // Remove required keyword
-    let param = param.trim_start_matches("required ").trim();
-    // Parse type and name
-    let mut parts = param.split_whitespace();
-    if let Some(first) = parts.next() {
-        ty = first.to_string();
-        if let Some(second) = parts.next() {
-            name = second.trim_end_matches(',').to_string();
-            // Check if this is a named parameter (contains ':' or is in a named parameter context)
-            is_named = param.contains(':') || param.contains('{') || param.contains('}');
-        }
-    }
-    if ty.is_empty() || name.is_empty() {
-        return None;
-    }
-    Some(DartField {
-        name,
-        ty,
-        is_named,
-        has_default,
-        default_value,
-    })
+    balance
 }
 
 pub fn extract_union_cases_from_dart_class(source_content: &str, class_name: &str) -> Vec<CaseInfo> {
-    eprintln!("[DEBUG] extract_union_cases_from_dart_class called for {}", class_name);
+    debug!("extract_union_cases_from_dart_class called for {}", class_name);
     let mut cases = Vec::new();
     let lines: Vec<&str> = source_content.lines().collect();
     let mut in_class = false;
-    let mut brace_count = 0;
+    let mut brace_count: isize = 0;
     let mut in_factory = false;
     let mut factory_lines = Vec::new();
+    // Open parentheses of the factory being accumulated; it ends when they are all closed
+    let mut factory_parens = 0;
+    let mut factory_opened = false;
     for line in lines.iter() {
         let trimmed = line.trim();
-        if trimmed.starts_with(&format!("class {}", class_name)) {
+        let declaration = trimmed.strip_prefix("sealed ").or_else(|| trimmed.strip_prefix("abstract ")).unwrap_or(trimmed);
+        if declaration.starts_with(&format!("class {}", class_name)) {
             in_class = true;
             brace_count = 0;
-            brace_count += trimmed.chars().filter(|&c| c == '{').count();
-            brace_count -= trimmed.chars().filter(|&c| c == '}').count();
+            brace_count += trimmed.chars().filter(|&c| c == '{').count() as isize;
+            brace_count -= trimmed.chars().filter(|&c| c == '}').count() as isize;
             continue;
         }
         if in_class {
-            brace_count += trimmed.chars().filter(|&c| c == '{').count();
-            brace_count -= trimmed.chars().filter(|&c| c == '}').count();
+            brace_count += trimmed.chars().filter(|&c| c == '{').count() as isize;
+            brace_count -= trimmed.chars().filter(|&c| c == '}').count() as isize;
             if brace_count <= 0 {
                 break;
             }
             if trimmed.contains("const factory") {
                 in_factory = true;
                 factory_lines.clear();
+                factory_parens = 0;
+                factory_opened = false;
             }
             if in_factory {
                 factory_lines.push(trimmed);
-                if trimmed.contains(")") || trimmed.contains(";" ) {
+                factory_parens += paren_balance(trimmed);
+                factory_opened |= trimmed.contains('(');
+                if factory_parens <= 0 && (factory_opened || trimmed.contains(';')) {
                     let factory_decl = factory_lines.join(" ");
                     in_factory = false;
                     if let Some(dot_pos) = factory_decl.find(&format!("{}.", class_name)) {
                         let after_dot = &factory_decl[dot_pos + class_name.len() + 1..];
                         if let Some(paren_pos) = after_dot.find('(') {
                             let case_name = &after_dot[..paren_pos].trim();
-                            eprintln!("[DEBUG] Case name: {}", case_name);
+                            debug!("Case name: {}", case_name);
                             let mut params_content = String::new();
                             let mut paren_level = 0;
                             let mut found_start = false;
+                            let mut quote = None;
                             for ch in after_dot[paren_pos..].chars() {
+                                // Parentheses in string literals (e.g. default values) don't nest
+                                if let Some(q) = quote {
+                                    if ch == q {
+                                        quote = None;
+                                    }
+                                    params_content.push(ch);
+                                    continue;
+                                }
+                                if found_start && (ch == '\'' || ch == '"') {
+                                    quote = Some(ch);
+                                }
                                 if ch == '(' {
                                     paren_level += 1;
                                     found_start = true;
@@ -1296,12 +2292,13 @@ pub fn extract_union_cases_from_dart_class(source_content: &str, class_name: &st
                                         if param_trimmed.is_empty() || param_trimmed.starts_with("//") {
                                             continue;
                                         }
-                                        eprintln!("[DEBUG] Processing union case parameter: {}", param_trimmed);
-                                        if let Some(field) = parse_dart_parameter(param_trimmed) {
+                                        debug!("Processing union case parameter: {}", param_trimmed);
+                                        if let Some(mut field) = parse_dart_parameter(param_trimmed) {
+                                            field.is_named = true;
                                             if !case_fields.iter().any(|f: &DartField| f.name == field.name) {
                                                 let field_clone = field.clone();
                                                 case_fields.push(field);
-                                                eprintln!("[DEBUG] Added union case field: {} {}", field_clone.ty, field_clone.name);
+                                                debug!("Added union case field: {} {}", field_clone.ty, field_clone.name);
                                             }
                                         }
                                     }
@@ -1335,12 +2332,13 @@ pub fn extract_union_cases_from_dart_class(source_content: &str, class_name: &st
                                         if param_trimmed.is_empty() || param_trimmed.starts_with("//") {
                                             continue;
                                         }
-                                        eprintln!("[DEBUG] Processing union case parameter: {}", param_trimmed);
-                                        if let Some(field) = parse_dart_parameter(param_trimmed) {
+                                        debug!("Processing union case parameter: {}", param_trimmed);
+                                        if let Some(mut field) = parse_dart_parameter(param_trimmed) {
+                                            field.is_named = false;
                                             if !case_fields.iter().any(|f: &DartField| f.name == field.name) {
                                                 let field_clone = field.clone();
                                                 case_fields.push(field);
-                                                eprintln!("[DEBUG] Added union case field: {} {}", field_clone.ty, field_clone.name);
+                                                debug!("Added union case field: {} {}", field_clone.ty, field_clone.name);
                                             }
                                         }
                                     }
@@ -1361,31 +2359,1048 @@ pub fn extract_union_cases_from_dart_class(source_content: &str, class_name: &st
             }
         }
     }
-    eprintln!("[DEBUG] union cases for {}:", class_name);
+    for case in extract_sealed_subclass_cases(source_content, class_name) {
+        if !cases.iter().any(|c| c.case_name == case.case_name) {
+            cases.push(case);
+        }
+    }
+    // Fields every case shares are declared once as getters on the union class
+    for shared in union_shared_fields(source_content, class_name) {
+        for case in cases.iter_mut().filter(|case| !case.fields.iter().any(|f| f.name == shared.name)) {
+            let is_named = case.fields.first().map_or(true, |f| f.is_named);
+            case.fields.insert(0, DartField { is_named, ..shared.clone() });
+        }
+    }
+    debug!("union cases for {}:", class_name);
     for case in &cases {
-        eprintln!("  case: {}", case.case_name);
+        debug!("  case: {}", case.case_name);
         for field in &case.fields {
-            eprintln!("    field: {} {}", field.ty, field.name);
+            debug!("    field: {} {}", field.ty, field.name);
         }
     }
     cases
 }
 
-pub fn get_safe_output_paths(file_path: &Path) -> (PathBuf, PathBuf) {
-    let file_stem = file_path.file_stem().unwrap().to_string_lossy();
-    let base_name = if file_stem.ends_with(".freezed") {
-        &file_stem[..file_stem.len() - 8]
-    } else if file_stem.ends_with(".g") {
-        &file_stem[..file_stem.len() - 2]
-    } else {
-        &file_stem
+/// Path of the source file relative to the output directory, as used in `part of` (e.g. "../models/user.dart")
+pub fn part_of_path(file_path: &Path, output_dir: &Path) -> String {
+    let source_dir = file_path.parent().unwrap_or_else(|| Path::new(""));
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    if source_dir == output_dir {
+        return file_name;
+    }
+    relative_import(&absolute_path(output_dir), &absolute_path(file_path))
+}
+
+/// Where generated files are written relative to their source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputLayout {
+    /// Next to the source file (`lib/models/user.g.dart`)
+    #[default]
+    Sibling,
+    /// Under the output directory, mirroring the path from the input root (`lib/gen/models/user.g.dart`)
+    Mirror,
+}
+
+impl std::str::FromStr for OutputLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sibling" => Ok(OutputLayout::Sibling),
+            "mirror" => Ok(OutputLayout::Mirror),
+            _ => Err(format!("unknown output layout '{}' (expected sibling or mirror)", s)),
+        }
+    }
+}
+
+/// Output `.freezed.dart`/`.g.dart` paths for file_path.
+/// input_root and output_dir are only used by the mirror layout.
+/// Output names come from the full source name (`user.dart` -> `user.freezed.dart`), the same
+/// name the outputs' `part of` directive points back to, so the two always agree.
+pub fn get_safe_output_paths(file_path: &Path, layout: OutputLayout, input_root: &Path, output_dir: &Path) -> (PathBuf, PathBuf) {
+    let base_name = file_path.file_stem().unwrap().to_string_lossy();
+    let source_dir = file_path.parent().unwrap();
+    let target_dir = match layout {
+        OutputLayout::Sibling => source_dir.to_path_buf(),
+        OutputLayout::Mirror => output_dir.join(source_dir.strip_prefix(input_root).unwrap_or(Path::new(""))),
     };
-    // Always output to the same directory as the source file
-    let mut freezed_output_path = file_path.parent().unwrap().to_path_buf();
-    freezed_output_path.push(format!("{}.freezed.dart", base_name));
-    let mut g_dart_output_path = file_path.parent().unwrap().to_path_buf();
-    g_dart_output_path.push(format!("{}.g.dart", base_name));
+    let freezed_output_path = target_dir.join(format!("{}.freezed.dart", base_name));
+    let g_dart_output_path = target_dir.join(format!("{}.g.dart", base_name));
     (freezed_output_path, g_dart_output_path)
 }
 
- 
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    // A @freezed class parsed from an in-memory model.dart; the generators get its source passed in
+    fn model_class(class_name: &str) -> DartClass {
+        DartClass {
+            name: class_name.to_string(),
+            annotations: vec!["@freezed".to_string()],
+            file_path: PathBuf::from("model.dart"),
+        }
+    }
+
+    #[test]
+    fn test_part_of_matches_output_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n}\n";
+        for (source_name, freezed_name) in [("user.dart", "user.freezed.dart"), ("user.model.dart", "user.model.freezed.dart")] {
+            let file_path = temp_dir.path().join(source_name);
+            fs::write(&file_path, source).unwrap();
+            let (freezed_path, g_dart_path) = get_safe_output_paths(&file_path, OutputLayout::Sibling, temp_dir.path(), temp_dir.path());
+            assert_eq!(freezed_path, temp_dir.path().join(freezed_name));
+            assert_eq!(g_dart_path, temp_dir.path().join(freezed_name.replace(".freezed.", ".g.")));
+
+            let class = DartClass { name: "User".to_string(), annotations: vec!["@freezed".to_string()], file_path: file_path.clone() };
+            let result = generate_freezed_file(&file_path, source, &[class], temp_dir.path(), &GenOptions::default()).unwrap();
+            let part_of = format!("part of '{}';", source_name);
+            for code in [&result.freezed_code, &result.g_dart_code] {
+                // The first line that isn't a comment is the part-of directive
+                assert_eq!(code.lines().find(|line| !line.is_empty() && !line.starts_with("//")), Some(part_of.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_dart_parameter() {
+        let field = parse_dart_parameter("@Default(0) int count").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("count", "int"));
+        assert!(field.has_default);
+        assert_eq!(field.default_value.as_deref(), Some("0"));
+
+        let field = parse_dart_parameter("required String name").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("name", "String"));
+        assert!(!field.has_default);
+        assert_eq!(field.default_value, None);
+
+        let field = parse_dart_parameter("int? age").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("age", "int?"));
+        assert!(!field.has_default);
+
+        let field = parse_dart_parameter("@Default([]) List<String> tags").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("tags", "List<String>"));
+        assert_eq!(field.default_value.as_deref(), Some("[]"));
+
+        let field = parse_dart_parameter("@Default(Duration(seconds: 1)) Duration timeout, // comment").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("timeout", "Duration"));
+        assert_eq!(field.default_value.as_deref(), Some("Duration(seconds: 1)"));
+
+        let field = parse_dart_parameter("required Map<String, int> scores").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("scores", "Map<String, int>"));
+
+        assert!(parse_dart_parameter("// just a comment").is_none());
+    }
+
+    #[test]
+    fn test_parameters_named_like_status_words_are_kept() {
+        let source = r#"
+@freezed
+class Job with _$Job {
+  const factory Job({
+    // pending, failed or draft
+    required int pendingCount,
+    /* counts retries
+       failed */
+    @Default(0) int failedAttempts,
+  }) = _Job;
+}
+"#;
+        let fields = extract_fields_from_dart_class(source, "Job");
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, vec!["pendingCount", "failedAttempts"]);
+    }
+
+    #[test]
+    fn test_union_type_getter_skipped_for_standalone_case_type() {
+        let source = r#"
+@freezed
+class Result with _$Result {
+  const factory Result.success({required String value}) = Success;
+  const factory Result.failure({required String message}) = _Failure;
+}
+
+@JsonSerializable()
+class Success {
+  final String value;
+  Success(this.value);
+}
+"#;
+        let class = model_class("Result");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert_eq!(code.matches("String get $type").count(), 1);
+        assert!(code.contains("String get $type => 'failure';"));
+        assert!(!code.contains("String get $type => 'success';"));
+    }
+
+    #[test]
+    fn test_union_pattern_methods_match_mixin_signatures() {
+        let source = r#"
+@freezed
+class Shape with _$Shape {
+  const factory Shape.empty() = _Empty;
+  const factory Shape.circle({required double radius}) = _Circle;
+  const factory Shape.rect({required double width, required double height}) = _Rect;
+}
+"#;
+        let class = model_class("Shape");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        
+        assert!(code.contains("    required TResult Function(double width, double height) rect,\n"));
+        assert!(code.contains("    TResult Function()? empty,\n    TResult Function(double radius)? circle,"));
+        assert!(code.contains("    TResult? Function(ShapeRect)? rect,\n  })"));
+        // Each case overrides every method with exactly the mixin's signature
+        for method in PATTERN_METHODS {
+            let signature = pattern_method_signature("Shape", method, &extract_union_cases_from_dart_class(source, "Shape"));
+            assert_eq!(code.matches(&format!("{} => throw _privateConstructorUsedError;", signature)).count(), 1, "{}", method);
+            assert_eq!(code.matches(&format!("  @override\n  @optionalTypeArgs\n{} {{", signature)).count(), 3, "{}", method);
+        }
+        assert!(code.contains("    if (rect != null) {\n      return rect(width, height);\n    }\n    return orElse();"));
+        assert!(code.contains("    if (circle != null) {\n      return circle(this);\n    }\n    return orElse();"));
+        assert!(code.contains("    return empty?.call();"));
+    }
+
+    #[test]
+    fn test_equality_uses_deep_collection_equality_for_collections() {
+        let source = r#"
+@freezed
+class Team with _$Team {
+  const factory Team({required String name, required List<String> members}) = _Team;
+}
+"#;
+        let class = model_class("Team");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains(
+            "        (other.runtimeType == runtimeType &&\n            other is _$$TeamImplImpl &&\n            (identical(other.name, name) || other.name == name) &&\n            const DeepCollectionEquality().equals(other.members, members));\n  }\n"
+        ));
+        assert!(code.contains("      name,\n      const DeepCollectionEquality().hash(members),\n  );"));
+    }
+
+    #[test]
+    fn test_freezed_options_skip_to_string_and_equality() {
+        let source = r#"
+@Freezed(toStringOverride: false, equal: false)
+class Point with _$Point {
+  const factory Point({required int x, required int y}) = _Point;
+}
+"#;
+        let mut class = model_class("Point");
+        class.annotations = vec!["@Freezed(toStringOverride: false, equal: false)".to_string()];
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(!code.contains("String toString()"));
+        assert!(!code.contains("bool operator ==(Object other)"));
+        assert!(!code.contains("int get hashCode"));
+
+        class.annotations = vec!["@freezed".to_string()];
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("String toString()"));
+        assert!(code.contains("bool operator ==(Object other)"));
+        assert!(code.contains("int get hashCode"));
+    }
+
+    #[test]
+    fn test_make_collections_unmodifiable() {
+        let source = r#"
+@Freezed(makeCollectionsUnmodifiable: true)
+class Team with _$Team {
+  const factory Team({required String name, required List<String> members, Map<String, int>? scores}) = _Team;
+}
+"#;
+        let mut class = model_class("Team");
+        class.annotations = vec!["@Freezed(makeCollectionsUnmodifiable: true)".to_string()];
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("required final List<String> members,"));
+        assert!(code.contains("final Map<String, int>? scores,"));
+        assert!(code.contains("required this.name,"));
+        assert!(code.contains("      : _members = members,\n        _scores = scores;"));
+        assert!(code.contains("  final List<String> _members;\n  @override\n  List<String> get members {"));
+        assert!(code.contains("    return EqualUnmodifiableListView(_members);"));
+        assert!(code.contains("    if (value == null) return null;\n    if (_scores is EqualUnmodifiableMapView) return _scores;"));
+        assert!(code.contains("  const _$$TeamImplImpl("));
+
+        // Without the option the fields are stored as given
+        class.annotations = vec!["@freezed".to_string()];
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("required this.members,"));
+        assert!(code.contains("  final List<String> members;"));
+        assert!(!code.contains("EqualUnmodifiable"));
+    }
+
+    #[test]
+    fn test_non_const_factory_is_mirrored() {
+        let source = r#"
+@freezed
+class Counter with _$Counter {
+  factory Counter({required List<int> values}) = _Counter;
+}
+"#;
+        assert!(!extract_constructor_from_dart_class(source, "Counter").1);
+        let class = model_class("Counter");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("  _$$CounterImplImpl(\n"));
+        assert!(code.contains("  factory _$CounterImpl(\n"));
+        assert!(!code.contains("const _$$CounterImplImpl("));
+        assert!(!code.contains("const factory _$CounterImpl("));
+
+        let (fields, is_const) = extract_constructor_from_dart_class(&source.replace("  factory", "  const factory"), "Counter");
+        assert!(is_const);
+        assert_eq!(fields.len(), 1);
+    }
+
+    #[test]
+    fn test_explicit_to_json_for_nested_models() {
+        let source = r#"
+@freezed
+class Order with _$Order {
+  const factory Order({
+    required String id,
+    required Customer customer,
+    Address? shipping,
+    required List<Item> items,
+  }) = _Order;
+}
+"#;
+        let mut class = model_class("Order");
+        let code = generate_json_code(&class, source, &GenOptions::default());
+        assert!(code.contains("'customer': instance.customer,"));
+
+        class.annotations = vec!["@JsonSerializable(explicitToJson: true)".to_string()];
+        let code = generate_json_code(&class, source, &GenOptions::default());
+        assert!(code.contains("'id': instance.id,"));
+        assert!(code.contains("'customer': instance.customer.toJson(),"));
+        assert!(code.contains("'shipping': instance.shipping?.toJson(),"));
+        assert!(code.contains("'items': instance.items.map((e) => e.toJson()).toList(),"));
+    }
+
+    #[test]
+    fn test_generated_files_include_version_signature() {
+        let source = "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n}\n";
+        let class = model_class("User");
+        let result = generate_freezed_file(&class.file_path, source, &[class.clone()], class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        let signature = format!("// SuperFastGen v{}", env!("CARGO_PKG_VERSION"));
+        assert!(result.freezed_code.contains(&signature));
+        assert!(result.g_dart_code.contains(&signature));
+    }
+
+    #[test]
+    fn test_declares_from_json_factory() {
+        let source = r#"
+@freezed
+class User with _$User {
+  const factory User({required String id}) = _User;
+
+  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);
+}
+"#;
+        assert!(declares_from_json_factory(source, "User"));
+        assert!(!declares_from_json_factory(source, "Post"));
+
+        let class = model_class("User");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("User _$UserFromJson(Map<String, dynamic> json) {"));
+    }
+
+    #[test]
+    fn test_enum_json_value_map() {
+        let source = r#"
+enum Status {
+  @JsonValue('ACTIVE')
+  active,
+  inactive, // not annotated
+  @JsonValue('SUSPENDED') suspended,
+}
+
+enum Priority { @JsonValue(1) low, @JsonValue(2) high }
+
+@freezed
+class Account with _$Account {
+  const factory Account({required Status status, Priority? priority}) = _Account;
+
+  factory Account.fromJson(Map<String, dynamic> json) => _$AccountFromJson(json);
+}
+"#;
+        let enums = extract_enums_from_dart_source(source);
+        assert_eq!(enums.len(), 2);
+        let status_map = generate_enum_map(&enums[0]);
+        assert!(status_map.contains("const _$StatusEnumMap = {"));
+        assert!(status_map.contains("  Status.active: 'ACTIVE',"));
+        assert!(status_map.contains("  Status.inactive: 'inactive',"));
+        assert!(status_map.contains("  Status.suspended: 'SUSPENDED',"));
+        let priority_map = generate_enum_map(&enums[1]);
+        assert!(priority_map.contains("  Priority.low: 1,"));
+        assert!(priority_map.contains("  Priority.high: 2,"));
+
+        let class = model_class("Account");
+        let result = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        assert!(result.g_dart_code.contains("status: $enumDecode(_$StatusEnumMap, json['status']),"));
+        assert!(result.g_dart_code.contains("priority: $enumDecodeNullable(_$PriorityEnumMap, json['priority']),"));
+        assert!(result.g_dart_code.contains("'status': _$StatusEnumMap[instance.status]!,"));
+        assert!(result.g_dart_code.contains("const _$StatusEnumMap = {"));
+    }
+
+    #[test]
+    fn test_enum_and_custom_defaults() {
+        let source = r#"
+enum Status { active, inactive }
+
+@freezed
+class Account with _$Account {
+  const factory Account({
+    @Default(Status.active) Status status,
+    @Default(Duration(seconds: 30)) Duration timeout,
+    @Default(<String, int>{}) Map<String, int> counts,
+  }) = _Account;
+
+  factory Account.fromJson(Map<String, dynamic> json) => _$AccountFromJson(json);
+}
+"#;
+        let class = model_class("Account");
+        let result = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        assert!(result.g_dart_code.contains("status: $enumDecodeNullable(_$StatusEnumMap, json['status']) ?? Status.active,"));
+        assert!(result.freezed_code.contains("this.status = Status.active,"));
+        assert!(result.freezed_code.contains("this.timeout = const Duration(seconds: 30),"));
+        
+        assert_eq!(const_default_value("Status.active"), "Status.active");
+        assert_eq!(const_default_value("Config.defaults()"), "const Config.defaults()");
+        assert_eq!(const_default_value("const Config()"), "const Config()");
+        assert_eq!(const_default_value("[]"), "const []");
+        assert_eq!(const_default_value("'text'"), "'text'");
+    }
+
+    #[test]
+    fn test_json_converter_annotation() {
+        let source = r#"
+@freezed
+class Theme with _$Theme {
+  const factory Theme({
+    @ColorConverter() required Color color,
+    @Deprecated('use color') @ColorConverter() Color? accent,
+  }) = _Theme;
+
+  factory Theme.fromJson(Map<String, dynamic> json) => _$ThemeFromJson(json);
+}
+"#;
+        let class = model_class("Theme");
+        let result = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        assert!(result.g_dart_code.contains("color: const ColorConverter().fromJson(json['color']),"));
+        assert!(result.g_dart_code.contains("accent: json['accent'] == null ? null : const ColorConverter().fromJson(json['accent']),"));
+        assert!(result.g_dart_code.contains("'color': const ColorConverter().toJson(instance.color),"));
+        assert!(result.g_dart_code.contains("'accent': instance.accent == null ? null : const ColorConverter().toJson(instance.accent!),"));
+
+        let field = parse_dart_parameter("@Deprecated('x') required int count").unwrap();
+        assert_eq!((field.name.as_str(), field.converter), ("count", None));
+    }
+
+    #[test]
+    fn test_assert_annotations_in_impl_constructor() {
+        let source = r#"
+@freezed
+class Person with _$Person {
+  @Assert('name.isNotEmpty', 'name cannot be empty')
+  @Assert("age >= 0")
+  const factory Person({required String name, required int age, @Default([]) List<String> tags}) = _Person;
+}
+"#;
+        let mut class = model_class("Person");
+        class.annotations = vec!["@Freezed(makeCollectionsUnmodifiable: true)".to_string()];
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("      : assert(name.isNotEmpty, 'name cannot be empty'),\n        assert(age >= 0),\n        _tags = tags;"));
+
+        assert!(extract_constructor_asserts("class A { const factory A({int? a}) = _A; }", "A").is_empty());
+    }
+
+    #[test]
+    fn test_g_dart_defines_json_functions_the_impl_calls() {
+        let source = r#"
+@freezed
+class User with _$User {
+  const factory User({required String id}) = _User;
+
+  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);
+}
+"#;
+        let class = model_class("User");
+        let result = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        assert!(result.freezed_code.contains("      _$$UserImplImplFromJson(json);\n"));
+        assert!(result.freezed_code.contains("    return _$$UserImplImplToJson(\n"));
+        assert!(result.g_dart_code.contains("_$$UserImplImpl _$$UserImplImplFromJson(\n"));
+        assert!(result.g_dart_code.contains("Map<String, dynamic> _$$UserImplImplToJson("));
+        assert!(!result.g_dart_code.contains("_$_$$"));
+    }
+
+    #[test]
+    fn test_private_class_symbols_drop_leading_underscore() {
+        let source = r#"
+@freezed
+class _Internal with _$Internal {
+  const factory _Internal({required int id}) = __Internal;
+
+  factory _Internal.fromJson(Map<String, dynamic> json) => _$InternalFromJson(json);
+}
+"#;
+        let class = model_class("_Internal");
+        assert_eq!(class.symbol_name(), "Internal");
+        let result = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        let code = format!("{}{}", result.freezed_code, result.g_dart_code);
+        assert!(code.contains("_Internal _$InternalFromJson(Map<String, dynamic> json) {"));
+        assert!(code.contains("mixin _$Internal {"));
+        assert!(code.contains("class _$$InternalImplImpl implements _$InternalImpl {"));
+        assert!(code.contains("abstract class _$InternalImpl implements _Internal {"));
+        assert!(result.g_dart_code.contains(") => _$$InternalImplImpl("));
+        assert!(!code.contains("_$_Internal"));
+        let identifier = regex::Regex::new(r"^[A-Za-z_$][\w$]*$").unwrap();
+        let declaration = regex::Regex::new(r"(?m)^(?:abstract class|class|mixin) ([^\s<{]+)").unwrap();
+        for name in declaration.captures_iter(&code).map(|captures| captures[1].to_string()) {
+            assert!(identifier.is_match(&name), "invalid identifier {}", name);
+        }
+    }
+
+    #[test]
+    fn test_enum_list_fields_use_enum_map() {
+        let source = r#"
+enum Status { active, inactive }
+
+@freezed
+class Filter with _$Filter {
+  const factory Filter({
+    required List<Status> statuses,
+    List<Status>? excluded,
+    @Default([]) List<Status> pinned,
+  }) = _Filter;
+
+  factory Filter.fromJson(Map<String, dynamic> json) => _$FilterFromJson(json);
+}
+"#;
+        let class = model_class("Filter");
+        let code = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap().g_dart_code;
+        assert!(code.contains("statuses: (json['statuses'] as List<dynamic>).map((e) => $enumDecode(_$StatusEnumMap, e)).toList(),"));
+        assert!(code.contains("excluded: (json['excluded'] as List<dynamic>?)\n          ?.map((e) => $enumDecode(_$StatusEnumMap, e)).toList(),"));
+        assert!(code.contains("pinned: (json['pinned'] as List<dynamic>?)\n          ?.map((e) => $enumDecode(_$StatusEnumMap, e)).toList() ?? const [],"));
+        assert!(code.contains("'statuses': instance.statuses.map((e) => _$StatusEnumMap[e]!).toList(),"));
+        assert!(code.contains("'excluded': instance.excluded?.map((e) => _$StatusEnumMap[e]!).toList(),"));
+        assert!(code.contains("const _$StatusEnumMap = {"));
+    }
+
+    #[test]
+    fn test_mixed_positional_and_named_constructor() {
+        let source = r#"
+@freezed
+class Range with _$Range {
+  const factory Range(int start, int end, {required String label, @Default(1) int step}) = _Range;
+
+  factory Range.fromJson(Map<String, dynamic> json) => _$RangeFromJson(json);
+}
+"#;
+        let (fields, _) = extract_constructor_from_dart_class(source, "Range");
+        let fields: Vec<(&str, bool)> = fields.iter().map(|field| (field.name.as_str(), field.is_named)).collect();
+        assert_eq!(fields, [("start", false), ("end", false), ("label", true), ("step", true)]);
+        
+        let class = model_class("Range");
+        let result = generate_freezed_file(&class.file_path, source, std::slice::from_ref(&class), class.file_path.parent().unwrap(), &GenOptions::default()).unwrap();
+        assert!(result.freezed_code.contains("const _$$RangeImplImpl(\n      this.start, this.end, {required this.label,this.step = 1,})"));
+        assert!(result.g_dart_code.contains("  (json['start'] as num).toInt(),\n  (json['end'] as num).toInt(),\n  label: json['label'] as String,\n"));
+    }
+
+    #[test]
+    fn test_hash_code_falls_back_to_hash_all() {
+        let field = |name: String, ty: &str| DartField { name, ty: ty.to_string(), is_named: true, has_default: false, default_value: None, omit_if_null: false, converter: None };
+        let mut fields: Vec<DartField> = (1..=24).map(|i| field(format!("f{}", i), "int")).collect();
+        fields.push(field("tags".to_string(), "List<String>"));
+        
+        let code = hash_code_getter(&fields);
+        assert!(code.starts_with("  int get hashCode => Object.hashAll([\n        runtimeType,\n        f1,\n"));
+        assert!(code.contains("        f24,\n        const DeepCollectionEquality().hash(tags),\n      ]);"));
+        assert!(!code.contains("Object.hash("));
+        
+        // runtimeType plus 19 fields is still within Object.hash's 20 arguments
+        let code = hash_code_getter(&fields[..19]);
+        assert!(code.starts_with("  int get hashCode => Object.hash(\n      runtimeType,\n"));
+        assert!(code.contains("      f19,\n  );"));
+        assert!(hash_code_getter(&fields[..20]).contains("Object.hashAll(["));
+    }
+
+    #[test]
+    fn test_thirty_field_model_uses_hash_all() {
+        let params: Vec<String> = (1..=30)
+            .map(|i| if i % 10 == 0 { format!("    required Map<String, int> m{},", i) } else { format!("    required int f{},", i) })
+            .collect();
+        let source = format!("@freezed\nclass Wide with _$Wide {{\n  const factory Wide({{\n{}\n  }}) = _Wide;\n}}\n", params.join("\n"));
+        let class = model_class("Wide");
+        
+        let code = generate_freezed_code(&class, &source, &GenOptions::default());
+        assert!(code.contains("  int get hashCode => Object.hashAll([\n        runtimeType,\n        f1,\n"));
+        assert!(code.contains("        const DeepCollectionEquality().hash(m30),\n      ]);"));
+        assert!(!code.contains("Object.hash("));
+        
+        let empty_source = "@freezed\nclass Empty with _$Empty {\n  const factory Empty() = _Empty;\n}\n";
+        assert!(generate_freezed_code(&model_class("Empty"), empty_source, &GenOptions::default()).contains("  int get hashCode => runtimeType.hashCode;"));
+    }
+
+    #[test]
+    fn test_copy_with_single_field() {
+        let source = "@freezed\nclass User with _$User {\n  const factory User({required String name, int? age}) = _User;\n}\n";
+        let class = model_class("User");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        
+        // `user.copyWith(name: 'x')`: every parameter of the public signature is optional and named
+        let public = code.split("abstract class $UserCopyWith<$Res> {").nth(1).unwrap().split("\n}\n").next().unwrap();
+        assert!(public.contains("  $Res call({\n      String name,\n      int? age,\n  });"));
+        assert!(!public.contains("required"));
+        
+        // Omitted fields fall back to their sentinel and keep the current value; `age: null` still resets age
+        let implementation = code.split("class _$UserCopyWithImpl<$Res, $Val extends User>").nth(1).unwrap().split("\n}\n").next().unwrap();
+        assert!(implementation.contains("    Object? name = null,\n    Object? age = freezed,"));
+        assert!(implementation.contains("      name: null == name\n          ? _value.name\n"));
+        assert!(implementation.contains("      age: freezed == age\n          ? _value.age\n"));
+    }
+
+    #[test]
+    fn test_nullable_detection_ignores_nested_question_marks() {
+        let map = parse_dart_parameter("required Map<String, int?> m,").unwrap();
+        assert_eq!(map.ty, "Map<String, int?>");
+        assert!(!has_nullable_type("required Map<String, int?> m"));
+
+        let list = parse_dart_parameter("List<int>? xs,").unwrap();
+        assert_eq!(list.ty, "List<int>?");
+        assert!(has_nullable_type("List<int>? xs"));
+
+        assert!(!has_nullable_type("@Default('?') String label"));
+        assert!(!has_nullable_type("final List<String?> names;"));
+        assert!(has_nullable_type("final int? count;"));
+    }
+
+    #[test]
+    fn test_parse_dart_parameter_complex_types() {
+        let parsed = |param: &str| parse_dart_parameter(param).map(|field| (field.ty, field.name));
+        let pair = |ty: &str, name: &str| Some((ty.to_string(), name.to_string()));
+        assert_eq!(parsed("Map<String, List<int>> data"), pair("Map<String, List<int>>", "data"));
+        assert_eq!(parsed("required Map<String, List<int>> data,"), pair("Map<String, List<int>>", "data"));
+        assert_eq!(parsed("void Function(int) cb"), pair("void Function(int)", "cb"));
+        assert_eq!(parsed("void Function(int a, String b)? onChange"), pair("void Function(int a, String b)?", "onChange"));
+        assert_eq!(parsed("prefix.MyType field"), pair("prefix.MyType", "field"));
+        assert_eq!(parsed("Map<String,int>counts"), pair("Map<String,int>", "counts"));
+        assert_eq!(parsed("int count = 0"), pair("int", "count"));
+        assert_eq!(parsed("List<int>>"), None);
+    }
+
+    #[test]
+    fn test_json_copy_with_extension() {
+        let source = r#"
+@JsonSerializable()
+class Order {
+  final String id;
+  final int? quantity;
+
+  Order({required this.id, this.quantity});
+
+  factory Order.fromJson(Map<String, dynamic> json) => _$OrderFromJson(json);
+}
+
+@JsonSerializable()
+class Point {
+  final double y;
+  final double x;
+
+  const Point(this.x, this.y);
+}
+"#;
+        let class = model_class("Order");
+        let fields = extract_plain_class_fields(source, "Order");
+        assert_eq!(
+            generate_json_copy_with(&class, &fields),
+            "extension OrderCopyWith on Order {\n  Order copyWith({\n    String? id,\n    int? quantity,\n  }) {\n    return Order(\n      id: id ?? this.id,\n      quantity: quantity ?? this.quantity,\n    );\n  }\n}\n\n"
+        );
+        
+        // Positional arguments follow the constructor's order, not the declaration order
+        let point = DartClass { name: "Point".to_string(), ..class };
+        let code = generate_json_copy_with(&point, &extract_plain_class_fields(source, "Point"));
+        assert!(code.contains("    return Point(\n      x ?? this.x,\n      y ?? this.y,\n    );"));
+    }
+
+    #[test]
+    fn test_immutable_collection_round_trip() {
+        let field = DartField { name: "tags".to_string(), ty: "IList<String>".to_string(), is_named: true, has_default: false, default_value: None, omit_if_null: false, converter: None };
+        assert_eq!(
+            immutable_collection_from_json(&field).unwrap(),
+            "IList((json['tags'] as List<dynamic>).map((e) => e as String))"
+        );
+        let scores = DartField { name: "scores".to_string(), ty: "IMap<String, int>?".to_string(), ..field.clone() };
+        assert_eq!(
+            immutable_collection_from_json(&scores).unwrap(),
+            "json['scores'] == null\n          ? null\n          : IMap((json['scores'] as Map<String, dynamic>).map((k, e) => MapEntry(k as String, (e as num).toInt())))"
+        );
+        assert!(immutable_collection_from_json(&DartField { ty: "List<String>".to_string(), ..field.clone() }).is_none());
+
+        let options = GenOptions { immutable_collections: true, ..GenOptions::default() };
+        assert_eq!(from_json_conversion(&field, &[], &options), "IList((json['tags'] as List<dynamic>).map((e) => e as String))");
+        assert_eq!(to_json_conversion(&field, "instance.tags", &[], false, &options), "instance.tags.unlock");
+        assert_eq!(to_json_conversion(&scores, "instance.scores", &[], false, &options), "instance.scores?.unlock");
+        // Off by default: IList fields are cast like any other type
+        assert_eq!(to_json_conversion(&field, "instance.tags", &[], false, &GenOptions::default()), "instance.tags");
+    }
+
+    #[test]
+    fn test_union_shared_fields() {
+        let source = r#"
+@freezed
+sealed class Message with _$Message {
+  String get id;
+
+  const factory Message.text({required String id, required String body}) = MessageText;
+  const factory Message.image({required String url}) = MessageImage;
+  const factory Message.deleted() = MessageDeleted;
+}
+"#;
+        let class = model_class("Message");
+        let cases = extract_union_cases_from_dart_class(source, "Message");
+        let describe: Vec<String> = cases
+            .iter()
+            .map(|case| format!("{}({})", case.case_name, case.fields.iter().map(|f| format!("{} {}", f.ty, f.name)).collect::<Vec<_>>().join(", ")))
+            .collect();
+        assert_eq!(describe, ["text(String id, String body)", "image(String id, String url)", "deleted(String id)"]);
+
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("  String get id => throw _privateConstructorUsedError;\n"));
+        // The shared getter overrides the union's; the case's own getters override nothing
+        assert!(code.contains("abstract class MessageText implements Message {"));
+        assert!(code.contains("  @override\n  String get id;\n  String get body;\n"));
+        assert!(code.contains("  @override\n  String get id;\n  String get url;\n"));
+        assert!(!code.contains("  @override\n  String get body;"));
+        // Impl fields implement the case class getters
+        assert!(code.contains("  @override\n  final String id;\n  @override\n  final String body;\n"));
+        assert!(code.contains("required TResult Function(String id, String url) image,"));
+    }
+
+    #[test]
+    fn test_json_key_include_if_null() {
+        let source = r#"
+@freezed
+class Profile with _$Profile {
+  const factory Profile({
+    required String id,
+    @JsonKey(includeIfNull: false) String? nickname,
+    @JsonKey(includeIfNull: false) required String handle,
+    String? bio,
+  }) = _Profile;
+}
+"#;
+        let class = model_class("Profile");
+        let code = generate_json_code(&class, source, &GenOptions::default());
+        assert!(code.contains("  if (instance.nickname != null) 'nickname': instance.nickname,\n"));
+        // Non-nullable fields and fields without the option are always written
+        assert!(code.contains("  'handle': instance.handle,\n"));
+        assert!(code.contains("  'bio': instance.bio,\n"));
+        assert!(code.contains("  'id': instance.id,\n"));
+    }
+
+    #[test]
+    fn test_multi_line_union_case() {
+        let source = r#"
+@freezed
+class Request with _$Request {
+  const factory Request.fetch({
+    required String url,
+    @Default(Duration(seconds: 30)) Duration timeout,
+    @Default('GET (default)') String method,
+    int? retries,
+  }
+  ) = RequestFetch;
+  const factory Request.cancel(
+    String reason,
+  ) = RequestCancel;
+}
+"#;
+        let cases = extract_union_cases_from_dart_class(source, "Request");
+        let describe: Vec<String> = cases
+            .iter()
+            .map(|case| format!("{}({})", case.case_name, case.fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>().join(", ")))
+            .collect();
+        assert_eq!(describe, ["fetch(url, timeout, method, retries)", "cancel(reason)"]);
+
+        let code = generate_json_code(&model_class("Request"), source, &GenOptions::default());
+        assert!(code.contains("      return Request.fetch(\n        url: json['url'] as String,\n"));
+        assert!(code.contains("      return Request.cancel(\n        json['reason'] as String,\n      );"));
+        assert_eq!(paren_balance("@Default(')') String method,"), 0);
+    }
+
+    #[test]
+    fn test_union_value_case() {
+        let source = r#"
+@Freezed(unionValueCase: FreezedUnionCase.kebab)
+sealed class Name with _$Name {
+  const factory Name.firstName({required String value}) = NameFirstName;
+  const factory Name.last() = NameLast;
+
+  factory Name.fromJson(Map<String, dynamic> json) => _$NameFromJson(json);
+}
+"#;
+        let mut class = model_class("Name");
+        class.annotations = vec!["@Freezed(unionValueCase: FreezedUnionCase.kebab)".to_string()];
+        let freezed_code = generate_freezed_code(&class, source, &GenOptions::default());
+        let json_code = generate_json_code(&class, source, &GenOptions::default());
+        assert!(freezed_code.contains("      'type': 'first-name',\n"));
+        assert!(json_code.contains("    case 'first-name':\n      return Name.firstName(\n"));
+        assert!(json_code.contains("    case 'last':\n"));
+
+        assert_eq!(FreezedUnionCase::Snake.apply("firstName"), "first_name");
+        assert_eq!(FreezedUnionCase::ScreamingSnake.apply("firstName"), "FIRST_NAME");
+        assert_eq!(FreezedUnionCase::Pascal.apply("firstName"), "FirstName");
+        assert_eq!(FreezedUnionCase::None.apply("firstName"), "firstName");
+    }
+
+    #[test]
+    fn test_json_key_default_value() {
+        let field = parse_dart_parameter("@JsonKey(defaultValue: 42) int count").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("count", "int"));
+        assert!(field.has_default);
+        assert_eq!(field.default_value.as_deref(), Some("42"));
+        assert_eq!(get_field_conversion(&field), "(json['count'] as num?)?.toInt() ?? 42");
+        
+        let field = parse_dart_parameter("@JsonKey(name: 'display_name', defaultValue: 'a, b') String label").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("label", "String"));
+        assert_eq!(get_field_conversion(&field), "json['label'] as String? ?? 'a, b'");
+        
+        let field = parse_dart_parameter("@JsonKey(defaultValue: 1.5) double ratio").unwrap();
+        assert_eq!(get_field_conversion(&field), "(json['ratio'] as num?)?.toDouble() ?? 1.5");
+        
+        // Without defaultValue, @JsonKey leaves the field required
+        let field = parse_dart_parameter("@JsonKey(name: 'id') required int id").unwrap();
+        assert_eq!((field.name.as_str(), field.ty.as_str()), ("id", "int"));
+        assert!(!field.has_default);
+        assert_eq!(get_field_conversion(&field), "(json['id'] as num).toInt()");
+    }
+
+    #[test]
+    fn test_empty_collection_defaults() {
+        let field = |name: &str, ty: &str, default_value: &str| DartField {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            is_named: true,
+            has_default: true,
+            default_value: Some(default_value.to_string()),
+            omit_if_null: false,
+            converter: None,
+        };
+        assert_eq!(
+            get_field_conversion(&field("scores", "List<int>", "[]")),
+            "(json['scores'] as List<dynamic>?)\n          ?.map((e) => (e as num).toInt())\n          .toList() ??\n      const []"
+        );
+        assert_eq!(
+            get_field_conversion(&field("counts", "Map<String, int>", "const {}")),
+            "(json['counts'] as Map<String, dynamic>?)\n          ?.map((k, e) => MapEntry(k as String, (e as num).toInt())) ??\n      const {}"
+        );
+        assert!(get_field_conversion(&field("tags", "Set<String>", "<String>{}")).ends_with(".toSet() ??\n      const {}"));
+        assert!(get_field_conversion(&field("names", "List<String>", "[]")).contains("?.map((e) => e as String)"));
+        // Non-empty defaults keep the plain conversion
+        assert_eq!(get_field_conversion(&field("ids", "List<int>", "[1]")), "json['ids'] as List<int>");
+    }
+
+    #[test]
+    fn test_duration_uri_bigint_conversions() {
+        let field = |name: &str, ty: &str| DartField {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            is_named: true,
+            has_default: false,
+            default_value: None,
+            omit_if_null: false,
+            converter: None,
+        };
+        let timeout = field("timeout", "Duration");
+        assert_eq!(get_field_conversion(&timeout), "Duration(microseconds: (json['timeout'] as num).toInt())");
+        assert_eq!(get_to_json_value_conversion(&timeout, "instance.timeout", false), "instance.timeout.inMicroseconds");
+        let delay = field("delay", "Duration?");
+        assert_eq!(
+            get_field_conversion(&delay),
+            "json['delay'] == null\n          ? null\n          : Duration(microseconds: (json['delay'] as num).toInt())"
+        );
+        assert_eq!(get_to_json_value_conversion(&delay, "instance.delay", false), "instance.delay?.inMicroseconds");
+
+        let homepage = field("homepage", "Uri");
+        assert_eq!(get_field_conversion(&homepage), "Uri.parse(json['homepage'] as String)");
+        assert_eq!(get_to_json_value_conversion(&homepage, "instance.homepage", false), "instance.homepage.toString()");
+        let avatar = field("avatar", "Uri?");
+        assert_eq!(get_field_conversion(&avatar), "json['avatar'] == null ? null : Uri.parse(json['avatar'] as String)");
+        assert_eq!(get_to_json_value_conversion(&avatar, "instance.avatar", true), "instance.avatar?.toString()");
+
+        let balance = field("balance", "BigInt");
+        assert_eq!(get_field_conversion(&balance), "BigInt.parse(json['balance'] as String)");
+        assert_eq!(get_to_json_value_conversion(&balance, "instance.balance", false), "instance.balance.toString()");
+        let limit = field("limit", "BigInt?");
+        assert_eq!(get_field_conversion(&limit), "json['limit'] == null ? null : BigInt.parse(json['limit'] as String)");
+        assert_eq!(get_to_json_value_conversion(&limit, "instance.limit", false), "instance.limit?.toString()");
+    }
+
+    #[test]
+    fn test_sealed_class_union_cases_match_factory_form() {
+        let factory_source = r#"
+@freezed
+class Result with _$Result {
+  const factory Result.success({required int value}) = _Success;
+  const factory Result.failure({required String message}) = _Failure;
+}
+"#;
+        let sealed_source = r#"
+sealed class Result {
+  const Result();
+}
+
+class Success extends Result {
+  const Success({required this.value});
+  final int value;
+}
+
+class ResultFailure extends Result {
+  const ResultFailure({required this.message});
+  final String message;
+
+  @override
+  String toString() {
+    return message;
+  }
+}
+"#;
+        let describe = |cases: Vec<CaseInfo>| {
+            cases
+                .into_iter()
+                .map(|case| (case.case_name, case.fields.into_iter().map(|f| (f.ty, f.name)).collect::<Vec<_>>()))
+                .collect::<Vec<_>>()
+        };
+        let factory_cases = describe(extract_union_cases_from_dart_class(factory_source, "Result"));
+        let sealed_cases = describe(extract_union_cases_from_dart_class(sealed_source, "Result"));
+        assert_eq!(factory_cases.len(), 2);
+        assert_eq!(sealed_cases, factory_cases);
+    }
+
+    #[test]
+    fn test_part_of_relative_to_output_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let models_dir = temp_dir.path().join("lib/models");
+        let gen_dir = temp_dir.path().join("lib/gen");
+        fs::create_dir_all(&models_dir).unwrap();
+        let source = "@freezed\nclass User with _$User {\n  const factory User({required String name}) = _User;\n}\n";
+        let file_path = models_dir.join("user.dart");
+        fs::write(&file_path, source).unwrap();
+        let class = DartClass { name: "User".to_string(), annotations: vec!["@freezed".to_string()], file_path: file_path.clone() };
+
+        let result = generate_freezed_file(&file_path, source, std::slice::from_ref(&class), &gen_dir, &GenOptions::default()).unwrap();
+        assert!(result.freezed_code.contains("part of '../models/user.dart';"));
+        assert!(result.g_dart_code.contains("part of '../models/user.dart';"));
+
+        let result = generate_freezed_file(&file_path, source, std::slice::from_ref(&class), &models_dir, &GenOptions::default()).unwrap();
+        assert!(result.freezed_code.contains("part of 'user.dart';"));
+    }
+
+    #[test]
+    fn test_default_values_with_string_literals() {
+        let field = parse_dart_parameter(r#"@Default("a)b") String label"#).unwrap();
+        assert_eq!((field.name.as_str(), field.default_value.as_deref()), ("label", Some(r#""a)b""#)));
+        let field = parse_dart_parameter(r"@Default(r'(\d+)\') String pattern").unwrap();
+        assert_eq!((field.name.as_str(), field.default_value.as_deref()), ("pattern", Some(r"r'(\d+)\'")));
+        let field = parse_dart_parameter("@Default('it\\'s (fine)') String note, // trailing").unwrap();
+        assert_eq!(field.default_value.as_deref(), Some("'it\\'s (fine)'"));
+        let field = parse_dart_parameter("@Default('https://example.com') String url").unwrap();
+        assert_eq!(field.default_value.as_deref(), Some("'https://example.com'"));
+
+        let source = r#"
+@freezed
+class Filter with _$Filter {
+  const factory Filter({@Default("a)b, c") String label, @Default(r'\d+') String pattern}) = _Filter;
+}
+"#;
+        let class = model_class("Filter");
+        let fields = extract_fields_from_dart_class(source, "Filter");
+        assert_eq!(fields.len(), 2);
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains(r#"this.label = "a)b, c","#));
+        assert!(code.contains(r"this.pattern = r'\d+',"));
+    }
+
+    #[test]
+    fn test_output_layouts() {
+        let file_path = Path::new("lib/models/user.dart");
+        let (freezed, g_dart) = get_safe_output_paths(file_path, OutputLayout::Sibling, Path::new("lib"), Path::new("lib/gen"));
+        assert_eq!(freezed, Path::new("lib/models/user.freezed.dart"));
+        assert_eq!(g_dart, Path::new("lib/models/user.g.dart"));
+
+        let (freezed, g_dart) = get_safe_output_paths(file_path, OutputLayout::Mirror, Path::new("lib"), Path::new("lib/gen"));
+        assert_eq!(freezed, Path::new("lib/gen/models/user.freezed.dart"));
+        assert_eq!(g_dart, Path::new("lib/gen/models/user.g.dart"));
+        assert_eq!(part_of_path(file_path, freezed.parent().unwrap()), "../../models/user.dart");
+
+        assert_eq!("mirror".parse::<OutputLayout>(), Ok(OutputLayout::Mirror));
+        assert!("nested".parse::<OutputLayout>().is_err());
+    }
+
+    #[test]
+    fn test_single_line_and_trailing_comma_constructors() {
+        let describe = |source: &str| -> Vec<String> {
+            extract_fields_from_dart_class(source, "User").iter().map(|f| format!("{} {} {}", f.ty, f.name, f.is_named)).collect()
+        };
+        let expected = ["String name true", "int? age true"];
+        assert_eq!(describe("class User with _$User {\n  const factory User({required String name, int? age}) = _User;\n}\n"), expected);
+        assert_eq!(describe("class User with _$User {\n  const factory User({required String name, int? age,}) = _User;\n}\n"), expected);
+        // Inline redirect followed by more members on the same line
+        assert_eq!(
+            describe("class User with _$User { const factory User({required String name, int? age,}) = _User; factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json); }\n"),
+            expected
+        );
+        // Parentheses and braces in comments don't end the parameter list
+        let source = r#"
+class User with _$User {
+  const factory User({
+    required String name, // shown as "Name (display)"
+    int? age, /* years) */
+  }) = _User;
+}
+"#;
+        assert_eq!(describe(source), expected);
+    }
+
+    #[test]
+    fn test_positional_freezed_constructor() {
+        let source = r#"
+@freezed
+class Point with _$Point {
+  const factory Point(int x, int y) = _Point;
+
+  factory Point.fromJson(Map<String, dynamic> json) => _$PointFromJson(json);
+}
+"#;
+        let fields = extract_fields_from_dart_class(source, "Point");
+        assert_eq!(fields.len(), 2);
+        assert_eq!((fields[0].name.as_str(), fields[0].ty.as_str(), fields[0].is_named), ("x", "int", false));
+        assert_eq!((fields[1].name.as_str(), fields[1].ty.as_str(), fields[1].is_named), ("y", "int", false));
+
+        let class = model_class("Point");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+        assert!(code.contains("  const _$$PointImplImpl(\n      this.x, this.y);"));
+        assert!(code.contains("    final int x,\n    final int y,\n  ) = _$$PointImplImpl;"));
+
+        let json_code = generate_json_code(&class, source, &GenOptions::default());
+        assert!(json_code.contains("  (json['x'] as num).toInt(),\n"));
+        assert!(!json_code.contains("x: "));
+    }
+
+    #[test]
+    fn test_union_to_json_converts_date_time() {
+        let source = r#"
+@freezed
+class Event with _$Event {
+  const factory Event.created({required DateTime at}) = _Created;
+  const factory Event.deleted() = _Deleted;
+
+  factory Event.fromJson(Map<String, dynamic> json) => _$EventFromJson(json);
+}
+"#;
+        let class = model_class("Event");
+        let code = generate_freezed_code(&class, source, &GenOptions::default());
+
+        assert!(code.contains("'at': at.toIso8601String(),"));
+        assert!(!code.contains("'at': at,"));
+    }
+}
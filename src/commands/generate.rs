@@ -5,113 +5,518 @@ use walkdir::WalkDir;
 use tree_sitter::Parser;
 use std::fs::OpenOptions;
 use std::io::Write;
-use log::{info, debug, error};
+use log::{info, debug, error, warn};
+use crate::utils::output::SUCCESS;
 use sha1::{Sha1, Digest};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::process::Command;
+use std::sync::{Arc, Mutex, Once};
+use std::time::{Duration, Instant};
 use regex;
 
+/// Spaces per indentation level the emitters write
+pub const DEFAULT_INDENT: usize = 2;
+
+/// Settings of a generator run, handed down to every generator instead of kept in process-wide
+/// state, so runs with different settings (e.g. two `superfastgen::generate` calls) don't interfere
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenOptions {
+    /// Where .freezed.dart/.g.dart files are written (sibling by default)
+    pub output_layout: OutputLayout,
+    /// Run `dart format` on every generated file after writing it (--format)
+    pub format: bool,
+    /// Also look for sources in the SKIPPED_DIRECTORIES (--include-hidden)
+    pub include_hidden: bool,
+    /// Globs of source files to leave out, relative to the scanned directory (--exclude-glob)
+    pub exclude: Vec<String>,
+    /// Add a copyWith extension for plain @JsonSerializable classes (`generate.copywith`)
+    pub copywith: bool,
+    /// (De)serialize fast_immutable_collections' IList/ISet/IMap fields (`generate.immutable_collections`)
+    pub immutable_collections: bool,
+    /// Indentation width of generated files (`generate.indent`), see supported_indent
+    pub indent: usize,
+    /// Treat duplicate model class names as an error instead of a warning (--strict)
+    pub strict: bool,
+    /// Insert a missing `part` directive for each generated file into its source (--add-parts)
+    pub add_parts: bool,
+    /// Also write a JSON Lines manifest of the generated assets here (--asset-manifest)
+    pub asset_manifest: Option<PathBuf>,
+    /// Project root forced with --root instead of walking up from the current directory for pubspec.yaml
+    pub project_root: Option<PathBuf>,
+    /// Append the AST of every parsed provider source to this file (--dump-ast)
+    pub dump_ast: Option<PathBuf>,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        GenOptions {
+            output_layout: OutputLayout::Sibling,
+            format: false,
+            include_hidden: false,
+            exclude: Vec::new(),
+            copywith: false,
+            immutable_collections: false,
+            indent: DEFAULT_INDENT,
+            strict: false,
+            add_parts: false,
+            asset_manifest: None,
+            project_root: None,
+            dump_ast: None,
+        }
+    }
+}
+
+static DART_MISSING_WARNING: Once = Once::new();
+
+/// Format freshly written files with `dart format` when --format is on.
+/// A missing `dart` binary or a formatter failure is only a warning; the unformatted files stay.
+pub fn format_dart_files(paths: &[PathBuf], options: &GenOptions) {
+    if paths.is_empty() || !options.format {
+        return;
+    }
+    match run_dart_format(paths) {
+        Ok(true) => debug!("Formatted {} files with dart format", paths.len()),
+        Ok(false) => warn!("dart format failed; generated files were left unformatted"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            DART_MISSING_WARNING.call_once(|| warn!("--format: `dart` not found on PATH, skipping formatting"));
+        }
+        Err(e) => warn!("Could not run dart format: {}", e),
+    }
+}
+
+// `dart format <paths>`; Ok(false) when the formatter ran but reported an error
+fn run_dart_format(paths: &[PathBuf]) -> std::io::Result<bool> {
+    let output = Command::new("dart").arg("format").args(paths).output()?;
+    if !output.status.success() {
+        debug!("dart format: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    Ok(output.status.success())
+}
+
+/// The indentation width of generated code for a configured one: 2 or 4 spaces, anything else
+/// falls back to DEFAULT_INDENT with a warning
+pub fn supported_indent(width: usize) -> usize {
+    if width == 2 || width == 4 {
+        width
+    } else {
+        warn!("Unsupported indent {}, using {}", width, DEFAULT_INDENT);
+        DEFAULT_INDENT
+    }
+}
+
+/// Final form of a generated file: `width`-space indentation and exactly one trailing newline
+pub(crate) fn finish_generated_code(code: &str, width: usize) -> String {
+    let mut code = reindent(code, width);
+    code.truncate(code.trim_end_matches('\n').len());
+    code.push('\n');
+    code
+}
+
+// Scale leading indentation from the emitters' DEFAULT_INDENT-space levels to `width` spaces
+fn reindent(code: &str, width: usize) -> String {
+    if width == DEFAULT_INDENT {
+        return code.to_string();
+    }
+    let mut result = String::with_capacity(code.len());
+    for line in code.split_inclusive('\n') {
+        let content = line.trim_start_matches(' ');
+        let spaces = line.len() - content.len();
+        let levels = spaces / DEFAULT_INDENT;
+        result.push_str(&" ".repeat(levels * width + spaces % DEFAULT_INDENT));
+        result.push_str(content);
+    }
+    result
+}
+
+/// A code generator run for every class carrying its annotation. Its output is appended to the
+/// source's `.freezed.dart` (`freezed_code`) and `.g.dart` (`g_dart_code`) part files.
+pub trait DartGenerator: Send + Sync {
+    /// Annotation the generator handles, e.g. `@freezed` (arguments are ignored when matching)
+    fn annotation(&self) -> &str;
+    /// `source` is the text of the file the class was parsed from
+    fn generate(&self, class: &DartClass, source: &str, fields: &[DartField], options: &GenOptions) -> GenerationResult;
+
+    /// Whether the generator applies to the class
+    fn matches(&self, class: &DartClass) -> bool {
+        class.annotations.iter().any(|ann| annotation_name(ann) == self.annotation())
+    }
+}
+
+// Generators registered on top of the built-in ones
+static REGISTERED_GENERATORS: Mutex<Vec<Arc<dyn DartGenerator>>> = Mutex::new(Vec::new());
+
+/// Add a generator for a custom annotation; call it before generating
+pub fn register_generator(generator: impl DartGenerator + 'static) {
+    REGISTERED_GENERATORS.lock().unwrap().push(Arc::new(generator));
+}
+
+/// Remove the registered generators for an annotation, e.g. once a test that registered one is done
+pub fn unregister_generator(annotation: &str) {
+    REGISTERED_GENERATORS.lock().unwrap().retain(|generator| generator.annotation() != annotation);
+}
+
+fn registered_generators() -> Vec<Arc<dyn DartGenerator>> {
+    REGISTERED_GENERATORS.lock().unwrap().clone()
+}
+
+/// The dispatch table for model classes: the built-in freezed/json generator, then registered ones.
+/// (Provider classes go through RiverpodGenerator in the provider pass instead, since they are
+/// written to their own `.g.dart` along with the file's @riverpod functions.)
+pub(crate) fn dart_generators() -> Vec<Arc<dyn DartGenerator>> {
+    let mut generators: Vec<Arc<dyn DartGenerator>> = vec![Arc::new(FreezedGenerator)];
+    generators.extend(registered_generators());
+    generators
+}
+
+/// Dart file exporting every generated output, with imports relative to the barrel's directory
+pub fn barrel_file_content(barrel_path: &Path, outputs: &[PathBuf]) -> String {
+    let barrel_dir = absolute_path(barrel_path.parent().unwrap_or_else(|| Path::new("")));
+    let mut exports: Vec<String> = outputs
+        .iter()
+        .filter(|output| output.as_path() != barrel_path)
+        .map(|output| relative_import(&barrel_dir, &absolute_path(output)))
+        .collect();
+    exports.sort();
+    exports.dedup();
+    
+    let mut code = String::new();
+    code.push_str("// GENERATED CODE - DO NOT MODIFY BY HAND\n");
+    code.push_str(&format!("{}\n\n", super::GENERATOR_SIGNATURE));
+    for export in exports {
+        code.push_str(&format!("export '{}';\n", export));
+    }
+    code
+}
+
+/// Write the barrel file exporting the given generated files (see GenStats::outputs)
+pub fn write_barrel_file(barrel_path: &Path, outputs: &[PathBuf]) {
+    let content = barrel_file_content(barrel_path, outputs);
+    if let Some(parent) = barrel_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Err(e) = fs::create_dir_all(parent) {
+            error!("Failed to create {}: {}", parent.display(), e);
+            return;
+        }
+    }
+    match fs::write(barrel_path, content) {
+        Ok(()) => info!(target: SUCCESS, "Generated: {}", barrel_path.display()),
+        Err(e) => error!("Failed to write {}: {}", barrel_path.display(), e),
+    }
+}
+
+pub(crate) fn absolute_path(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    // Resolve "." and ".." lexically so the paths can be compared component-wise
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => { normalized.pop(); }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+// Relative import from a directory to a file, using '/' separators (e.g. "../models/user.g.dart")
+pub(crate) fn relative_import(from_dir: &Path, target: &Path) -> String {
+    let from: Vec<_> = from_dir.components().collect();
+    let to: Vec<_> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(to[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    dart_uri_path(&parts.join("/"))
+}
+
+/// Dart `part`/`import` URIs always use '/', whatever the host separator is
+pub(crate) fn dart_uri_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Files written by a generator run
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GenStats {
+    pub files: usize,
+    /// Paths of the written files, in write order (exported by the --barrel file)
+    pub outputs: Vec<PathBuf>,
+    /// Time spent parsing the Dart sources (part of the run's duration)
+    pub parse_time: Duration,
+    /// Annotated classes and functions code was generated for, with the file declaring them
+    pub declarations: Vec<(PathBuf, String)>,
+}
+
+impl GenStats {
+    /// Add the stats of a later generator pass; files it rewrote and declarations it generated
+    /// for again are counted once
+    pub fn merge(&mut self, other: GenStats) {
+        for output in other.outputs {
+            if !self.outputs.contains(&output) {
+                self.outputs.push(output);
+                self.files += 1;
+            }
+        }
+        self.add_declarations(&other.declarations);
+        self.parse_time += other.parse_time;
+    }
+
+    fn add_declarations(&mut self, declarations: &[(PathBuf, String)]) {
+        for declaration in declarations {
+            if !self.declarations.contains(declaration) {
+                self.declarations.push(declaration.clone());
+            }
+        }
+    }
+}
+
+/// Stats of one run of the enabled code generators, per phase (see run_code_generators)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CodeGenStats {
+    /// The freezed and json passes
+    pub models: GenStats,
+    /// The riverpod and provider passes
+    pub providers: GenStats,
+    pub freezed_time: Duration,
+    pub json_time: Duration,
+    pub provider_time: Duration,
+}
+
+impl CodeGenStats {
+    /// Everything the run wrote, each file counted once
+    pub fn total(self) -> GenStats {
+        let mut stats = self.models;
+        stats.merge(self.providers);
+        stats
+    }
+}
+
+/// A failure that should make the whole run fail (and the process exit non-zero)
+#[derive(Debug)]
+pub enum GenError {
+    Io { path: PathBuf, source: std::io::Error },
+    Parse { path: PathBuf, message: String },
+    /// No directory with both pubspec.yaml and lib/ at or above the starting directory
+    NoFlutterProject { searched_from: PathBuf },
+    /// The same model class name is declared in several files (an error with --strict)
+    DuplicateClass { name: String, paths: Vec<PathBuf> },
+    /// The input has no Dart files, or none with an annotated declaration (an error with --fail-on-empty)
+    NothingToGenerate { input: PathBuf, dart_files: usize },
+}
+
+impl GenError {
+    pub fn io(path: impl AsRef<Path>, source: std::io::Error) -> Self {
+        GenError::Io { path: path.as_ref().to_path_buf(), source }
+    }
+}
+
+impl std::fmt::Display for GenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            GenError::Parse { path, message } => write!(f, "{}: {}", path.display(), message),
+            GenError::NoFlutterProject { searched_from } => write!(
+                f,
+                "No Flutter project found from {}. Make sure you're in a directory with pubspec.yaml and lib/",
+                searched_from.display()
+            ),
+            GenError::DuplicateClass { name, paths } => write!(
+                f,
+                "class {} is declared in several files: {}",
+                name,
+                paths.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            GenError::NothingToGenerate { input, dart_files: 0 } => write!(f, "No Dart files found in {}", input.display()),
+            GenError::NothingToGenerate { input, dart_files } => write!(
+                f,
+                "No annotated classes or functions found in {} Dart files under {}",
+                dart_files,
+                input.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GenError {}
+
 // tree-sitter FFI bindings
 #[link(name = "tree-sitter-dart")]
 extern "C" {
     fn tree_sitter_dart() -> *const std::ffi::c_void;
 }
 
-use super::freezed_gen::{DartClass, DartField, DartFunction, CaseInfo, GenerationResult, generate_freezed_file, generate_freezed_code, generate_json_code, extract_fields_from_dart_class, extract_union_cases_from_dart_class, get_safe_output_paths};
-use super::provider_gen::{ProviderClass, ProviderGenerationResult, generate_enhanced_provider_file, get_provider_output_paths, extract_provider_annotations, ProviderType};
+use super::freezed_gen::{DartClass, DartField, DartFunction, CaseInfo, FreezedGenerator, GenerationResult, generate_freezed_file, generate_freezed_code, generate_json_code, extract_fields_from_dart_class, extract_union_cases_from_dart_class, get_safe_output_paths, part_of_path, annotation_name, declares_from_json_factory, has_nullable_type, is_freezed_annotation, OutputLayout};
+use super::provider_gen::{ProviderClass, ProviderGenerationResult, RiverpodGenerator, generate_provider_file_code, get_provider_output_paths, extract_provider_annotations, is_auto_dispose_annotation, is_ref_parameter, is_riverpod_annotation, ProviderType};
 
 // New functions: configurable paths
-pub fn generate_freezed_with_paths(input_path: &str, output_path: &str) {
+pub fn generate_freezed_with_paths(input_path: &str, output_path: &str) -> Result<GenStats, GenError> {
     info!("Generating Freezed code from {} to {}...", input_path, output_path);
     generate_code_for_annotation_with_paths("@freezed", "freezed", input_path, output_path)
 }
 
-pub fn generate_freezed_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool) {
-    eprintln!("[DEBUG] generate_freezed_with_paths_and_clean called with input_path: {}", input_path);
+pub fn generate_freezed_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, options: &GenOptions) -> Result<GenStats, GenError> {
+    debug!("generate_freezed_with_paths_and_clean called with input_path: {}", input_path);
     info!("Generating Freezed code from {} to {}...", input_path, output_path);
-    generate_code_for_annotation_with_paths_and_clean("@freezed", "freezed", input_path, output_path, delete_conflicting_outputs)
+    generate_code_for_annotation_with_paths_and_clean("@freezed", "freezed", input_path, output_path, delete_conflicting_outputs, options)
 }
 
-pub fn generate_json_with_paths(input_path: &str, output_path: &str) {
+pub fn generate_json_with_paths(input_path: &str, output_path: &str) -> Result<GenStats, GenError> {
     info!("Generating JSON code from {} to {}...", input_path, output_path);
     generate_code_for_annotation_with_paths("@JsonSerializable", "json", input_path, output_path)
 }
 
-pub fn generate_json_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool) {
+pub fn generate_json_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, options: &GenOptions) -> Result<GenStats, GenError> {
     info!("Generating JSON code from {} to {}...", input_path, output_path);
-    generate_code_for_annotation_with_paths_and_clean("@JsonSerializable", "json", input_path, output_path, delete_conflicting_outputs)
+    generate_code_for_annotation_with_paths_and_clean("@JsonSerializable", "json", input_path, output_path, delete_conflicting_outputs, options)
 }
 
-pub fn generate_riverpod_with_paths(input_path: &str, output_path: &str) {
+pub fn generate_riverpod_with_paths(input_path: &str, output_path: &str) -> Result<GenStats, GenError> {
     info!("Generating Riverpod code from {} to {}...", input_path, output_path);
     generate_code_for_annotation_with_paths("@riverpod", "riverpod", input_path, output_path)
 }
 
-pub fn generate_riverpod_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool) {
+pub fn generate_riverpod_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, options: &GenOptions) -> Result<GenStats, GenError> {
     info!("Generating Riverpod code from {} to {}...", input_path, output_path);
-    generate_provider_code_with_paths_and_clean(input_path, output_path, delete_conflicting_outputs)
+    generate_provider_code_with_paths_and_clean(input_path, output_path, delete_conflicting_outputs, options)
 }
 
-pub fn generate_provider_with_paths(input_path: &str, output_path: &str) {
+pub fn generate_provider_with_paths(input_path: &str, output_path: &str) -> Result<GenStats, GenError> {
     info!("Generating Provider code from {} to {}...", input_path, output_path);
-    generate_provider_code_with_paths_and_clean(input_path, output_path, false)
+    generate_provider_code_with_paths_and_clean(input_path, output_path, false, &GenOptions::default())
 }
 
-pub fn generate_provider_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool) {
+pub fn generate_provider_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, options: &GenOptions) -> Result<GenStats, GenError> {
     info!("Generating Provider code from {} to {}...", input_path, output_path);
-    generate_provider_code_with_paths_and_clean(input_path, output_path, delete_conflicting_outputs)
+    generate_provider_code_with_paths_and_clean(input_path, output_path, delete_conflicting_outputs, options)
 }
 
-fn generate_code_for_annotation(annotation: &str, generator_type: &str) {
-    // Auto-detect Flutter project root
-    if let Some(project_root) = find_flutter_project_root() {
-        let lib_path = project_root.join("lib");
-        let lib_path_str = lib_path.to_string_lossy();
-        
-        info!("Using Flutter project: {}", project_root.display());
-        info!("Lib directory: {}", lib_path_str);
-        
-        // Output to same location as lib directory (.g.dart files in same directory as original files)
-        generate_code_for_annotation_with_paths(annotation, generator_type, &lib_path_str, &lib_path_str)
-    } else {
-        error!("No Flutter project found. Make sure you're in a directory with pubspec.yaml and lib/");
-        std::process::exit(1);
+/// Run the enabled code generators, cleaning conflicting outputs at most once.
+/// This is the run behind both the CLI and `superfastgen::generate`.
+#[allow(clippy::too_many_arguments)]
+pub fn run_code_generators(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, freezed: bool, json: bool, riverpod: bool, provider: bool, options: &GenOptions) -> Result<CodeGenStats, GenError> {
+    let mut stats = CodeGenStats::default();
+    // Only the first generator that runs cleans
+    let mut clean = delete_conflicting_outputs;
+    
+    if freezed {
+        let started = Instant::now();
+        stats.models.merge(generate_freezed_with_paths_and_clean(input_path, output_path, clean, options)?);
+        stats.freezed_time = started.elapsed();
+        clean = false;
+    }
+    
+    if json {
+        // The json pass rewrites every file of the freezed pass
+        let started = Instant::now();
+        stats.models.merge(generate_json_with_paths_and_clean(input_path, output_path, clean, options)?);
+        stats.json_time = started.elapsed();
+        clean = false;
+    }
+    
+    let started = Instant::now();
+    if riverpod {
+        stats.providers.merge(generate_riverpod_with_paths_and_clean(input_path, output_path, clean, options)?);
+        clean = false;
     }
+    
+    if provider {
+        stats.providers.merge(generate_provider_with_paths_and_clean(input_path, output_path, clean, options)?);
+    }
+    stats.provider_time = started.elapsed();
+    Ok(stats)
 }
 
-fn generate_code_for_annotation_with_paths(annotation: &str, generator_type: &str, input_path: &str, output_path: &str) {
-    generate_code_for_annotation_with_paths_and_clean(annotation, generator_type, input_path, output_path, false)
+/// Generate code for `annotation` in the Flutter project containing the current directory,
+/// writing outputs next to the sources under its lib/
+pub fn generate_code_for_annotation(annotation: &str, generator_type: &str) -> Result<GenStats, GenError> {
+    let current_dir = std::env::current_dir().map_err(|e| GenError::io(".", e))?;
+    let project_root = find_flutter_project_root(&current_dir)?;
+    let lib_path = project_root.join("lib");
+    let lib_path_str = lib_path.to_string_lossy();
+    
+    info!("Using Flutter project: {}", project_root.display());
+    info!("Lib directory: {}", lib_path_str);
+    
+    // Output to same location as lib directory (.g.dart files in same directory as original files)
+    generate_code_for_annotation_with_paths(annotation, generator_type, &lib_path_str, &lib_path_str)
 }
 
-fn generate_code_for_annotation_with_paths_and_clean(annotation: &str, generator_type: &str, input_path: &str, output_path: &str, delete_conflicting_outputs: bool) {
-    eprintln!("[DEBUG] generate_code_for_annotation_with_paths_and_clean called: annotation={}, generator_type={}, input_path={}, output_path={}, delete_conflicting_outputs={}", annotation, generator_type, input_path, output_path, delete_conflicting_outputs);
+fn generate_code_for_annotation_with_paths(annotation: &str, generator_type: &str, input_path: &str, output_path: &str) -> Result<GenStats, GenError> {
+    generate_code_for_annotation_with_paths_and_clean(annotation, generator_type, input_path, output_path, false, &GenOptions::default())
+}
+
+fn generate_code_for_annotation_with_paths_and_clean(annotation: &str, generator_type: &str, input_path: &str, output_path: &str, delete_conflicting_outputs: bool, options: &GenOptions) -> Result<GenStats, GenError> {
+    debug!("generate_code_for_annotation_with_paths_and_clean called: annotation={}, generator_type={}, input_path={}, output_path={}, delete_conflicting_outputs={}", annotation, generator_type, input_path, output_path, delete_conflicting_outputs);
     
+    if delete_conflicting_outputs {
+        info!("Cleaning output directory...");
+        clean_output_directory_all_g_dart(Path::new(input_path)).map_err(|e| GenError::io(input_path, e))?;
+    }
+
+    let mut stats = GenStats::default();
+    let outputs = collect_annotation_outputs(annotation, input_path, output_path, options, &mut stats)?;
+    stats.merge(write_generated_outputs(&outputs, options)?);
+    Ok(stats)
+}
+
+/// Generate the .freezed.dart/.g.dart contents for every file with `annotation` without writing them
+fn collect_annotation_outputs(annotation: &str, input_path: &str, output_path: &str, options: &GenOptions, stats: &mut GenStats) -> Result<Vec<(PathBuf, String)>, GenError> {
     info!("Using input path: {}", input_path);
+    let dart_files = input_dart_files(input_path, options);
+    info!("Found {} Dart files", dart_files.len());
+    collect_annotation_outputs_for(annotation, &read_sources(&dart_files)?, input_root(Path::new(input_path)), Path::new(output_path), options, stats)
+}
+
+// Mirrored outputs keep their path relative to the input directory (a single input file's directory)
+fn input_root(input_path: &Path) -> &Path {
+    if input_path.is_file() {
+        input_path.parent().unwrap_or(Path::new(""))
+    } else {
+        input_path
+    }
+}
+
+// The input file itself, or every source .dart file under the input directory
+fn input_dart_files(input_path: &str, options: &GenOptions) -> Vec<PathBuf> {
     let path = Path::new(input_path);
-    let dart_files = if path.is_file() {
+    if path.is_file() {
         vec![path.to_path_buf()]
     } else {
-        find_dart_files(input_path)
-    };
-    info!("Found {} Dart files", dart_files.len());
-
-    if delete_conflicting_outputs {
-        info!("Cleaning output directory...");
-        clean_output_directory_all_g_dart(Path::new(input_path)).unwrap_or_else(|e| {
-            error!("Failed to clean output directory: {}", e);
-        });
+        find_dart_files(input_path, options)
     }
+}
 
-    // Group classes by file
+// The classes declared in each source, grouped by file; the parsing time is added to parse_time
+fn parse_sources(sources: &HashMap<PathBuf, String>, parse_time: &mut Duration) -> HashMap<PathBuf, Vec<DartClass>> {
+    let started = Instant::now();
     let mut file_classes: HashMap<PathBuf, Vec<DartClass>> = HashMap::new();
-    
-    for file_path in &dart_files {
-        if let Some(classes) = parse_dart_file(file_path) {
+    for (file_path, content) in sources {
+        if let Some(classes) = parse_dart_content(content, file_path) {
             for class in classes {
                 file_classes.entry(file_path.clone()).or_insert_with(Vec::new).push(class);
             }
         }
     }
+    *parse_time += started.elapsed();
+    file_classes
+}
+
+// The text of each Dart file, read once for all the generators that run on it
+fn read_sources(dart_files: &[PathBuf]) -> Result<HashMap<PathBuf, String>, GenError> {
+    dart_files
+        .iter()
+        .map(|file_path| Ok((file_path.clone(), fs::read_to_string(file_path).map_err(|e| GenError::io(file_path, e))?)))
+        .collect()
+}
+
+fn collect_annotation_outputs_for(annotation: &str, sources: &HashMap<PathBuf, String>, input_root: &Path, output_dir: &Path, options: &GenOptions, stats: &mut GenStats) -> Result<Vec<(PathBuf, String)>, GenError> {
+    let file_classes = parse_sources(sources, &mut stats.parse_time);
+    report_duplicate_class_names(&file_classes, options.strict)?;
+
+    let mut outputs = Vec::new();
+    let custom_generators = registered_generators();
 
     // Generate code for each file (only if it has at least one class with the annotation)
     for (file_path, classes) in file_classes {
@@ -120,8 +525,9 @@ fn generate_code_for_annotation_with_paths_and_clean(annotation: &str, generator
         let filtered_classes: Vec<DartClass> = classes
             .into_iter()
             .filter(|class| {
-                class.annotations.iter().any(|ann| ann.trim() == annotation) ||
-                (annotation == "@JsonSerializable" && class.annotations.iter().any(|ann| ann.trim() == "@freezed"))
+                class.annotations.iter().any(|ann| annotation_name(ann) == annotation) ||
+                (matches!(annotation, "@freezed" | "@JsonSerializable") && class.annotations.iter().any(|ann| is_freezed_annotation(ann))) ||
+                custom_generators.iter().any(|generator| generator.matches(class))
             })
             .collect();
         if filtered_classes.is_empty() {
@@ -131,92 +537,76 @@ fn generate_code_for_annotation_with_paths_and_clean(annotation: &str, generator
         
         // Only generate .freezed.dart and .g.dart files for @freezed and @JsonSerializable
         if annotation == "@freezed" || annotation == "@JsonSerializable" {
-            eprintln!("[DEBUG] Attempting to generate freezed file for: {}", file_path.display());
-            eprintln!("[DEBUG] Filtered classes count: {}", filtered_classes.len());
-            if let Some(result) = generate_freezed_file(&file_path, &filtered_classes) {
-                eprintln!("[DEBUG] Successfully generated freezed file");
-                
-                // Always use the same directory as the source file
-                let (freezed_output_path, g_dart_output_path) = get_safe_output_paths(&file_path);
-                
-                eprintln!("[DEBUG] Freezed output path: {}", freezed_output_path.display());
-                eprintln!("[DEBUG] G dart output path: {}", g_dart_output_path.display());
-                
-                eprintln!("[DEBUG] Writing freezed file to: {}", freezed_output_path.display());
-                eprintln!("[DEBUG] Freezed code length: {} bytes", result.freezed_code.len());
-                if let Err(e) = std::fs::write(&freezed_output_path, &result.freezed_code) {
-                    eprintln!("[DEBUG] Failed to write freezed file: {}", e);
-                    error!("Failed to write {}: {}", freezed_output_path.display(), e);
-                } else {
-                    eprintln!("[DEBUG] Successfully wrote freezed file");
-                    info!("Generated: {}", freezed_output_path.display());
+            let source_content = &sources[&file_path];
+            debug!("Attempting to generate freezed file for: {}", file_path.display());
+            debug!("Filtered classes count: {}", filtered_classes.len());
+            let (freezed_output_path, g_dart_output_path) = get_safe_output_paths(&file_path, options.output_layout, input_root, output_dir);
+            let output_dir = freezed_output_path.parent().unwrap_or_else(|| Path::new(""));
+            if let Some(result) = generate_freezed_file(&file_path, source_content, &filtered_classes, output_dir, options) {
+                let declarations: Vec<_> = filtered_classes.iter().map(|class| (file_path.clone(), class.name.clone())).collect();
+                stats.add_declarations(&declarations);
+                // Files with only plain @JsonSerializable classes get no .freezed.dart
+                let needs_freezed_part = filtered_classes.iter().any(|class| {
+                    class.annotations.iter().any(|ann| is_freezed_annotation(ann)) ||
+                    custom_generators.iter().any(|generator| generator.matches(class))
+                });
+                if needs_freezed_part {
+                    outputs.push((freezed_output_path, finish_generated_code(&result.freezed_code, options.indent)));
                 }
-                
-                // Always generate .g.dart for @freezed classes (they have JSON serialization)
-                eprintln!("[DEBUG] Writing g.dart file to: {}", g_dart_output_path.display());
-                eprintln!("[DEBUG] G dart code length: {} bytes", result.g_dart_code.len());
-                if let Err(e) = std::fs::write(&g_dart_output_path, &result.g_dart_code) {
-                    eprintln!("[DEBUG] Failed to write g.dart file: {}", e);
-                    error!("Failed to write {}: {}", g_dart_output_path.display(), e);
-                } else {
-                    eprintln!("[DEBUG] Successfully wrote g.dart file");
-                    info!("Generated: {}", g_dart_output_path.display());
+                // freezed models without a fromJson factory have no JSON serialization, so no .g.dart
+                let needs_g_part = filtered_classes.iter().any(|class| {
+                    !class.annotations.iter().any(|ann| is_freezed_annotation(ann)) ||
+                    declares_from_json_factory(source_content, &class.name) ||
+                    custom_generators.iter().any(|generator| generator.matches(class))
+                });
+                if needs_g_part {
+                    outputs.push((g_dart_output_path, finish_generated_code(&result.g_dart_code, options.indent)));
                 }
             } else {
-                eprintln!("[DEBUG] Failed to generate freezed file - generate_freezed_file returned None");
+                debug!("Failed to generate freezed file - generate_freezed_file returned None");
             }
         }
         // For @riverpod, we don't generate .freezed.dart or .g.dart files
         // Riverpod has its own code generation mechanism
     }
+
+    Ok(outputs)
 }
 
-fn generate_provider_code_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool) {
-    eprintln!("[DEBUG] generate_provider_code_with_paths_and_clean called: input_path={}, output_path={}, delete_conflicting_outputs={}", input_path, output_path, delete_conflicting_outputs);
+fn generate_provider_code_with_paths_and_clean(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, options: &GenOptions) -> Result<GenStats, GenError> {
+    debug!("generate_provider_code_with_paths_and_clean called: input_path={}, output_path={}, delete_conflicting_outputs={}", input_path, output_path, delete_conflicting_outputs);
     
-    info!("Using input path: {}", input_path);
-    let path = Path::new(input_path);
-    let dart_files = if path.is_file() {
-        vec![path.to_path_buf()]
-    } else {
-        find_dart_files(input_path)
-    };
-    info!("Found {} Dart files", dart_files.len());
-
     if delete_conflicting_outputs {
         info!("Cleaning output directory...");
-        clean_output_directory_all_g_dart(Path::new(input_path)).unwrap_or_else(|e| {
-            error!("Failed to clean output directory: {}", e);
-        });
+        clean_output_directory_all_g_dart(Path::new(input_path)).map_err(|e| GenError::io(input_path, e))?;
     }
 
-    // Group classes by file
-    let mut file_classes: HashMap<PathBuf, Vec<DartClass>> = HashMap::new();
-    
-    for file_path in &dart_files {
-        if let Some(classes) = parse_dart_file(file_path) {
-            for class in classes {
-                file_classes.entry(file_path.clone()).or_insert_with(Vec::new).push(class);
-            }
-        }
-    }
+    let mut stats = GenStats::default();
+    let outputs = collect_provider_outputs(input_path, options, &mut stats)?;
+    stats.merge(write_generated_outputs(&outputs, options)?);
+    Ok(stats)
+}
+
+/// Generate the provider .g.dart contents for every file with provider annotations without writing them
+fn collect_provider_outputs(input_path: &str, options: &GenOptions, stats: &mut GenStats) -> Result<Vec<(PathBuf, String)>, GenError> {
+    info!("Using input path: {}", input_path);
+    let dart_files = input_dart_files(input_path, options);
+    info!("Found {} Dart files", dart_files.len());
+    collect_provider_outputs_for(&read_sources(&dart_files)?, options, stats)
+}
+
+fn collect_provider_outputs_for(sources: &HashMap<PathBuf, String>, options: &GenOptions, stats: &mut GenStats) -> Result<Vec<(PathBuf, String)>, GenError> {
+    let file_classes = parse_sources(sources, &mut stats.parse_time);
+
+    let mut outputs = Vec::new();
+    let generator = RiverpodGenerator;
 
     // Generate provider code for each file (only if it has at least one class with provider annotations)
     for (file_path, classes) in file_classes {
         // Filter: only classes with provider annotations
-        let provider_annotations = [
-            "@riverpod", "@FutureProvider", "@StreamProvider", "@StateNotifierProvider", 
-            "@StateProvider", "@AutoDisposeProvider", "@AutoDisposeFutureProvider", 
-            "@AutoDisposeStreamProvider", "@AutoDisposeStateNotifierProvider", "@AutoDisposeStateProvider"
-        ];
-        
         let filtered_classes: Vec<DartClass> = classes
             .into_iter()
-            .filter(|class| {
-                class.annotations.iter().any(|ann| {
-                    provider_annotations.iter().any(|provider_ann| ann.trim() == *provider_ann)
-                })
-            })
+            .filter(|class| generator.matches(class))
             .collect();
             
         if filtered_classes.is_empty() {
@@ -224,46 +614,455 @@ fn generate_provider_code_with_paths_and_clean(input_path: &str, output_path: &s
             continue;
         }
         
-        // Convert DartClass to ProviderClass and extract functions
-        let mut provider_classes = Vec::new();
+        // Generate the class providers and extract functions
+        let source_content = &sources[&file_path];
+        let mut class_code = String::new();
         let mut provider_functions = Vec::new();
         
-        for class in filtered_classes {
-            if let Some(provider_class) = convert_dart_class_to_provider_class(&class) {
-                provider_classes.push(provider_class);
-            }
+        for class in &filtered_classes {
+            let fields = extract_fields_from_dart_class(source_content, &class.name);
+            class_code.push_str(&generator.generate(class, source_content, &fields, options).g_dart_code);
         }
         
         // Extract @riverpod functions from the same file
-        let source_content = std::fs::read_to_string(&file_path).unwrap_or_default();
-        let functions = extract_functions_from_dart_source(&source_content, &file_path);
+        let functions = extract_functions_from_dart_source(source_content, &file_path, options.dump_ast.as_deref());
         for function in functions {
-            if function.annotations.iter().any(|ann| ann.trim() == "@riverpod") {
+            if function.annotations.iter().any(|ann| is_riverpod_annotation(ann)) {
                 provider_functions.push(function);
             }
         }
         
-        eprintln!("[DEBUG] Found {} provider classes and {} provider functions", provider_classes.len(), provider_functions.len());
+        debug!("Found {} provider classes and {} provider functions", filtered_classes.len(), provider_functions.len());
         
-        if !provider_classes.is_empty() || !provider_functions.is_empty() {
-            // Generate .g.dart file for providers
-            let (_, g_dart_path) = get_provider_output_paths(&file_path);
-            eprintln!("[DEBUG] Generating provider file to: {}", g_dart_path.display());
-            if let Err(e) = generate_enhanced_provider_file(&provider_classes, &provider_functions, &g_dart_path) {
-                eprintln!("Failed to write provider file: {}", e);
-            } else {
-                info!("Generated Riverpod code: {}", g_dart_path.display());
+        // Generate .g.dart file for providers
+        let (_, g_dart_path) = get_provider_output_paths(&file_path);
+        let project_root = options.project_root.as_deref().or_else(|| pubspec_dir(&file_path)).unwrap_or(Path::new(""));
+        let code = generate_provider_file_code(&class_code, &provider_functions, &g_dart_path, project_root);
+        outputs.push((g_dart_path, finish_generated_code(&code, options.indent)));
+        // @riverpod functions are found both as classes and as functions
+        let declarations: Vec<_> = filtered_classes.iter().map(|class| &class.name)
+            .chain(provider_functions.iter().map(|function| &function.name))
+            .map(|name| (file_path.clone(), name.clone()))
+            .collect();
+        stats.add_declarations(&declarations);
+    }
+
+    Ok(outputs)
+}
+
+/// Fail when `input_path` has no Dart files or none of them declares anything to generate code for
+/// (checked with --fail-on-empty, so a misconfigured input doesn't pass silently)
+pub fn check_not_empty(input_path: &str, options: &GenOptions) -> Result<(), GenError> {
+    let dart_files = input_dart_files(input_path, options);
+    let annotated = dart_files.iter().any(|file_path| parse_dart_file(file_path).is_some_and(|classes| !classes.is_empty()));
+    if annotated {
+        return Ok(());
+    }
+    Err(GenError::NothingToGenerate { input: PathBuf::from(input_path), dart_files: dart_files.len() })
+}
+
+/// Warn about @freezed/@JsonSerializable class names declared in more than one file: their
+/// generated `_$Name...` symbols collide in the Dart library. With `strict` the first one fails the run.
+fn report_duplicate_class_names(file_classes: &HashMap<PathBuf, Vec<DartClass>>, strict: bool) -> Result<(), GenError> {
+    for (name, paths) in find_duplicate_class_names(file_classes) {
+        if strict {
+            return Err(GenError::DuplicateClass { name, paths });
+        }
+        warn!("{}", GenError::DuplicateClass { name, paths });
+    }
+    Ok(())
+}
+
+fn find_duplicate_class_names(file_classes: &HashMap<PathBuf, Vec<DartClass>>) -> Vec<(String, Vec<PathBuf>)> {
+    let mut file_paths: Vec<&PathBuf> = file_classes.keys().collect();
+    file_paths.sort();
+    let mut declared: Vec<(String, Vec<PathBuf>)> = Vec::new();
+    for file_path in file_paths {
+        for class in &file_classes[file_path] {
+            let is_model = class.annotations.iter().any(|ann| is_freezed_annotation(ann) || annotation_name(ann) == "@JsonSerializable");
+            if !is_model {
+                continue;
+            }
+            match declared.iter_mut().find(|(name, _)| *name == class.name) {
+                Some((_, paths)) if !paths.contains(file_path) => paths.push(file_path.clone()),
+                Some(_) => {}
+                None => declared.push((class.name.clone(), vec![file_path.clone()])),
+            }
+        }
+    }
+    declared.retain(|(_, paths)| paths.len() > 1);
+    declared
+}
+
+/// The files among `files` that a full run over `input_path` would generate for, so a watched change
+/// to an ignored, excluded or skipped file regenerates nothing
+pub fn source_files_among(files: &[PathBuf], input_path: &str, options: &GenOptions) -> Vec<PathBuf> {
+    let sources: Vec<PathBuf> = input_dart_files(input_path, options).iter().map(|path| absolute_path(path)).collect();
+    files.iter().filter(|file| sources.contains(&absolute_path(file))).cloned().collect()
+}
+
+/// Regenerate the outputs of one source file, e.g. after a watch event, instead of the whole input tree.
+/// `input_path` is the directory being generated, which mirrored outputs are placed relative to.
+/// Generation only ever reads the source file itself, so no other file's outputs depend on it.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_file(file_path: &Path, input_path: &str, output_path: &str, freezed: bool, json: bool, riverpod: bool, provider: bool, options: &GenOptions) -> Result<GenStats, GenError> {
+    info!("Regenerating {}", file_path.display());
+    let sources = read_sources(&[absolute_path(file_path)])?;
+    let input_root = absolute_path(input_root(Path::new(input_path)));
+    let output_dir = absolute_path(Path::new(output_path));
+    let mut stats = GenStats::default();
+    let outputs = collect_source_outputs(&sources, &input_root, &output_dir, freezed, json, riverpod || provider, options, &mut stats)?;
+    stats.merge(write_generated_outputs(&outputs, options)?);
+    Ok(stats)
+}
+
+/// Generate the outputs for a Dart source held in memory, e.g. piped in by an editor, without
+/// touching the project. `file_name` is the name the source's `part` directives refer to; the
+/// returned paths are the output file names.
+pub fn generate_source(source: &str, file_name: &str, freezed: bool, json: bool, riverpod: bool, provider: bool, options: &GenOptions) -> Result<Vec<(PathBuf, String)>, GenError> {
+    // A bare file name with no directory, so the outputs are named relative to it
+    let file_name = Path::new(file_name).file_name().unwrap_or(std::ffi::OsStr::new("input.dart"));
+    let sources = HashMap::from([(PathBuf::from(file_name), source.to_string())]);
+    collect_source_outputs(&sources, Path::new(""), Path::new(""), freezed, json, riverpod || provider, options, &mut GenStats::default())
+}
+
+// The outputs of the enabled generators for sources already in memory
+#[allow(clippy::too_many_arguments)]
+fn collect_source_outputs(sources: &HashMap<PathBuf, String>, input_root: &Path, output_dir: &Path, freezed: bool, json: bool, providers: bool, options: &GenOptions, stats: &mut GenStats) -> Result<Vec<(PathBuf, String)>, GenError> {
+    let mut outputs = Vec::new();
+    // The json pass covers the freezed pass, so one of them is enough
+    if json {
+        outputs.extend(collect_annotation_outputs_for("@JsonSerializable", sources, input_root, output_dir, options, stats)?);
+    } else if freezed {
+        outputs.extend(collect_annotation_outputs_for("@freezed", sources, input_root, output_dir, options, stats)?);
+    }
+    if providers {
+        outputs.extend(collect_provider_outputs_for(sources, options, stats)?);
+    }
+    Ok(outputs)
+}
+
+/// The `.freezed.dart` and `.g.dart` code generated for one @freezed/@JsonSerializable class of a
+/// Dart source held in memory, e.g. to check generated content in a test. Their `part of` directives
+/// refer to `input.dart`. Both are empty if the source declares no such class named `class_name`.
+///
+/// ```
+/// let source = "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n\n  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);\n}\n";
+///
+/// let result = superfastgen::generate_freezed_to_string(source, "User");
+/// assert!(result.freezed_code.contains("mixin _$User {"));
+/// assert!(result.g_dart_code.contains("'id': instance.id,"));
+/// ```
+pub fn generate_freezed_to_string(source: &str, class_name: &str) -> GenerationResult {
+    // Nothing touches the disk; the path only names the source in `part of`
+    let file_path = Path::new("input.dart");
+    let classes: Vec<DartClass> = parse_dart_content(source, file_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|class| class.name == class_name && FreezedGenerator.matches(class))
+        .collect();
+    if classes.is_empty() {
+        return GenerationResult::default();
+    }
+    generate_freezed_file(file_path, source, &classes, Path::new(""), &GenOptions::default()).unwrap_or_default()
+}
+
+/// Write in-memory generator outputs to disk, stopping at the first file that can't be written
+fn write_generated_outputs(outputs: &[(PathBuf, String)], options: &GenOptions) -> Result<GenStats, GenError> {
+    let mut stats = GenStats::default();
+    for (path, content) in outputs {
+        // Mirrored outputs may go to directories that don't exist yet
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(|e| GenError::io(parent, e))?;
+        }
+        fs::write(path, content).map_err(|e| GenError::io(path, e))?;
+        info!(target: SUCCESS, "Generated: {}", path.display());
+        stats.files += 1;
+        stats.outputs.push(path.clone());
+        if options.add_parts {
+            add_part_directive(path, content)?;
+        }
+    }
+    let written: Vec<PathBuf> = outputs.iter().map(|(path, _)| path.clone()).collect();
+    format_dart_files(&written, options);
+    Ok(stats)
+}
+
+/// Move existing .freezed.dart/.g.dart files from where the `from` layout puts them to where `to`
+/// does (`superfastgen migrate`), rewriting the source's `part` and the output's `part of` directives
+pub fn migrate_output_layout(input_path: &str, output_path: &str, from: OutputLayout, to: OutputLayout, options: &GenOptions) -> Result<GenStats, GenError> {
+    let input_root = input_root(Path::new(input_path));
+    let output_dir = Path::new(output_path);
+    let mut stats = GenStats::default();
+    for file_path in input_dart_files(input_path, options) {
+        let (old_freezed, old_g_dart) = get_safe_output_paths(&file_path, from, input_root, output_dir);
+        let (new_freezed, new_g_dart) = get_safe_output_paths(&file_path, to, input_root, output_dir);
+        for (old_output, new_output) in [(old_freezed, new_freezed), (old_g_dart, new_g_dart)] {
+            if old_output == new_output || !old_output.exists() {
+                continue;
             }
+            move_generated_file(&file_path, &old_output, &new_output)?;
+            info!(target: SUCCESS, "Moved: {} -> {}", old_output.display(), new_output.display());
+            stats.files += 1;
+            stats.outputs.push(new_output);
+        }
+    }
+    Ok(stats)
+}
+
+fn move_generated_file(source: &Path, old_output: &Path, new_output: &Path) -> Result<(), GenError> {
+    let content = fs::read_to_string(old_output).map_err(|e| GenError::io(old_output, e))?;
+    let old_dir = old_output.parent().unwrap_or_else(|| Path::new(""));
+    let new_dir = new_output.parent().unwrap_or_else(|| Path::new(""));
+    let content = content.replace(
+        &format!("part of '{}';", part_of_path(source, old_dir)),
+        &format!("part of '{}';", part_of_path(source, new_dir)),
+    );
+    if !new_dir.as_os_str().is_empty() {
+        fs::create_dir_all(new_dir).map_err(|e| GenError::io(new_dir, e))?;
+    }
+    fs::write(new_output, content).map_err(|e| GenError::io(new_output, e))?;
+    fs::remove_file(old_output).map_err(|e| GenError::io(old_output, e))?;
+    update_part_directive_in_file(source, old_output, new_output)
+}
+
+/// Generated `.g.dart`/`.freezed.dart` files under input_path whose source `.dart` file is gone
+/// or no longer declares anything that would produce them
+pub fn find_orphaned_outputs(input_path: &str) -> Vec<PathBuf> {
+    let mut orphans = Vec::new();
+    for entry in WalkDir::new(input_path).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let (stem, is_freezed) = if let Some(stem) = file_name.strip_suffix(".freezed.dart") {
+            (stem, true)
+        } else if let Some(stem) = file_name.strip_suffix(".g.dart") {
+            (stem, false)
+        } else {
+            continue;
+        };
+        let source_path = path.with_file_name(format!("{}.dart", stem));
+        let classes = if source_path.exists() { parse_dart_file(&source_path).unwrap_or_default() } else { Vec::new() };
+        let still_generated = if is_freezed {
+            classes.iter().any(|class| class.annotations.iter().any(|ann| is_freezed_annotation(ann)))
+        } else {
+            !classes.is_empty()
+        };
+        if !still_generated {
+            orphans.push(path.to_path_buf());
+        }
+    }
+    orphans.sort();
+    orphans
+}
+
+/// Warn about orphaned generated files, or delete them when prune is set
+pub fn handle_orphaned_outputs(input_path: &str, prune: bool, options: &GenOptions) {
+    // Mirrored outputs don't sit next to their sources, so they can't be matched up here
+    if options.output_layout == OutputLayout::Mirror {
+        debug!("Skipping orphan scan for the mirror output layout");
+        return;
+    }
+    for orphan in find_orphaned_outputs(input_path) {
+        if !prune {
+            warn!("Orphaned generated file (no matching source): {} (use --prune-orphans to delete)", orphan.display());
+            continue;
+        }
+        match fs::remove_file(&orphan) {
+            Ok(()) => info!("Removed orphaned file: {}", orphan.display()),
+            Err(e) => error!("Failed to remove {}: {}", orphan.display(), e),
+        }
+    }
+}
+
+/// A class or function found by parse_dart_content (used by `superfastgen list`)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DetectedDeclaration {
+    pub name: String,
+    pub annotations: Vec<String>,
+    pub field_count: usize,
+}
+
+/// Annotated declarations found in one Dart file
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DetectedFile {
+    pub path: String,
+    pub declarations: Vec<DetectedDeclaration>,
+}
+
+/// Parse every Dart file under input_path and report what the generators would see, without generating
+pub fn list_annotated_declarations(input_path: &str, options: &GenOptions) -> Vec<DetectedFile> {
+    let mut files = Vec::new();
+    for file_path in find_dart_files(input_path, options) {
+        let Some(classes) = parse_dart_file(&file_path) else { continue };
+        if classes.is_empty() {
+            continue;
+        }
+        let source_content = fs::read_to_string(&file_path).unwrap_or_default();
+        let declarations = classes.into_iter().map(|class| DetectedDeclaration {
+            field_count: extract_fields_from_dart_class(&source_content, &class.name).len(),
+            name: class.name,
+            annotations: class.annotations,
+        }).collect();
+        files.push(DetectedFile { path: file_path.display().to_string(), declarations });
+    }
+    files
+}
+
+/// Plain-text listing of detected declarations, one file per block
+pub fn format_declaration_listing(files: &[DetectedFile]) -> String {
+    let mut listing = String::new();
+    for file in files {
+        listing.push_str(&format!("{}\n", file.path));
+        for declaration in &file.declarations {
+            listing.push_str(&format!(
+                "  {} [{}] ({} fields)\n",
+                declaration.name,
+                declaration.annotations.join(", "),
+                declaration.field_count
+            ));
+        }
+    }
+    let count: usize = files.iter().map(|f| f.declarations.len()).sum();
+    listing.push_str(&format!("{} annotated declaration(s) in {} file(s)\n", count, files.len()));
+    listing
+}
+
+/// Generate the outputs of the selected generators into memory, in the order they would be written
+pub fn collect_generated_outputs(input_path: &str, output_path: &str, freezed: bool, json: bool, riverpod: bool, provider: bool, options: &GenOptions) -> Result<Vec<(PathBuf, String)>, GenError> {
+    let mut outputs = Vec::new();
+    let mut stats = GenStats::default();
+    if freezed {
+        outputs.extend(collect_annotation_outputs("@freezed", input_path, output_path, options, &mut stats)?);
+    }
+    if json {
+        outputs.extend(collect_annotation_outputs("@JsonSerializable", input_path, output_path, options, &mut stats)?);
+    }
+    // Riverpod and provider generation share the same output
+    if riverpod || provider {
+        outputs.extend(collect_provider_outputs(input_path, options, &mut stats)?);
+    }
+    Ok(outputs)
+}
+
+// Later generators overwrite earlier ones for the same path, so only the last content counts
+fn final_outputs(outputs: &[(PathBuf, String)]) -> Vec<(&PathBuf, &String)> {
+    let mut expected: Vec<(&PathBuf, &String)> = Vec::new();
+    for (path, content) in outputs {
+        if let Some(entry) = expected.iter_mut().find(|(p, _)| *p == path) {
+            entry.1 = content;
         } else {
-            eprintln!("[DEBUG] No providers found, skipping generation");
+            expected.push((path, content));
         }
     }
+    expected
 }
 
-fn convert_dart_class_to_provider_class(dart_class: &DartClass) -> Option<ProviderClass> {
+/// Print the full content of each generated output under a `// ==== <path> ====` header, writing no files
+pub fn print_generated_outputs(outputs: &[(PathBuf, String)], mut out: impl std::io::Write) -> std::io::Result<()> {
+    for (path, content) in final_outputs(outputs) {
+        writeln!(out, "// ==== {} ====", path.display())?;
+        out.write_all(content.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Compare generated outputs against the files on disk without writing anything.
+/// Prints a diff summary for each stale or missing file and returns how many were found.
+pub fn check_generated_outputs(outputs: &[(PathBuf, String)], options: &GenOptions) -> usize {
+    let mut stale_count = 0;
+    for (path, content) in formatted_outputs(final_outputs(outputs), options) {
+        match fs::read_to_string(&path) {
+            Ok(existing) if existing == content => {}
+            Ok(existing) => {
+                stale_count += 1;
+                println!("Stale: {}", path.display());
+                print_diff_summary(&path, &existing, &content);
+            }
+            Err(_) => {
+                stale_count += 1;
+                println!("Missing: {}", path.display());
+            }
+        }
+    }
+
+    if stale_count == 0 {
+        println!("All generated files are up to date");
+    } else {
+        println!("{} generated file(s) are out of date", stale_count);
+    }
+    stale_count
+}
+
+// The outputs as they end up on disk: with --format they're run through `dart format` in a scratch
+// directory, so files written by a --format run don't all look stale
+fn formatted_outputs(outputs: Vec<(&PathBuf, &String)>, options: &GenOptions) -> Vec<(PathBuf, String)> {
+    let mut outputs: Vec<(PathBuf, String)> = outputs.into_iter().map(|(path, content)| (path.clone(), content.clone())).collect();
+    if !options.format || outputs.is_empty() {
+        return outputs;
+    }
+    let scratch_dir = match tempfile::TempDir::new() {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!("Could not create a directory to format the outputs in: {}", e);
+            return outputs;
+        }
+    };
+    let scratch_paths: Vec<PathBuf> = (0..outputs.len()).map(|i| scratch_dir.path().join(format!("{}.dart", i))).collect();
+    for (scratch_path, (_, content)) in scratch_paths.iter().zip(&outputs) {
+        if let Err(e) = fs::write(scratch_path, content) {
+            warn!("Could not write {}: {}", scratch_path.display(), e);
+            return outputs;
+        }
+    }
+    format_dart_files(&scratch_paths, options);
+    for (scratch_path, (_, content)) in scratch_paths.iter().zip(outputs.iter_mut()) {
+        if let Ok(formatted) = fs::read_to_string(scratch_path) {
+            *content = formatted;
+        }
+    }
+    outputs
+}
+
+fn print_diff_summary(path: &Path, existing: &str, generated: &str) {
+    const MAX_DIFF_LINES: usize = 10;
+
+    let existing_lines: Vec<&str> = existing.lines().collect();
+    let generated_lines: Vec<&str> = generated.lines().collect();
+    let first_diff = existing_lines.iter()
+        .zip(generated_lines.iter())
+        .position(|(a, b)| a != b)
+        .unwrap_or_else(|| existing_lines.len().min(generated_lines.len()));
+
+    println!("--- {} (on disk)", path.display());
+    println!("+++ {} (generated)", path.display());
+    println!("@@ line {} @@", first_diff + 1);
+    for line in existing_lines.iter().skip(first_diff).take(MAX_DIFF_LINES) {
+        println!("-{}", line);
+    }
+    for line in generated_lines.iter().skip(first_diff).take(MAX_DIFF_LINES) {
+        println!("+{}", line);
+    }
+}
+
+/// The provider for an annotated class; `content` is the source it was parsed from
+pub(crate) fn convert_dart_class_to_provider_class(dart_class: &DartClass, content: &str) -> Option<ProviderClass> {
     // Extract provider type from annotations
     let provider_types = extract_provider_annotations(&dart_class.annotations);
-    let _provider_type = provider_types.first().cloned().unwrap_or(ProviderType::Provider);
+    let provider_type = provider_types.first().cloned().unwrap_or(ProviderType::Provider);
+    let auto_dispose = is_auto_dispose_annotation(&dart_class.annotations);
+    
+    // Explicit provider annotations take their type from the declaration itself
+    if provider_type != ProviderType::Provider {
+        return Some(ProviderClass {
+            name: dart_class.name.clone(),
+            return_type: extract_provider_value_type(content, &dart_class.name, &provider_type),
+            provider_type,
+            auto_dispose,
+        });
+    }
     
     // Try to extract return type from the class name or annotations
     let return_type = if dart_class.name.ends_with("Notifier") {
@@ -290,37 +1089,70 @@ fn convert_dart_class_to_provider_class(dart_class: &DartClass) -> Option<Provid
     Some(ProviderClass {
         name: dart_class.name.clone(),
         return_type,
+        provider_type,
+        auto_dispose,
     })
 }
 
-fn generate_freezed_by_file(annotation: &str, input_path: &str, output_path: &str) {
-    let dart_files = find_dart_files(input_path);
+/// Find the value type exposed by an annotated provider declaration:
+/// the notifier state type for classes, or the (unwrapped) return type for functions
+fn extract_provider_value_type(content: &str, name: &str, provider_type: &ProviderType) -> String {
+    if provider_type.is_class_based() {
+        let class_pattern = regex::Regex::new(&format!(r"class\s+{}\s+extends\s+\w+<(.+?)>\s*\{{", regex::escape(name))).unwrap();
+        if let Some(cap) = class_pattern.captures(content) {
+            return cap[1].trim().to_string();
+        }
+    } else {
+        let function_pattern = regex::Regex::new(&format!(r"(?m)^\s*([A-Za-z_][\w<>,? ]*?)\s+{}\s*\(", regex::escape(name))).unwrap();
+        if let Some(cap) = function_pattern.captures(content) {
+            let declared = cap[1].trim();
+            let unwrapped = match provider_type {
+                ProviderType::FutureProvider => declared.strip_prefix("Future<").and_then(|t| t.strip_suffix('>')),
+                ProviderType::StreamProvider => declared.strip_prefix("Stream<").and_then(|t| t.strip_suffix('>')),
+                _ => None,
+            };
+            return unwrapped.unwrap_or(declared).to_string();
+        }
+    }
+    "dynamic".to_string()
+}
+
+fn generate_freezed_by_file(annotation: &str, input_path: &str, output_path: &str, options: &GenOptions) {
+    let dart_files = find_dart_files(input_path, options);
     
     for file_path in dart_files {
+        let content = match fs::read_to_string(&file_path) {
+            Ok(content) => content,
+            Err(e) => {
+                error!("Error reading {}: {}", file_path.display(), e);
+                continue;
+            }
+        };
         // Parse all classes from this file
-        if let Some(classes) = parse_dart_file(&file_path) {
+        if let Some(classes) = parse_dart_content(&content, &file_path) {
             // Filter classes with @freezed annotation
             let freezed_classes: Vec<DartClass> = classes
                 .into_iter()
-                .filter(|class| class.annotations.iter().any(|ann| ann.contains(annotation)))
+                .filter(|class| class.annotations.iter().any(|ann| annotation_name(ann) == annotation))
                 .collect();
             
             if !freezed_classes.is_empty() {
                 // Generate one .freezed.dart file for all classes in this file
-                if let Some(result) = generate_freezed_file(&file_path, &freezed_classes) {
-                    // Use safe output path generation
-                    let (freezed_output_path, g_dart_output_path) = get_safe_output_paths(&file_path);
+                // Use safe output path generation
+                let (freezed_output_path, g_dart_output_path) = get_safe_output_paths(&file_path, options.output_layout, Path::new(input_path), Path::new(output_path));
+                let output_dir = freezed_output_path.parent().unwrap_or_else(|| Path::new(""));
+                if let Some(result) = generate_freezed_file(&file_path, &content, &freezed_classes, output_dir, options) {
                     
-                    if let Err(e) = fs::write(&freezed_output_path, &result.freezed_code) {
+                    if let Err(e) = fs::write(&freezed_output_path, finish_generated_code(&result.freezed_code, options.indent)) {
                         error!("Error writing {}: {}", freezed_output_path.display(), e);
                     } else {
-                        info!("Generated: {}", freezed_output_path.display());
+                        info!(target: SUCCESS, "Generated: {}", freezed_output_path.display());
                     }
                     
-                    if let Err(e) = fs::write(&g_dart_output_path, &result.g_dart_code) {
+                    if let Err(e) = fs::write(&g_dart_output_path, finish_generated_code(&result.g_dart_code, options.indent)) {
                         error!("Error writing {}: {}", g_dart_output_path.display(), e);
                     } else {
-                        info!("Generated: {}", g_dart_output_path.display());
+                        info!(target: SUCCESS, "Generated: {}", g_dart_output_path.display());
                     }
                 }
             }
@@ -328,8 +1160,8 @@ fn generate_freezed_by_file(annotation: &str, input_path: &str, output_path: &st
     }
 }
 
-fn find_flutter_project_root() -> Option<PathBuf> {
-    let mut current_dir = std::env::current_dir().ok()?;
+pub(crate) fn find_flutter_project_root(start_dir: &Path) -> Result<PathBuf, GenError> {
+    let mut current_dir = start_dir.to_path_buf();
     
     // Search parent directories for pubspec.yaml
     loop {
@@ -338,7 +1170,7 @@ fn find_flutter_project_root() -> Option<PathBuf> {
         
         if pubspec_path.exists() && lib_path.exists() {
             debug!("Found Flutter project root: {}", current_dir.display());
-            return Some(current_dir);
+            return Ok(current_dir);
         }
         
         // Move to parent directory
@@ -347,7 +1179,7 @@ fn find_flutter_project_root() -> Option<PathBuf> {
         }
     }
     
-    None
+    Err(GenError::NoFlutterProject { searched_from: start_dir.to_path_buf() })
 }
 
 fn find_project_root_from_file(file_path: &Path) -> PathBuf {
@@ -370,84 +1202,144 @@ fn find_project_root_from_file(file_path: &Path) -> PathBuf {
     std::env::current_dir().unwrap_or_else(|_| Path::new(".").to_path_buf())
 }
 
-fn update_part_directive_in_file(input_file: &Path, output_file: &Path) {
-    let content = match fs::read_to_string(input_file) {
-        Ok(content) => content,
-        Err(e) => {
-            eprintln!("[DEBUG] Failed to read input file {}: {}", input_file.display(), e);
-            return;
-        }
+// The directory of the pubspec.yaml above a source file; provider source hashes are taken relative to it
+fn pubspec_dir(file_path: &Path) -> Option<&Path> {
+    file_path.ancestors().skip(1).find(|dir| dir.join("pubspec.yaml").is_file())
+}
+
+// Add `part '<output>';` to the source the output's `part of` points back to, unless it's there already
+fn add_part_directive(output: &Path, output_content: &str) -> Result<(), GenError> {
+    let part_of = regex::Regex::new(r"(?m)^part of '([^']+)';").unwrap();
+    let Some(captures) = part_of.captures(output_content) else {
+        return Ok(());
     };
-    
-    // Calculate relative path from input file to output file
-    let input_dir = input_file.parent().unwrap_or_else(|| Path::new(""));
-    let output_dir = output_file.parent().unwrap_or_else(|| Path::new(""));
-    let output_filename = output_file.file_name().unwrap_or_else(|| std::ffi::OsStr::new(""));
-    
-    let relative_path = if input_dir == output_dir {
-        output_filename.to_string_lossy().to_string()
-    } else {
-        // Calculate relative path from input to output
-        let mut relative = String::new();
-        let mut input_parts: Vec<_> = input_dir.components().collect();
-        let mut output_parts: Vec<_> = output_dir.components().collect();
-        
-        // Find common prefix
-        let mut common_len = 0;
-        for (a, b) in input_parts.iter().zip(output_parts.iter()) {
-            if a == b {
-                common_len += 1;
-            } else {
-                break;
-            }
+    let source = output.parent().unwrap_or_else(|| Path::new("")).join(&captures[1]);
+    if !source.is_file() {
+        debug!("No source {} to add a part directive to", source.display());
+        return Ok(());
+    }
+    let content = fs::read_to_string(&source).map_err(|e| GenError::io(&source, e))?;
+    let part_path = part_of_path(output, source.parent().unwrap_or_else(|| Path::new("")));
+    let directive = format!("part '{}';", part_path);
+    // The source may already name the part in double quotes
+    let double_quoted = format!("part \"{}\";", part_path);
+    if content.lines().any(|line| line.trim() == directive || line.trim() == double_quoted) {
+        return Ok(());
+    }
+    fs::write(&source, insert_part_directive(&content, &directive)).map_err(|e| GenError::io(&source, e))?;
+    info!(target: SUCCESS, "Added {} to {}", directive, source.display());
+    Ok(())
+}
+
+/// Insert a `part` directive where Dart's directive order allows it: after the library, import,
+/// export and existing part directives, and before the first declaration
+fn insert_part_directive(content: &str, directive: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    // Index of the line ending the last directive, and whether that directive is a part
+    let mut last_directive: Option<(usize, bool)> = None;
+    let mut first_declaration = lines.len();
+    let mut in_directive = false;
+    let mut in_block_comment = false;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if in_block_comment {
+            in_block_comment = !trimmed.contains("*/");
+            continue;
         }
-        
-        // Add ".." for each level up from input to common ancestor
-        for _ in 0..(input_parts.len() - common_len) {
-            if !relative.is_empty() {
-                relative.push('/');
-            }
-            relative.push_str("..");
+        if in_directive {
+            // A directive ends at its `;`, e.g. `import 'a.dart'\n    show A;`
+            in_directive = !trimmed.contains(';');
+            let is_part = last_directive.is_some_and(|(_, is_part)| is_part);
+            last_directive = Some((i, is_part));
+            continue;
         }
-        
-        // Add path from common ancestor to output file
-        for part in output_parts.iter().skip(common_len) {
-            if !relative.is_empty() {
-                relative.push('/');
+        if trimmed.is_empty() || (trimmed.starts_with("//") && !trimmed.starts_with("///")) {
+            continue;
+        }
+        if trimmed.starts_with("/*") {
+            in_block_comment = !trimmed.contains("*/");
+            continue;
+        }
+        let keyword = trimmed.split(|c: char| c.is_whitespace() || c == '\'' || c == '"').next().unwrap_or("");
+        if matches!(keyword, "library" | "import" | "export" | "part") {
+            in_directive = !trimmed.contains(';');
+            last_directive = Some((i, keyword == "part"));
+            continue;
+        }
+        first_declaration = i;
+        break;
+    }
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len() + 3);
+    let insert_at = match last_directive {
+        Some((end, is_part)) => {
+            result.extend(&lines[..=end]);
+            // Parts form their own group after the imports and exports
+            if !is_part {
+                result.push("");
             }
-            relative.push_str(&part.as_os_str().to_string_lossy());
+            end + 1
         }
-        
-        // Add filename
-        if !relative.is_empty() {
-            relative.push('/');
+        None => {
+            result.extend(&lines[..first_declaration]);
+            first_declaration
         }
-        relative.push_str(&output_filename.to_string_lossy());
-        
-        relative
     };
-    
-    // Replace the part directive
-    let old_part = format!("part '{}';", output_filename.to_string_lossy());
-    let new_part = format!("part '{}';", relative_path);
+    result.push(directive);
+    if lines.get(insert_at).is_some_and(|line| !line.trim().is_empty()) {
+        result.push("");
+    }
+    result.extend(&lines[insert_at..]);
+    let mut updated = result.join("\n");
+    if content.ends_with('\n') || content.is_empty() {
+        updated.push('\n');
+    }
+    updated
+}
+
+// Point the source's `part` directive for a generated file at the file's new location
+fn update_part_directive_in_file(input_file: &Path, old_output: &Path, new_output: &Path) -> Result<(), GenError> {
+    let content = fs::read_to_string(input_file).map_err(|e| GenError::io(input_file, e))?;
+    let input_dir = input_file.parent().unwrap_or_else(|| Path::new(""));
+    let old_part = format!("part '{}';", part_of_path(old_output, input_dir));
+    let new_part = format!("part '{}';", part_of_path(new_output, input_dir));
     
     let updated_content = content.replace(&old_part, &new_part);
-    
     if updated_content != content {
-        if let Err(e) = fs::write(input_file, updated_content) {
-            eprintln!("[DEBUG] Failed to update part directive in {}: {}", input_file.display(), e);
-        } else {
-            eprintln!("[DEBUG] Updated part directive in {}: {} -> {}", input_file.display(), old_part, new_part);
-        }
+        fs::write(input_file, updated_content).map_err(|e| GenError::io(input_file, e))?;
+        debug!("Updated part directive in {}: {} -> {}", input_file.display(), old_part, new_part);
     }
+    Ok(())
 }
 
-fn find_dart_files(dir_path: &str) -> Vec<PathBuf> {
-    eprintln!("[DEBUG] find_dart_files called with dir_path: {}", dir_path);
+// Build output, tool caches and vendored plugin sources, relative to the scanned directory.
+// They aren't scanned unless --include-hidden is passed.
+const SKIPPED_DIRECTORIES: [&str; 4] = [".dart_tool", "build", "ios/.symlinks", "macos/.symlinks"];
+
+/// Source .dart files under dir_path, honoring .gitignore and .superfastgenignore files
+/// and the --exclude-glob patterns. Symlinks aren't followed, so a link cycle can't loop the walk.
+fn find_dart_files(dir_path: &str, options: &GenOptions) -> Vec<PathBuf> {
+    debug!("find_dart_files called with dir_path: {}", dir_path);
     let mut dart_files = Vec::new();
     
-    for entry in WalkDir::new(dir_path).into_iter().filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
+    let include_hidden = options.include_hidden;
+    let root = PathBuf::from(dir_path);
+    let walker = ignore::WalkBuilder::new(dir_path)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .add_custom_ignore_filename(".superfastgenignore")
+        .filter_entry(move |entry| {
+            let skipped = !include_hidden
+                && entry.file_type().is_some_and(|ft| ft.is_dir())
+                && entry.path().strip_prefix(&root).is_ok_and(|relative| SKIPPED_DIRECTORIES.iter().any(|dir| relative == Path::new(dir)));
+            if skipped {
+                debug!("Skipping directory: {}", entry.path().display());
+            }
+            !skipped
+        })
+        .build();
+    for entry in walker.filter_map(|e| e.ok()) {
+        if entry.file_type().is_some_and(|ft| ft.is_file()) {
             let path = entry.path();
             if let Some(extension) = path.extension() {
                 if extension == "dart" {
@@ -455,40 +1347,55 @@ fn find_dart_files(dir_path: &str) -> Vec<PathBuf> {
                     if let Some(file_name) = path.file_name() {
                         let file_name_str = file_name.to_string_lossy();
                         if file_name_str.ends_with(".freezed.dart") || file_name_str.ends_with(".g.dart") {
-                            eprintln!("[DEBUG] Skipping generated file: {}", path.display());
+                            debug!("Skipping generated file: {}", path.display());
                             continue;
                         }
                     }
-                    eprintln!("[DEBUG] Found Dart file: {}", path.display());
+                    if is_excluded_source(path, Path::new(dir_path), &options.exclude) {
+                        debug!("Skipping excluded file: {}", path.display());
+                        continue;
+                    }
+                    debug!("Found Dart file: {}", path.display());
                     dart_files.push(path.to_path_buf());
                 }
             }
         }
     }
+    // Stable order regardless of directory listing order
+    dart_files.sort();
     
-    eprintln!("[DEBUG] find_dart_files returning {} files", dart_files.len());
+    debug!("find_dart_files returning {} files", dart_files.len());
     dart_files
 }
 
+// Whether an --exclude-glob pattern matches the file's path relative to the scanned directory
+fn is_excluded_source(path: &Path, root: &Path, patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return false;
+    }
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    super::assets::matches_any_glob(&dart_uri_path(&relative.to_string_lossy()), patterns)
+}
+
 fn clean_output_directory(output_dir: &Path) -> Result<(), std::io::Error> {
-    eprintln!("[DEBUG] clean_output_directory called for: {}", output_dir.display());
+    debug!("clean_output_directory called for: {}", output_dir.display());
     if !output_dir.exists() {
-        eprintln!("[DEBUG] Output directory does not exist: {}", output_dir.display());
+        debug!("Output directory does not exist: {}", output_dir.display());
         return Ok(());
     }
     
-    eprintln!("[DEBUG] Scanning output directory: {}", output_dir.display());
+    debug!("Scanning output directory: {}", output_dir.display());
     for entry in WalkDir::new(output_dir).into_iter().filter_map(|e| e.ok()) {
         if entry.file_type().is_file() {
             let path = entry.path();
-            eprintln!("[DEBUG] Found file: {}", path.display());
+            debug!("Found file: {}", path.display());
             if let Some(file_name) = path.file_name() {
                 let file_name_str = file_name.to_string_lossy();
-                eprintln!("[DEBUG] File name: {}", file_name_str);
+                debug!("File name: {}", file_name_str);
                 if file_name_str.ends_with(".g.dart") || file_name_str.ends_with(".freezed.dart") {
                     info!("Deleting conflicting output: {}", path.display());
                     fs::remove_file(path)?;
-                    eprintln!("[DEBUG] Deleted file: {}", path.display());
+                    debug!("Deleted file: {}", path.display());
                 }
             }
         }
@@ -498,9 +1405,9 @@ fn clean_output_directory(output_dir: &Path) -> Result<(), std::io::Error> {
 }
 
 fn clean_output_directory_all_g_dart(input_path: &Path) -> Result<(), std::io::Error> {
-    eprintln!("[DEBUG] clean_output_directory_all_g_dart called for: {}", input_path.display());
+    debug!("clean_output_directory_all_g_dart called for: {}", input_path.display());
     if !input_path.exists() {
-        eprintln!("[DEBUG] Input directory does not exist: {}", input_path.display());
+        debug!("Input directory does not exist: {}", input_path.display());
         return Ok(());
     }
     for entry in WalkDir::new(input_path).into_iter().filter_map(|e| e.ok()) {
@@ -511,7 +1418,7 @@ fn clean_output_directory_all_g_dart(input_path: &Path) -> Result<(), std::io::E
                 if file_name_str.ends_with(".g.dart") || file_name_str.ends_with(".freezed.dart") {
                     info!("Deleting conflicting output (all): {}", path.display());
                     fs::remove_file(path)?;
-                    eprintln!("[DEBUG] Deleted file (all): {}", path.display());
+                    debug!("Deleted file (all): {}", path.display());
                 }
             }
         }
@@ -520,9 +1427,9 @@ fn clean_output_directory_all_g_dart(input_path: &Path) -> Result<(), std::io::E
 }
 
 fn clean_freezed_files(input_path: &Path) -> Result<(), std::io::Error> {
-    eprintln!("[DEBUG] clean_freezed_files called for: {}", input_path.display());
+    debug!("clean_freezed_files called for: {}", input_path.display());
     if !input_path.exists() {
-        eprintln!("[DEBUG] Input directory does not exist: {}", input_path.display());
+        debug!("Input directory does not exist: {}", input_path.display());
         return Ok(());
     }
     for entry in WalkDir::new(input_path).into_iter().filter_map(|e| e.ok()) {
@@ -533,7 +1440,7 @@ fn clean_freezed_files(input_path: &Path) -> Result<(), std::io::Error> {
                 if file_name_str.ends_with(".freezed.dart") {
                     info!("Deleting freezed file: {}", path.display());
                     fs::remove_file(path)?;
-                    eprintln!("[DEBUG] Deleted freezed file: {}", path.display());
+                    debug!("Deleted freezed file: {}", path.display());
                 }
             }
         }
@@ -542,7 +1449,7 @@ fn clean_freezed_files(input_path: &Path) -> Result<(), std::io::Error> {
 }
 
 fn parse_dart_file(file_path: &Path) -> Option<Vec<DartClass>> {
-    eprintln!("[DEBUG] parse_dart_file called: {}", file_path.display());
+    debug!("parse_dart_file called: {}", file_path.display());
     let content = match fs::read_to_string(file_path) {
         Ok(content) => content,
         Err(e) => {
@@ -555,62 +1462,129 @@ fn parse_dart_file(file_path: &Path) -> Option<Vec<DartClass>> {
     parse_dart_content(&content, file_path)
 }
 
+// A declaration's return type, including nested generics and nullability (`Future<List<User>?>`)
+const RETURN_TYPE_PATTERN: &str = r"[A-Za-z_][\w<>,? ]*?";
+
+// What may sit between an annotation and its declaration: a line break plus any
+// blank lines, `//`/`///` comments, `/* */` blocks and further annotations
+pub(crate) const ANNOTATION_GAP: &str = r"[ \t]*(?://[^\n]*)?\n(?:\s*(?://[^\n]*|(?s:/\*.*?\*/)|@[\w.]+(?:\([^)]*\))?))*\s*";
+
 fn parse_dart_content(content: &str, file_path: &Path) -> Option<Vec<DartClass>> {
-    eprintln!("[DEBUG] parse_dart_content called: {} ({} bytes)", file_path.display(), content.len());
+    debug!("parse_dart_content called: {} ({} bytes)", file_path.display(), content.len());
     
     let mut classes = Vec::new();
     
     // Use regex to find class declarations with @freezed annotations
-    let class_pattern = regex::Regex::new(r"@freezed\s*\n\s*class\s+(\w+)").unwrap();
-    let json_pattern = regex::Regex::new(r"@JsonSerializable\s*\n\s*class\s+(\w+)").unwrap();
-    let riverpod_class_pattern = regex::Regex::new(r"@riverpod\s*\n\s*class\s+(\w+)").unwrap();
-    let riverpod_function_pattern = regex::Regex::new(r"@riverpod\s*\n\s*(?:Future<[^>]+>|Stream<[^>]+>|[A-Za-z_][A-Za-z0-9_]*)\s+(\w+)\s*\(").unwrap();
+    let class_pattern = regex::Regex::new(&format!(r"(@freezed|@Freezed(?:\([^)]*\))?){}(?:sealed\s+)?class\s+(\w+)", ANNOTATION_GAP)).unwrap();
+    let json_pattern = regex::Regex::new(&format!(r"(@JsonSerializable(?:\([^)]*\))?){}class\s+(\w+)", ANNOTATION_GAP)).unwrap();
+    let riverpod_class_pattern = regex::Regex::new(&format!(r"(@riverpod|@Riverpod(?:\([^)]*\))?){}(abstract\s+)?class\s+([\w$]+)", ANNOTATION_GAP)).unwrap();
+    // Block and expression-bodied functions (`Type name(...) => ...`) as well as getters (`Type get name => ...`)
+    let riverpod_function_pattern = regex::Regex::new(&format!(r"(@riverpod|@Riverpod(?:\([^)]*\))?){}{}\s+(?:get\s+(\w+)\s*(?:=>|\{{|async)|(\w+)\s*\()", ANNOTATION_GAP, RETURN_TYPE_PATTERN)).unwrap();
+    let provider_annotation_pattern = regex::Regex::new(&format!(r"@((?:AutoDispose)?(?:Future|Stream|State|StateNotifier|AsyncNotifier|Notifier)?Provider){}(?:(abstract\s+)?class\s+([\w$]+)|[A-Za-z_][\w<>,? ]*?\s+(\w+)\s*\()", ANNOTATION_GAP)).unwrap();
     
     // Find @freezed classes
     for cap in class_pattern.captures_iter(content) {
-        let class_name = cap[1].to_string();
-        eprintln!("[DEBUG] Found @freezed class: {}", class_name);
+        let class_name = cap[2].to_string();
+        debug!("Found {} class: {}", &cap[1], class_name);
         classes.push(DartClass {
             name: class_name,
-            annotations: vec!["@freezed".to_string()],
+            annotations: vec![cap[1].to_string()],
             file_path: file_path.to_path_buf(),
         });
     }
     
     // Find @JsonSerializable classes
     for cap in json_pattern.captures_iter(content) {
-        let class_name = cap[1].to_string();
-        eprintln!("[DEBUG] Found @JsonSerializable class: {}", class_name);
+        let class_name = cap[2].to_string();
+        debug!("Found {} class: {}", &cap[1], class_name);
         classes.push(DartClass {
             name: class_name,
-            annotations: vec!["@JsonSerializable".to_string()],
+            annotations: vec![cap[1].to_string()],
             file_path: file_path.to_path_buf(),
         });
     }
     
     // Find @riverpod classes
     for cap in riverpod_class_pattern.captures_iter(content) {
-        let class_name = cap[1].to_string();
-        eprintln!("[DEBUG] Found @riverpod class: {}", class_name);
+        let class_name = cap[3].to_string();
+        if is_excluded_provider_class(cap.get(2).is_some(), &class_name) {
+            debug!("Skipping {} class: {}", &cap[1], class_name);
+            continue;
+        }
+        debug!("Found {} class: {}", &cap[1], class_name);
         classes.push(DartClass {
             name: class_name,
-            annotations: vec!["@riverpod".to_string()],
+            annotations: vec![cap[1].to_string()],
             file_path: file_path.to_path_buf(),
         });
     }
     
     // Find @riverpod functions
     for cap in riverpod_function_pattern.captures_iter(content) {
-        let function_name = cap[1].to_string();
-        eprintln!("[DEBUG] Found @riverpod function: {}", function_name);
+        let function_name = cap.get(2).or_else(|| cap.get(3)).unwrap().as_str().to_string();
+        debug!("Found {} function: {}", &cap[1], function_name);
         classes.push(DartClass {
             name: function_name,
-            annotations: vec!["@riverpod".to_string()],
+            annotations: vec![cap[1].to_string()],
+            file_path: file_path.to_path_buf(),
+        });
+    }
+    
+    // Find classes with annotations of registered generators
+    for generator in registered_generators() {
+        let pattern = format!(r"({}(?:\([^)]*\))?){}(?:(?:abstract|sealed|final|base)\s+)*class\s+(\w+)", regex::escape(generator.annotation()), ANNOTATION_GAP);
+        let Ok(custom_pattern) = regex::Regex::new(&pattern) else { continue };
+        for cap in custom_pattern.captures_iter(content) {
+            debug!("Found {} class: {}", &cap[1], &cap[2]);
+            classes.push(DartClass {
+                name: cap[2].to_string(),
+                annotations: vec![cap[1].to_string()],
+                file_path: file_path.to_path_buf(),
+            });
+        }
+    }
+    
+    // Find classes and functions with explicit provider annotations (@FutureProvider, @StateNotifierProvider, ...)
+    for cap in provider_annotation_pattern.captures_iter(content) {
+        let annotation = format!("@{}", &cap[1]);
+        let name = cap.get(3).or_else(|| cap.get(4)).unwrap().as_str().to_string();
+        if is_excluded_provider_class(cap.get(2).is_some(), &name) {
+            debug!("Skipping {} class: {}", annotation, name);
+            continue;
+        }
+        debug!("Found {} declaration: {}", annotation, name);
+        classes.push(DartClass {
+            name,
+            annotations: vec![annotation],
             file_path: file_path.to_path_buf(),
         });
     }
     
-    Some(classes)
+    Some(merge_declaration_annotations(classes))
+}
+
+// Abstract base classes and already generated `_$Name` classes never get a provider
+fn is_excluded_provider_class(is_abstract: bool, name: &str) -> bool {
+    is_abstract || name.starts_with("_$")
+}
+
+// A declaration carrying several annotations (e.g. `@freezed` + `@JsonSerializable`) is matched
+// once per annotation; fold those matches into one entry holding every annotation
+fn merge_declaration_annotations(classes: Vec<DartClass>) -> Vec<DartClass> {
+    let mut merged: Vec<DartClass> = Vec::new();
+    for class in classes {
+        match merged.iter_mut().find(|existing| existing.name == class.name && existing.file_path == class.file_path) {
+            Some(existing) => {
+                for annotation in class.annotations {
+                    if !existing.annotations.contains(&annotation) {
+                        existing.annotations.push(annotation);
+                    }
+                }
+            }
+            None => merged.push(class),
+        }
+    }
+    merged
 }
 
 fn extract_fields_from_declaration(declaration: tree_sitter::Node, source: &str, fields: &mut Vec<DartField>, tree: &tree_sitter::Tree) {
@@ -654,12 +1628,12 @@ fn extract_fields_from_declaration(declaration: tree_sitter::Node, source: &str,
         for name in field_names {
             if !fields.iter().any(|f| f.name == name) {
                 // Check for nullable type in the declaration text
-                let final_type = if declaration_text.contains('?') && !ty.ends_with('?') {
+                let final_type = if has_nullable_type(declaration_text) && !ty.ends_with('?') {
                     format!("{}?", ty)
                 } else {
                     ty.clone()
                 };
-                fields.push(DartField { name: name.clone(), ty: final_type.clone(), is_named: false, has_default: false, default_value: None });
+                fields.push(DartField { name: name.clone(), ty: final_type.clone(), is_named: false, has_default: false, default_value: None, omit_if_null: false, converter: None });
                 debug!("Added field: {} {}", final_type, name);
             }
         }
@@ -711,7 +1685,7 @@ fn extract_field_from_parameter(param: tree_sitter::Node, source: &str, fields:
     }
     
     if !name.is_empty() && !ty.is_empty() {
-        fields.push(DartField { name, ty, is_named: false, has_default: false, default_value: None });
+        fields.push(DartField { name, ty, is_named: false, has_default: false, default_value: None, omit_if_null: false, converter: None });
     }
 }
 
@@ -728,7 +1702,7 @@ fn extract_field_from_typed_identifier(typed_id: tree_sitter::Node, source: &str
     }
     
     if !name.is_empty() && !ty.is_empty() {
-        fields.push(DartField { name, ty, is_named: false, has_default: false, default_value: None });
+        fields.push(DartField { name, ty, is_named: false, has_default: false, default_value: None, omit_if_null: false, converter: None });
     }
 }
 
@@ -808,9 +1782,9 @@ fn extract_field_from_formal_parameter(param: tree_sitter::Node, source: &str, f
     
     if let (Some(ty), Some(name)) = (ty, name) {
         // Determine the final type
-        let final_type = if param_text.contains('?') && !ty.ends_with('?') {
+        let final_type = if has_nullable_type(param_text) && !ty.ends_with('?') {
             format!("{}?", ty.clone())
-        } else if param_text.contains('?') && ty.ends_with('?') {
+        } else if has_nullable_type(param_text) && ty.ends_with('?') {
             ty.clone() // If it already has ?, keep it
         } else {
             // @Default annotation means the field has a default value, so it's non-nullable
@@ -819,21 +1793,22 @@ fn extract_field_from_formal_parameter(param: tree_sitter::Node, source: &str, f
         
         debug!("Extracted field: {} {} (final: {}, has_default: {})", ty, name, final_type, has_default_annotation);
         if !fields.iter().any(|f| f.name == name) {
-            fields.push(DartField { name, ty: final_type, is_named: false, has_default: has_default_annotation, default_value: None });
+            fields.push(DartField { name, ty: final_type, is_named: false, has_default: has_default_annotation, default_value: None, omit_if_null: false, converter: None });
             debug!("Added field to list");
         }
     }
 }
 
 fn generate_g_dart_file_with_output_path(class: &DartClass, generator_type: &str, output_path: &str) -> Option<GenerationResult> {
-    eprintln!("[DEBUG] generate_g_dart_file_with_output_path called: class={}, type={}, output={}", class.name, generator_type, output_path);
+    debug!("generate_g_dart_file_with_output_path called: class={}, type={}, output={}", class.name, generator_type, output_path);
     
+    let source = fs::read_to_string(&class.file_path).unwrap_or_default();
     let generated_code = match generator_type {
-        "json" => generate_json_code(class),
+        "json" => generate_json_code(class, &source, &GenOptions::default()),
         _ => return None,
     };
     
-    eprintln!("[DEBUG] Generated code length: {} characters", generated_code.len());
+    debug!("Generated code length: {} characters", generated_code.len());
     
     // Create output file path - use the same directory as the source file
     let mut output_file = class.file_path.parent().unwrap_or_else(|| Path::new(output_path)).to_path_buf();
@@ -877,7 +1852,7 @@ fn generate_riverpod_code(class: &DartClass) -> String {
 
     // Extract function and class information from source file
     let source_content = std::fs::read_to_string(&class.file_path).unwrap_or_default();
-    let functions = extract_functions_from_dart_source(&source_content, &class.file_path);
+    let functions = extract_functions_from_dart_source(&source_content, &class.file_path, None);
     
     debug!("Found {} functions in {}", functions.len(), class.file_path.display());
     for function in &functions {
@@ -886,7 +1861,7 @@ fn generate_riverpod_code(class: &DartClass) -> String {
     
     // Generate providers from functions with @riverpod annotation
     for function in &functions {
-        if function.annotations.iter().any(|ann| ann.contains("@riverpod")) {
+        if function.annotations.iter().any(|ann| is_riverpod_annotation(ann)) {
             debug!("Generating provider for function: {}", function.name);
             code.push_str(&generate_function_provider(function));
             code.push_str("\n");
@@ -894,7 +1869,7 @@ fn generate_riverpod_code(class: &DartClass) -> String {
     }
     
     // Generate NotifierProvider for class types
-    if class.annotations.iter().any(|ann| ann.contains("@riverpod")) {
+    if class.annotations.iter().any(|ann| is_riverpod_annotation(ann)) {
         debug!("Generating NotifierProvider for class: {}", class.name);
         code.push_str(&generate_notifier_provider(class));
     }
@@ -930,14 +1905,14 @@ fn generate_function_provider(function: &DartFunction) -> String {
     
     // Check for family support (whether parameters exist beyond Ref)
     let has_family_parameters = function.parameters.iter()
-        .any(|p| p.name != "ref" && p.ty != "Ref");
+        .any(|p| !is_ref_parameter(p));
     
     debug!("Function {}: has_family_parameters = {}", function.name, has_family_parameters);
     
     if has_family_parameters {
         debug!("Generating family provider for function: {}", function.name);
         let family_params: Vec<_> = function.parameters.iter()
-            .filter(|p| p.name != "ref" && p.ty != "Ref")
+            .filter(|p| !is_ref_parameter(p))
             .collect();
         
         debug!("Family params: {:?}", family_params);
@@ -1010,7 +1985,11 @@ fn generate_function_provider(function: &DartFunction) -> String {
         code.push_str(&format!("final {} = {}<{}>((ref) {{\n", 
             provider_name, provider_type, actual_return_type
         ));
-        code.push_str(&format!("  return {}(ref);\n", function.name));
+        if function.is_getter {
+            code.push_str(&format!("  return {};\n", function.name));
+        } else {
+            code.push_str(&format!("  return {}(ref);\n", function.name));
+        }
         code.push_str("});\n");
     }
     
@@ -1108,7 +2087,10 @@ fn to_lower_camel_case(s: &str) -> String {
     }
 }
 
-fn to_pascal_case(s: &str) -> String {
+/// PascalCase for identifiers such as union case names: `in_progress`/`inProgress` -> `InProgress`.
+/// Segments split on whitespace, `_` and `-` get an uppercase first letter; the rest is kept
+/// as written, so `HTTPError` stays as is and `case_2` becomes `Case2`.
+pub(crate) fn to_pascal_case(s: &str) -> String {
     let mut result = String::new();
     let mut capitalize_next = true;
     
@@ -1169,24 +2151,36 @@ fn write_ast_to_file(node: tree_sitter::Node, source: &str, depth: usize, file:
     }
 }
 
-/// Extract Dart function information using tree-sitter-dart
-pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec<DartFunction> {
+/// Extract Dart function information using tree-sitter-dart, appending the AST to `dump_ast` if given
+pub fn extract_functions_from_dart_source(source: &str, file_path: &Path, dump_ast: Option<&Path>) -> Vec<DartFunction> {
     debug!("Processing source with {} characters", source.len());
     if source.len() > 100 {
         debug!("Source preview: {}", &source[..100]);
     }
     
     let mut parser = Parser::new();
-    parser.set_language(unsafe { std::mem::transmute(tree_sitter_dart()) }).unwrap();
-    let tree = parser.parse(source, None).unwrap();
+    if let Err(e) = parser.set_language(unsafe { std::mem::transmute(tree_sitter_dart()) }) {
+        error!("skipping {}: parse failed ({})", file_path.display(), e);
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        error!("skipping {}: parse failed", file_path.display());
+        return Vec::new();
+    };
     let root = tree.root_node();
     let mut functions = Vec::new();
 
-    // Output AST to file for debugging
-    let mut file = OpenOptions::new().create(true).write(true).append(true).open("debug_ast.txt").unwrap();
-    writeln!(file, "\n=== Complete AST for {} ===", file_path.display()).unwrap();
-    write_ast_to_file(root, source, 0, &mut file);
-    writeln!(file, "=== End AST ===").unwrap();
+    // Output AST to file for debugging (only with --dump-ast)
+    if let Some(dump_path) = dump_ast {
+        match OpenOptions::new().create(true).append(true).open(dump_path) {
+            Ok(mut file) => {
+                let _ = writeln!(file, "\n=== Complete AST for {} ===", file_path.display());
+                write_ast_to_file(root, source, 0, &mut file);
+                let _ = writeln!(file, "=== End AST ===");
+            }
+            Err(e) => error!("Failed to open {}: {}", dump_path.display(), e),
+        }
+    }
 
     // Recursively visit all nodes to find function declarations
     fn visit_functions_recursive(node: tree_sitter::Node, source: &str, file_path: &Path, functions: &mut Vec<DartFunction>) {
@@ -1287,7 +2281,7 @@ pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec
                                     param_name = param_child.utf8_text(source.as_bytes()).unwrap_or("param").to_string();
                                 }
                             }
-                            if param_text.contains('?') && !param_type.ends_with('?') {
+                            if has_nullable_type(param_text) && !param_type.ends_with('?') {
                                 param_type.push('?');
                             }
                             parameters.push(DartField {
@@ -1296,6 +2290,8 @@ pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec
                                 is_named,
                                 has_default: false,
                                 default_value: None,
+                                omit_if_null: false,
+                                converter: None,
                             });
                         } else if param.kind() == "optional_formal_parameters" {
                             debug!("Found optional formal parameters");
@@ -1313,7 +2309,7 @@ pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec
                                             param_name = opt_param_child.utf8_text(source.as_bytes()).unwrap_or("param").to_string();
                                         }
                                     }
-                                    if opt_param_text.contains('?') && !param_type.ends_with('?') {
+                                    if has_nullable_type(opt_param_text) && !param_type.ends_with('?') {
                                         param_type.push('?');
                                     }
                                     parameters.push(DartField {
@@ -1322,6 +2318,8 @@ pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec
                                         is_named,
                                         has_default: false,
                                         default_value: None,
+                                        omit_if_null: false,
+                                        converter: None,
                                     });
                                 }
                             }
@@ -1335,6 +2333,8 @@ pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec
                 parameters,
                 annotations,
                 file_path: file_path.to_path_buf(),
+                is_getter: false,
+                source: node.utf8_text(source.as_bytes()).unwrap_or("").to_string(),
             });
         }
         for child in node.children(&mut node.walk()) {
@@ -1342,110 +2342,48 @@ pub fn extract_functions_from_dart_source(source: &str, file_path: &Path) -> Vec
         }
     }
     visit_functions_recursive(root, source, file_path, &mut functions);
+    functions.extend(extract_riverpod_getters(source, file_path));
     functions
 }
 
-fn parse_dart_parameter(param: &str) -> Option<DartField> {
-    let param = param.trim();
-    
-    // Skip comments and empty parameters
-    if param.starts_with("//") || param.starts_with("/*") || param.is_empty() {
-        return None;
-    }
-    
-    // Skip lines that are just comments or comment fragments
-    if param.chars().all(|c| c.is_whitespace() || c == '/') {
-        return None;
-    }
-    
-    // Skip standalone comment words like "draft", "published", "cancelled", "completed"
-    let comment_words = ["draft", "published", "cancelled", "completed", "pending", "succeeded", "failed"];
-    if comment_words.iter().any(|&word| param == word) {
-        return None;
-    }
-    
-    // Skip if the parameter contains comment fragments at the beginning
-    if comment_words.iter().any(|&word| param.starts_with(word)) {
-        return None;
-    }
-    
-    // Skip parameters that start with comment fragments
-    if param.starts_with("// ") || param.starts_with("/* ") {
-        return None;
-    }
-    
-    // Clean up the parameter by removing any trailing comments
-    let clean_param = if let Some(comment_start) = param.find("//") {
-        param[..comment_start].trim()
-    } else if let Some(comment_start) = param.find("/*") {
-        param[..comment_start].trim()
-    } else {
-        param
-    };
-    
-    if clean_param.is_empty() {
-        return None;
-    }
-    
-    // Handle @Default annotation
-    let (param_without_default, has_default, default_value) = if clean_param.contains("@Default") {
-        // Extract the parameter part after @Default(...)
-        if let Some(start_paren) = clean_param.find("@Default(") {
-            if let Some(end_paren) = clean_param[start_paren..].find(')') {
-                let after_default = &clean_param[start_paren + end_paren + 1..];
-                let default_val = &clean_param[start_paren + 9..start_paren + end_paren];
-                (after_default.trim(), true, Some(default_val.to_string()))
-            } else {
-                (clean_param, true, None)
+/// `@riverpod` getters (`@riverpod int get count => 0;`), which have no function_declaration node
+fn extract_riverpod_getters(source: &str, file_path: &Path) -> Vec<DartFunction> {
+    let getter_pattern = regex::Regex::new(&format!(r"(@riverpod|@Riverpod(?:\([^)]*\))?){}({})\s+get\s+(\w+)\s*(?:=>|\{{|async)", ANNOTATION_GAP, RETURN_TYPE_PATTERN)).unwrap();
+    getter_pattern.captures_iter(source).map(|cap| {
+        debug!("Found {} getter: {}", &cap[1], &cap[3]);
+        let declaration = cap.get(0).unwrap();
+        let end = declaration_end(source, declaration.end());
+        DartFunction {
+            name: cap[3].to_string(),
+            return_type: cap[2].trim().to_string(),
+            parameters: Vec::new(),
+            annotations: vec![cap[1].to_string()],
+            file_path: file_path.to_path_buf(),
+            is_getter: true,
+            source: source[declaration.start()..end].to_string(),
+        }
+    }).collect()
+}
+
+// End of a declaration whose body starts before `from`: after the `;` of an expression body,
+// or after the `}` closing a block body
+fn declaration_end(source: &str, from: usize) -> usize {
+    let block_body = source[..from].ends_with('{') || source[from..].trim_start().starts_with('{');
+    let mut depth = usize::from(source[..from].ends_with('{'));
+    for (offset, c) in source[from..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if block_body && depth == 0 {
+                    return from + offset + 1;
+                }
             }
-        } else {
-            (clean_param, true, None)
+            ';' if !block_body && depth == 0 => return from + offset + 1,
+            _ => {}
         }
-    } else {
-        (clean_param, false, None)
-    };
-    
-    // Handle required keyword
-    let (param_without_required, _is_required) = if param_without_default.starts_with("required") {
-        (param_without_default[8..].trim(), true)
-    } else {
-        (param_without_default, false)
-    };
-    
-    // Parse type and name
-    let parts: Vec<&str> = param_without_required.split_whitespace().collect();
-    if parts.len() < 2 {
-        return None;
     }
-    
-    let type_part = parts[0];
-    let name_part = parts[1];
-    
-    // Skip if name contains comment fragments
-    if comment_words.iter().any(|&word| name_part.contains(word)) {
-        return None;
-    }
-    
-    // Clean up the name (remove trailing commas, etc.)
-    let clean_name = name_part.trim_end_matches(',').trim();
-    
-    // Skip if name is empty or contains invalid characters
-    if clean_name.is_empty() || clean_name.chars().any(|c| !c.is_alphanumeric() && c != '_') {
-        return None;
-    }
-    
-    // Skip if the type or name contains comment fragments
-    if comment_words.iter().any(|&word| type_part.contains(word) || clean_name.contains(word)) {
-        return None;
-    }
-    
-    Some(DartField {
-        name: clean_name.to_string(),
-        ty: type_part.to_string(),
-        is_named: true,
-        has_default,
-        default_value,
-    })
+    source.len()
 }
 
 fn extract_fields_from_field_declaration(field_decl: tree_sitter::Node, source: &str, fields: &mut Vec<DartField>, tree: &tree_sitter::Tree) {
@@ -1455,7 +2393,7 @@ fn extract_fields_from_field_declaration(field_decl: tree_sitter::Node, source:
     for child in field_decl.children(&mut tree.walk()) {
         let kind = child.kind();
         let text = child.utf8_text(source.as_bytes()).unwrap_or("");
-        println!("[DEBUG] field_decl child kind: {} | text: {}", kind, text);
+        debug!("field_decl child kind: {} | text: {}", kind, text);
     }
 
     // Robustly extract all type/name pairs from field_declaration for normal Dart classes
@@ -1483,73 +2421,346 @@ fn extract_fields_from_field_declaration(field_decl: tree_sitter::Node, source:
             debug!("Found field name: {}", name);
         }
     }
-    
-    // Create type/name pairs
-    if let Some(ty) = field_type {
-        for name in field_names {
-            if !fields.iter().any(|f| f.name == name) {
-                // Check for nullable type
-                let final_type = if field_text.contains('?') && !ty.ends_with('?') {
-                    format!("{}?", ty)
-                } else {
-                    ty.clone()
-                };
-                fields.push(DartField { name: name.clone(), ty: final_type.clone(), is_named: false, has_default: false, default_value: None });
-                debug!("Added field: {} {}", final_type, name);
+    
+    // Create type/name pairs
+    if let Some(ty) = field_type {
+        for name in field_names {
+            if !fields.iter().any(|f| f.name == name) {
+                // Check for nullable type
+                let final_type = if has_nullable_type(field_text) && !ty.ends_with('?') {
+                    format!("{}?", ty)
+                } else {
+                    ty.clone()
+                };
+                fields.push(DartField { name: name.clone(), ty: final_type.clone(), is_named: false, has_default: false, default_value: None, omit_if_null: false, converter: None });
+                debug!("Added field: {} {}", final_type, name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_dart_files() {
+        // Create a temporary directory for testing
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+
+        // Create a test Dart file
+        fs::write(lib_dir.join("test.dart"), "class Test {}").unwrap();
+        fs::write(lib_dir.join("test.txt"), "not a dart file").unwrap();
+
+        let dart_files = find_dart_files(temp_dir.path().join("lib").to_str().unwrap(), &GenOptions::default());
+        
+        assert_eq!(dart_files.len(), 1);
+        assert!(dart_files[0].file_name().unwrap() == "test.dart");
+    }
+
+    #[test]
+    fn test_parse_dart_content() {
+        let content = r#"
+@freezed
+class User {
+  const factory User({required String name}) = _User;
+}
+"#;
+        
+        let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
+        
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].name, "User");
+        assert!(classes[0].annotations.iter().any(|ann| ann.contains("@freezed")));
+    }
+
+    #[test]
+    fn test_print_generated_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let model_path = temp_dir.path().join("user.dart");
+        fs::write(&model_path, "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n\n  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);\n}\n").unwrap();
+        let input_path = temp_dir.path().to_string_lossy().to_string();
+
+        let outputs = collect_generated_outputs(&input_path, &input_path, true, true, false, false, &GenOptions::default()).unwrap();
+        let mut printed = Vec::new();
+        print_generated_outputs(&outputs, &mut printed).unwrap();
+        let printed = String::from_utf8(printed).unwrap();
+        assert!(printed.contains(&format!("// ==== {} ====\n", temp_dir.path().join("user.freezed.dart").display())));
+        assert!(printed.contains("mixin _$User {"));
+        // The json pass rewrites the same files, so each is printed once
+        assert_eq!(printed.matches("// ==== ").count(), 2);
+        assert!(!temp_dir.path().join("user.freezed.dart").exists());
+    }
+
+    #[test]
+    fn test_generate_source_in_memory() {
+        let source = "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n\n  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);\n}\n";
+        let outputs = generate_source(source, "models/user.dart", true, false, false, false, &GenOptions::default()).unwrap();
+        let names: Vec<_> = outputs.iter().map(|(path, _)| path.to_string_lossy().to_string()).collect();
+        assert_eq!(names, ["user.freezed.dart", "user.g.dart"]);
+        assert!(outputs[0].1.contains("part of 'user.dart';"));
+    }
+
+    #[test]
+    fn test_freezed_model_without_from_json_gets_no_g_dart() {
+        let source = "@freezed\nclass Point with _$Point {\n  const factory Point({required int x, required int y}) = _Point;\n}\n";
+        let outputs = generate_source(source, "point.dart", true, false, false, false, &GenOptions::default()).unwrap();
+        let names: Vec<_> = outputs.iter().map(|(path, _)| path.to_string_lossy().to_string()).collect();
+        assert_eq!(names, ["point.freezed.dart"]);
+        let freezed_code = &outputs[0].1;
+        assert!(freezed_code.contains("mixin _$Point {"));
+        assert!(!freezed_code.contains("toJson"));
+        assert!(!freezed_code.contains("FromJson"));
+        assert!(!freezed_code.contains("@JsonKey"));
+    }
+
+    #[test]
+    fn test_missing_flutter_project_is_an_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        match find_flutter_project_root(&nested) {
+            Err(GenError::NoFlutterProject { searched_from }) => assert_eq!(searched_from, nested),
+            other => panic!("expected NoFlutterProject, got {:?}", other),
+        }
+
+        fs::write(temp_dir.path().join("pubspec.yaml"), "name: app\n").unwrap();
+        fs::create_dir(temp_dir.path().join("lib")).unwrap();
+        assert_eq!(find_flutter_project_root(&nested).unwrap(), temp_dir.path());
+    }
+
+    #[test]
+    fn test_finish_generated_code_indent_and_trailing_newline() {
+        let code = "class A {\n  int get x => 1;\n\n  void f() {\n    g();\n  }\n}\n\n\n";
+        assert_eq!(reindent(code, 4), "class A {\n    int get x => 1;\n\n    void f() {\n        g();\n    }\n}\n\n\n");
+        assert_eq!(reindent(code, 2), code);
+
+        assert_eq!(finish_generated_code(code, 4), "class A {\n    int get x => 1;\n\n    void f() {\n        g();\n    }\n}\n");
+        assert_eq!(finish_generated_code("part of 'a.dart';", 2), "part of 'a.dart';\n");
+    }
+
+    #[test]
+    fn test_duplicate_class_names_across_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let model = "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n}\n";
+        fs::create_dir(temp_dir.path().join("admin")).unwrap();
+        fs::write(temp_dir.path().join("user.dart"), model).unwrap();
+        fs::write(temp_dir.path().join("admin/user.dart"), model).unwrap();
+        fs::write(temp_dir.path().join("post.dart"), model.replace("User", "Post")).unwrap();
+        let file_classes: HashMap<PathBuf, Vec<DartClass>> = find_dart_files(&temp_dir.path().to_string_lossy(), &GenOptions::default())
+            .into_iter()
+            .filter_map(|file_path| Some((file_path.clone(), parse_dart_file(&file_path)?)))
+            .collect();
+
+        let duplicates = find_duplicate_class_names(&file_classes);
+        assert_eq!(duplicates, vec![("User".to_string(), vec![temp_dir.path().join("admin/user.dart"), temp_dir.path().join("user.dart")])]);
+        // Only a warning by default
+        let input_path = temp_dir.path().to_string_lossy();
+        assert!(collect_annotation_outputs("@freezed", &input_path, &input_path, &GenOptions::default(), &mut GenStats::default()).is_ok());
+        match collect_annotation_outputs("@freezed", &input_path, &input_path, &GenOptions { strict: true, ..GenOptions::default() }, &mut GenStats::default()) {
+            Err(GenError::DuplicateClass { name, paths }) => {
+                assert_eq!(name, "User");
+                assert_eq!(paths.len(), 2);
             }
+            other => panic!("expected DuplicateClass, got {:?}", other),
         }
     }
-}
 
-fn generate_from_json(class_name: &str, _fields: &[DartField]) -> String {
-    let mut code = String::new();
-    code.push_str(&format!("  factory {}.fromJson(Map<String, dynamic> json) => _${}FromJson(json);\n", class_name, class_name));
-    code
+    #[test]
+    fn test_to_pascal_case() {
+        assert_eq!(to_pascal_case("in_progress"), "InProgress");
+        assert_eq!(to_pascal_case("inProgress"), "InProgress");
+        assert_eq!(to_pascal_case("HTTPError"), "HTTPError");
+        assert_eq!(to_pascal_case("case_2"), "Case2");
+        assert_eq!(to_pascal_case("not-found yet"), "NotFoundYet");
+        assert_eq!(to_pascal_case(""), "");
+    }
+
+    #[test]
+    fn test_parse_dart_content_with_doc_comments() {
+        let content = r#"
+@freezed
+/// A registered user.
+///
+/// Created on sign-up.
+class User with _$User {
+  const factory User({required String name}) = _User;
 }
 
-fn generate_to_json(class_name: &str, _fields: &[DartField]) -> String {
-    let mut code = String::new();
-    code.push_str(&format!("  Map<String, dynamic> toJson() => _${}ToJson(this);\n", class_name));
-    code
+@JsonSerializable() // legacy model
+
+/* kept for the v1 API */
+class Account {}
+
+@riverpod
+// Cached for the session
+String greeting(Ref ref) => 'hi';
+"#;
+        
+        let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
+        
+        let names: Vec<&str> = classes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["User", "Account", "greeting"]);
+    }
+
+    #[test]
+    fn test_parse_dart_content_merges_annotations() {
+        let content = r#"
+@freezed
+@JsonSerializable(explicitToJson: true)
+class User with _$User {
+  const factory User({required String name}) = _User;
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+@JsonSerializable()
+@immutable
+@Freezed(equal: false)
+class Team with _$Team {
+  const factory Team({required String name}) = _Team;
+}
+"#;
+        
+        let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
+        
+        assert_eq!(classes.len(), 2);
+        let user = classes.iter().find(|c| c.name == "User").unwrap();
+        assert_eq!(user.annotations, vec!["@freezed".to_string(), "@JsonSerializable(explicitToJson: true)".to_string()]);
+        let team = classes.iter().find(|c| c.name == "Team").unwrap();
+        assert_eq!(team.annotations, vec!["@Freezed(equal: false)".to_string(), "@JsonSerializable()".to_string()]);
+    }
 
     #[test]
-    fn test_find_dart_files() {
-        // Create a temporary directory for testing
-        let temp_dir = TempDir::new().unwrap();
-        let lib_dir = temp_dir.path().join("lib");
-        fs::create_dir_all(&lib_dir).unwrap();
+    fn test_parse_dart_content_keeps_annotation_arguments() {
+        let content = r#"
+@Freezed(unionKey: 'kind')
+class Shape with _$Shape {}
 
-        // Create a test Dart file
-        fs::write(lib_dir.join("test.dart"), "class Test {}").unwrap();
-        fs::write(lib_dir.join("test.txt"), "not a dart file").unwrap();
+@JsonSerializable(explicitToJson: true)
+class Order {}
 
-        let dart_files = find_dart_files(temp_dir.path().join("lib").to_str().unwrap());
+@Riverpod(keepAlive: true)
+class Session extends _$Session {}
+"#;
         
-        assert_eq!(dart_files.len(), 1);
-        assert!(dart_files[0].file_name().unwrap() == "test.dart");
+        let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
+        
+        let annotations_of = |name: &str| classes.iter().find(|c| c.name == name).unwrap().annotations.clone();
+        assert_eq!(annotations_of("Shape"), vec!["@Freezed(unionKey: 'kind')".to_string()]);
+        assert_eq!(annotations_of("Order"), vec!["@JsonSerializable(explicitToJson: true)".to_string()]);
+        assert_eq!(annotations_of("Session"), vec!["@Riverpod(keepAlive: true)".to_string()]);
+        assert_eq!(annotation_name(&annotations_of("Order")[0]), "@JsonSerializable");
     }
 
     #[test]
-    fn test_parse_dart_content() {
+    fn test_parse_dart_content_provider_annotations() {
         let content = r#"
-@freezed
-class User {
-  const factory User({required String name}) = _User;
+@StateNotifierProvider
+class CounterNotifier extends StateNotifier<int> {
+  CounterNotifier() : super(0);
+}
+
+@FutureProvider
+Future<User> fetchUser(Ref ref) async {
+  return User();
 }
 "#;
         
         let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
         
-        assert_eq!(classes.len(), 1);
-        assert_eq!(classes[0].name, "User");
-        assert!(classes[0].annotations.iter().any(|ann| ann.contains("@freezed")));
+        let counter = classes.iter().find(|c| c.name == "CounterNotifier").unwrap();
+        assert_eq!(counter.annotations, vec!["@StateNotifierProvider".to_string()]);
+        let fetch_user = classes.iter().find(|c| c.name == "fetchUser").unwrap();
+        assert_eq!(fetch_user.annotations, vec!["@FutureProvider".to_string()]);
+        
+        assert_eq!(extract_provider_value_type(content, "CounterNotifier", &ProviderType::StateNotifierProvider), "int");
+        assert_eq!(extract_provider_value_type(content, "fetchUser", &ProviderType::FutureProvider), "User");
+    }
+
+    #[test]
+    fn test_migrate_output_layout() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib = temp_dir.path().join("lib");
+        let models = lib.join("models");
+        fs::create_dir_all(&models).unwrap();
+        let source = models.join("user.dart");
+        fs::write(&source, "part 'user.freezed.dart';\npart 'user.g.dart';\n\n@freezed\nclass User with _$User {}\n").unwrap();
+        fs::write(models.join("user.freezed.dart"), "// GENERATED CODE\n\npart of 'user.dart';\n").unwrap();
+        fs::write(models.join("user.g.dart"), "// GENERATED CODE\n\npart of 'user.dart';\n").unwrap();
+        let input = lib.to_string_lossy().to_string();
+        let output = lib.join("gen").to_string_lossy().to_string();
+        
+        let stats = migrate_output_layout(&input, &output, OutputLayout::Sibling, OutputLayout::Mirror, &GenOptions::default()).unwrap();
+        assert_eq!(stats.files, 2);
+        assert!(!models.join("user.g.dart").exists());
+        let moved = fs::read_to_string(lib.join("gen/models/user.g.dart")).unwrap();
+        assert_eq!(moved, "// GENERATED CODE\n\npart of '../../models/user.dart';\n");
+        let source_content = fs::read_to_string(&source).unwrap();
+        assert!(source_content.starts_with("part '../gen/models/user.freezed.dart';\npart '../gen/models/user.g.dart';\n"));
+        
+        // And back again
+        migrate_output_layout(&input, &output, OutputLayout::Mirror, OutputLayout::Sibling, &GenOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(models.join("user.g.dart")).unwrap(), "// GENERATED CODE\n\npart of 'user.dart';\n");
+        assert!(fs::read_to_string(&source).unwrap().starts_with("part 'user.freezed.dart';\npart 'user.g.dart';\n"));
+        assert_eq!(migrate_output_layout(&input, &output, OutputLayout::Mirror, OutputLayout::Sibling, &GenOptions::default()).unwrap().files, 0);
+    }
+
+    #[test]
+    fn test_abstract_and_generated_classes_get_no_provider() {
+        let content = r#"
+@riverpod
+abstract class BaseNotifier extends _$BaseNotifier {}
+
+@riverpod
+class _$CounterNotifier extends AutoDisposeNotifier<int> {}
+
+@StateNotifierProvider
+abstract class BaseStateNotifier extends StateNotifier<int> {}
+
+@riverpod
+class Counter extends _$Counter {
+  @override
+  int build() => 0;
+}
+"#;
+        let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
+        let names: Vec<&str> = classes.iter().map(|class| class.name.as_str()).collect();
+        assert_eq!(names, ["Counter"]);
+    }
+
+    #[test]
+    fn test_expression_bodied_and_getter_riverpod_functions() {
+        let content = r#"
+@riverpod
+String greeting(Ref ref) => 'Hello';
+
+@riverpod
+Future<List<String>> names(Ref ref) async => ['a', 'b'];
+
+@riverpod
+int get count => 0;
+
+@Riverpod(keepAlive: true)
+Future<User?> get currentUser async => null;
+"#;
+        let classes = parse_dart_content(content, Path::new("test.dart")).unwrap();
+        let names: Vec<&str> = classes.iter().map(|class| class.name.as_str()).collect();
+        assert_eq!(names, ["greeting", "names", "count", "currentUser"]);
+        
+        let getters = extract_riverpod_getters(content, Path::new("test.dart"));
+        let getters: Vec<(&str, &str, bool)> = getters.iter().map(|g| (g.name.as_str(), g.return_type.as_str(), g.is_getter)).collect();
+        assert_eq!(getters, [("count", "int", true), ("currentUser", "Future<User?>", true)]);
+    }
+
+    #[test]
+    fn test_riverpod_getter_source_is_its_declaration() {
+        let source = "@riverpod\nint get count => 0;\n\n@riverpod\nString get name {\n  return 'a';\n}\n";
+        let getters = extract_riverpod_getters(source, Path::new("counter.dart"));
+        let sources: Vec<&str> = getters.iter().map(|getter| getter.source.as_str()).collect();
+        assert_eq!(sources, ["@riverpod\nint get count => 0;", "@riverpod\nString get name {\n  return 'a';\n}"]);
     }
 
     #[test]
@@ -1618,7 +2829,7 @@ Future<String> testFunction(TestFunctionRef ref) async {
         
         // This would normally call the actual generation function
         // For now, we'll test the path handling
-        let dart_files = find_dart_files(input_path);
+        let dart_files = find_dart_files(input_path, &GenOptions::default());
         assert_eq!(dart_files.len(), 1);
         assert!(dart_files[0].file_name().unwrap() == "test_provider.dart");
         
@@ -1653,6 +2864,346 @@ Future<String> testFunction(TestFunctionRef ref) async {
         assert!(result.g_dart_code.contains("// GENERATED CODE"));
     }
 
+    #[test]
+    fn test_check_generated_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let up_to_date = temp_dir.path().join("user.g.dart");
+        let stale = temp_dir.path().join("user.freezed.dart");
+        let missing = temp_dir.path().join("event.g.dart");
+        fs::write(&up_to_date, "same\n").unwrap();
+        fs::write(&stale, "old\n").unwrap();
+
+        let outputs = vec![(up_to_date.clone(), "same\n".to_string())];
+        assert_eq!(check_generated_outputs(&outputs, &GenOptions::default()), 0);
+
+        let outputs = vec![
+            (up_to_date, "same\n".to_string()),
+            (stale.clone(), "new\n".to_string()),
+            (missing.clone(), "new\n".to_string()),
+        ];
+        assert_eq!(check_generated_outputs(&outputs, &GenOptions::default()), 2);
+
+        // Nothing is written in check mode
+        assert_eq!(fs::read_to_string(&stale).unwrap(), "old\n");
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_run_code_generators_counts_each_file_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::write(lib_dir.join("user.dart"), "part 'user.freezed.dart';\npart 'user.g.dart';\n\n@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n\n  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);\n}\n").unwrap();
+        fs::write(lib_dir.join("counter.dart"), "part 'counter.g.dart';\n\n@riverpod\nint counter(CounterRef ref) {\n  return 0;\n}\n").unwrap();
+
+        let lib_path = lib_dir.to_string_lossy().to_string();
+        let stats = run_code_generators(&lib_path, &lib_path, false, true, true, true, true, &GenOptions::default()).unwrap();
+        // The json pass rewrites the freezed pass's files and the provider pass the riverpod pass's
+        assert_eq!(stats.models.files, 2);
+        assert_eq!(stats.providers.files, 1);
+        assert_eq!(stats.models.declarations, vec![(lib_dir.join("user.dart"), "User".to_string())]);
+        assert_eq!(stats.providers.declarations, vec![(lib_dir.join("counter.dart"), "counter".to_string())]);
+        let total = stats.total();
+        assert_eq!(total.files, 3);
+        assert_eq!(total.outputs.len(), 3);
+    }
+
+    #[test]
+    fn test_generation_does_not_dump_ast_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::write(lib_dir.join("counter.dart"), r#"
+part 'counter.g.dart';
+
+@riverpod
+int counter(CounterRef ref) {
+  return 0;
+}
+"#).unwrap();
+
+        let lib_path = lib_dir.to_string_lossy().to_string();
+        generate_provider_code_with_paths_and_clean(&lib_path, &lib_path, false, &GenOptions::default()).unwrap();
+
+        assert!(lib_dir.join("counter.g.dart").exists());
+        assert!(!temp_dir.path().join("debug_ast.txt").exists());
+        assert!(!lib_dir.join("debug_ast.txt").exists());
+
+        // Only the run given a dump path writes one
+        let dump_path = temp_dir.path().join("ast.txt");
+        generate_provider_code_with_paths_and_clean(&lib_path, &lib_path, false, &GenOptions { dump_ast: Some(dump_path.clone()), ..GenOptions::default() }).unwrap();
+        assert!(fs::read_to_string(&dump_path).unwrap().contains("=== Complete AST for"));
+    }
+
+    #[test]
+    fn test_broken_dart_file_does_not_stop_generation() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::write(lib_dir.join("broken.dart"), r#"
+part 'broken.freezed.dart';
+
+@freezed
+class Broken with _$Broken {
+  const factory Broken({required String id,
+
+@riverpod
+int broken(BrokenRef ref) {{{
+"#).unwrap();
+        fs::write(lib_dir.join("user.dart"), r#"
+part 'user.freezed.dart';
+part 'user.g.dart';
+
+@freezed
+class User with _$User {
+  const factory User({required String id}) = _User;
+}
+
+@riverpod
+int counter(CounterRef ref) {
+  return 0;
+}
+"#).unwrap();
+
+        let outputs = collect_generated_outputs(lib_dir.to_str().unwrap(), lib_dir.to_str().unwrap(), true, true, true, false, &GenOptions::default()).unwrap();
+        assert!(outputs.iter().any(|(path, code)| path.ends_with("user.freezed.dart") && code.contains("mixin _$User")));
+        assert!(outputs.iter().any(|(path, code)| path.ends_with("user.g.dart") && code.contains("counterProvider")));
+    }
+
+    #[test]
+    fn test_list_annotated_declarations() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::write(lib_dir.join("user.dart"), r#"
+part 'user.freezed.dart';
+
+@freezed
+class User with _$User {
+  const factory User({required String id, required String name}) = _User;
+}
+"#).unwrap();
+        fs::write(lib_dir.join("plain.dart"), "class Plain {}\n").unwrap();
+
+        let files = list_annotated_declarations(lib_dir.to_str().unwrap(), &GenOptions::default());
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path.ends_with("user.dart"));
+        let user = &files[0].declarations[0];
+        assert_eq!(user.name, "User");
+        assert_eq!(user.annotations, vec!["@freezed".to_string()]);
+        assert_eq!(user.field_count, 2);
+
+        let listing = format_declaration_listing(&files);
+        assert!(listing.contains("  User [@freezed] (2 fields)"));
+        let json = serde_json::to_string(&files).unwrap();
+        assert!(json.contains("\"name\":\"User\""));
+    }
+
+    #[test]
+    fn test_barrel_file_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let outputs = vec![
+            root.join("lib/models/user.freezed.dart"),
+            root.join("lib/models/user.g.dart"),
+            root.join("lib/gen/assets.gen.dart"),
+        ];
+        
+        let barrel = barrel_file_content(&root.join("lib/generated.dart"), &outputs);
+        assert!(barrel.contains("export 'models/user.freezed.dart';"));
+        assert!(barrel.contains("export 'models/user.g.dart';"));
+        assert!(barrel.contains("export 'gen/assets.gen.dart';"));
+        
+        let barrel = barrel_file_content(&root.join("lib/gen/all.dart"), &outputs);
+        assert!(barrel.contains("export '../models/user.g.dart';"));
+        assert!(barrel.contains("export 'assets.gen.dart';"));
+        assert_eq!(barrel.matches("export '").count(), outputs.len());
+    }
+
+    #[test]
+    fn test_part_directives_use_forward_slashes() {
+        assert_eq!(dart_uri_path("..\\models/user.dart"), "../models/user.dart");
+        assert_eq!(dart_uri_path("models\\user.freezed.dart"), "models/user.freezed.dart");
+
+        let lib = Path::new("lib");
+        let models_file = lib.join("models").join("user.dart");
+        let gen_dir = lib.join("gen");
+        let directives = [
+            relative_import(&gen_dir, &models_file),
+            super::super::freezed_gen::part_of_path(&models_file, &gen_dir),
+        ];
+        assert_eq!(directives[0], "../models/user.dart");
+        assert!(directives.iter().all(|directive| !directive.contains('\\')));
+    }
+
+    #[test]
+    fn test_write_failure_is_an_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::write(lib_dir.join("user.dart"), "part 'user.freezed.dart';\n\n@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n\n  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);\n}\n").unwrap();
+        // A directory where the output file should go makes the write fail
+        fs::create_dir_all(lib_dir.join("user.freezed.dart")).unwrap();
+
+        let input_path = lib_dir.to_str().unwrap();
+        let result = generate_freezed_with_paths_and_clean(input_path, input_path, false, &GenOptions::default());
+        match result {
+            Err(GenError::Io { path, .. }) => assert_eq!(path, lib_dir.join("user.freezed.dart")),
+            other => panic!("expected a write error, got {:?}", other),
+        }
+
+        fs::remove_dir(lib_dir.join("user.freezed.dart")).unwrap();
+        let stats = generate_freezed_with_paths_and_clean(input_path, input_path, false, &GenOptions::default()).unwrap();
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.outputs, vec![lib_dir.join("user.freezed.dart"), lib_dir.join("user.g.dart")]);
+    }
+
+    #[test]
+    fn test_run_dart_format() {
+        if Command::new("dart").arg("--version").output().is_err() {
+            eprintln!("dart is not installed, skipping");
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("user.g.dart");
+        fs::write(&file, "class User{final String id;const User(this.id);}\n").unwrap();
+        
+        assert!(run_dart_format(&[file.clone()]).unwrap());
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "class User {\n  final String id;\n  const User(this.id);\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_check_compares_formatted_outputs() {
+        if Command::new("dart").arg("--version").output().is_err() {
+            eprintln!("dart is not installed, skipping");
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("user.g.dart");
+        // What a --format run left on disk
+        fs::write(&file, "class User {\n  final String id;\n  const User(this.id);\n}\n").unwrap();
+
+        let outputs = vec![(file, "class User{final String id;const User(this.id);}\n".to_string())];
+        assert_eq!(check_generated_outputs(&outputs, &GenOptions { format: true, ..GenOptions::default() }), 0);
+        assert_eq!(check_generated_outputs(&outputs, &GenOptions::default()), 1);
+    }
+
+    #[test]
+    fn test_registered_generator_runs_for_custom_annotation() {
+        struct MapperGenerator;
+        impl DartGenerator for MapperGenerator {
+            fn annotation(&self) -> &str {
+                "@TestMapper"
+            }
+            fn generate(&self, class: &DartClass, _source: &str, _fields: &[DartField], _options: &GenOptions) -> GenerationResult {
+                GenerationResult {
+                    freezed_code: String::new(),
+                    g_dart_code: format!("extension {}Mapper on {} {{}}\n\n", class.name, class.name),
+                }
+            }
+        }
+        // Unregistered when the test ends, even if an assertion fails, so other tests don't run it
+        struct Registration;
+        impl Drop for Registration {
+            fn drop(&mut self) {
+                unregister_generator("@TestMapper");
+            }
+        }
+        register_generator(MapperGenerator);
+        let registration = Registration;
+        
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("dto.dart"), "part 'dto.g.dart';\n\n@TestMapper(target: 'User')\nclass UserDto {}\n").unwrap();
+        fs::write(temp_dir.path().join("plain.dart"), "class Plain {}\n").unwrap();
+        let input_path = temp_dir.path().to_str().unwrap();
+        
+        let outputs = collect_annotation_outputs("@JsonSerializable", input_path, input_path, &GenOptions::default(), &mut GenStats::default()).unwrap();
+        
+        let g_dart = outputs.iter().find(|(path, _)| path.ends_with("dto.g.dart")).map(|(_, code)| code).unwrap();
+        assert!(g_dart.contains("extension UserDtoMapper on UserDto {}"));
+        // The built-in generator doesn't handle the custom annotation
+        assert!(!g_dart.contains("_$UserDtoFromJson"));
+        assert!(!outputs.iter().any(|(path, _)| path.ends_with("plain.g.dart")));
+        
+        drop(registration);
+        assert!(!dart_generators().iter().any(|generator| generator.annotation() == "@TestMapper"));
+    }
+
+    #[test]
+    fn test_plain_json_serializable_gets_only_json_functions() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("order.dart"), r#"
+part 'order.g.dart';
+
+@JsonSerializable()
+class Order {
+  final String id;
+  final int? quantity;
+
+  const Order({required this.id, this.quantity});
+
+  factory Order.fromJson(Map<String, dynamic> json) => _$OrderFromJson(json);
+  Map<String, dynamic> toJson() => _$OrderToJson(this);
+}
+"#).unwrap();
+        let input_path = temp_dir.path().to_str().unwrap();
+        
+        let outputs = collect_annotation_outputs("@JsonSerializable", input_path, input_path, &GenOptions::default(), &mut GenStats::default()).unwrap();
+        
+        assert!(!outputs.iter().any(|(path, _)| path.ends_with("order.freezed.dart")));
+        let g_dart = outputs.iter().find(|(path, _)| path.ends_with("order.g.dart")).map(|(_, code)| code).unwrap();
+        assert!(g_dart.contains("Order _$OrderFromJson(\n  Map<String, dynamic> json,\n) => Order(\n  id: json['id'] as String,\n  quantity: (json['quantity'] as num?)?.toInt(),\n);"));
+        assert!(g_dart.contains("Map<String, dynamic> _$OrderToJson(\n  Order instance,\n) => <String, dynamic>{\n  'id': instance.id,\n  'quantity': instance.quantity,\n};"));
+        let declarations = g_dart.lines().filter(|line| !line.starts_with(' ') && line.contains("_$")).count();
+        assert_eq!(declarations, 2);
+        assert!(!g_dart.contains("Impl") && !g_dart.contains("mixin") && !g_dart.contains("copyWith"));
+    }
+
+    #[test]
+    fn test_exclude_globs_skip_source_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("models/legacy")).unwrap();
+        fs::create_dir_all(root.join("legacy")).unwrap();
+        for file in ["user.dart", "models/order.dart", "models/legacy/old_order.dart", "legacy/old_user.dart"] {
+            fs::write(root.join(file), "@freezed\nclass Model with _$Model {}\n").unwrap();
+        }
+        let options = GenOptions { exclude: vec!["**/legacy/*.dart".to_string()], ..GenOptions::default() };
+        let kept = find_dart_files(root.to_str().unwrap(), &options);
+        assert_eq!(kept, [root.join("models/order.dart"), root.join("user.dart")]);
+        
+        // Nothing is excluded without patterns
+        assert_eq!(find_dart_files(root.to_str().unwrap(), &GenOptions::default()).len(), 4);
+    }
+
+    #[test]
+    fn test_find_orphaned_outputs() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(&lib_dir).unwrap();
+        fs::write(lib_dir.join("user.dart"), "@freezed\nclass User with _$User {}\n").unwrap();
+        fs::write(lib_dir.join("user.freezed.dart"), "// generated").unwrap();
+        fs::write(lib_dir.join("user.g.dart"), "// generated").unwrap();
+        // Source deleted
+        fs::write(lib_dir.join("post.g.dart"), "// generated").unwrap();
+        // Source no longer annotated
+        fs::write(lib_dir.join("plain.dart"), "class Plain {}\n").unwrap();
+        fs::write(lib_dir.join("plain.freezed.dart"), "// generated").unwrap();
+
+        let orphans = find_orphaned_outputs(lib_dir.to_str().unwrap());
+        assert_eq!(orphans, vec![lib_dir.join("plain.freezed.dart"), lib_dir.join("post.g.dart")]);
+
+        handle_orphaned_outputs(lib_dir.to_str().unwrap(), false, &GenOptions::default());
+        assert!(lib_dir.join("post.g.dart").exists());
+        handle_orphaned_outputs(lib_dir.to_str().unwrap(), true, &GenOptions::default());
+        assert!(!lib_dir.join("post.g.dart").exists());
+        assert!(!lib_dir.join("plain.freezed.dart").exists());
+        assert!(lib_dir.join("user.g.dart").exists());
+    }
+
     #[test]
     fn test_find_dart_files_recursive() {
         let temp_dir = TempDir::new().unwrap();
@@ -1668,7 +3219,7 @@ Future<String> testFunction(TestFunctionRef ref) async {
         fs::write(providers_dir.join("auth_provider.dart"), "class AuthProvider {}").unwrap();
         fs::write(lib_dir.join("main.dart"), "void main() {}").unwrap();
         
-        let dart_files = find_dart_files(temp_dir.path().join("lib").to_str().unwrap());
+        let dart_files = find_dart_files(temp_dir.path().join("lib").to_str().unwrap(), &GenOptions::default());
         
         // Should find all 3 Dart files recursively
         assert_eq!(dart_files.len(), 3);
@@ -1681,4 +3232,83 @@ Future<String> testFunction(TestFunctionRef ref) async {
         assert!(file_names.contains(&"auth_provider.dart".to_string()));
         assert!(file_names.contains(&"main.dart".to_string()));
     }
+
+    #[test]
+    fn test_find_dart_files_skips_ignored_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in [".dart_tool", "build", "ios/.symlinks", "lib/legacy", "lib/.hidden", "lib/features/build"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("lib/user.dart"), "class User {}").unwrap();
+        fs::write(root.join("lib/features/build/step.dart"), "class Step {}").unwrap();
+        fs::write(root.join(".dart_tool/foo.dart"), "class Foo {}").unwrap();
+        fs::write(root.join("build/bar.dart"), "class Bar {}").unwrap();
+        fs::write(root.join("ios/.symlinks/plugin.dart"), "class Plugin {}").unwrap();
+        fs::write(root.join("lib/legacy/old.dart"), "class Old {}").unwrap();
+        fs::write(root.join("lib/.hidden/secret.dart"), "class Secret {}").unwrap();
+        fs::write(root.join(".gitignore"), "lib/legacy/\n").unwrap();
+        
+        let dart_files = find_dart_files(root.to_str().unwrap(), &GenOptions::default());
+        
+        // Only the top-level build/ is skipped; hidden files are scanned
+        assert_eq!(dart_files, vec![root.join("lib/.hidden/secret.dart"), root.join("lib/features/build/step.dart"), root.join("lib/user.dart")]);
+        let dart_files = find_dart_files(root.to_str().unwrap(), &GenOptions { include_hidden: true, ..GenOptions::default() });
+        assert_eq!(dart_files.len(), 6);
+    }
+
+    #[test]
+    fn test_part_directive_follows_directive_order() {
+        let source = "// Copyright\n\nimport 'package:freezed_annotation/freezed_annotation.dart';\nimport 'package:app/colors.dart'\n    show Palette;\n\nexport 'address.dart';\n\npart 'user.freezed.dart';\n\n/// A user\n@freezed\nclass User with _$User {}\n";
+        assert_eq!(
+            insert_part_directive(source, "part 'user.g.dart';"),
+            "// Copyright\n\nimport 'package:freezed_annotation/freezed_annotation.dart';\nimport 'package:app/colors.dart'\n    show Palette;\n\nexport 'address.dart';\n\npart 'user.freezed.dart';\npart 'user.g.dart';\n\n/// A user\n@freezed\nclass User with _$User {}\n"
+        );
+
+        // Without parts the new one starts its own group after the exports
+        let source = "import 'a.dart';\nexport 'b.dart';\n@freezed\nclass User with _$User {}\n";
+        assert_eq!(
+            insert_part_directive(source, "part 'user.freezed.dart';"),
+            "import 'a.dart';\nexport 'b.dart';\n\npart 'user.freezed.dart';\n\n@freezed\nclass User with _$User {}\n"
+        );
+
+        // Without any directives it goes before the first declaration, below a header comment
+        let source = "// header\n\n@freezed\nclass User with _$User {}\n";
+        assert_eq!(insert_part_directive(source, "part 'user.freezed.dart';"), "// header\n\npart 'user.freezed.dart';\n\n@freezed\nclass User with _$User {}\n");
+
+        let temp_dir = TempDir::new().unwrap();
+        let model_path = temp_dir.path().join("user.dart");
+        fs::write(&model_path, "import 'a.dart';\n\npart 'user.freezed.dart';\n\nclass User {}\n").unwrap();
+        let output = temp_dir.path().join("user.g.dart");
+        add_part_directive(&output, "part of 'user.dart';\n").unwrap();
+        add_part_directive(&temp_dir.path().join("user.freezed.dart"), "part of 'user.dart';\n").unwrap();
+        assert_eq!(fs::read_to_string(&model_path).unwrap(), "import 'a.dart';\n\npart 'user.freezed.dart';\npart 'user.g.dart';\n\nclass User {}\n");
+
+        // A double-quoted part counts as present
+        let source = "import \"a.dart\";\n\npart \"user.freezed.dart\";\npart \"user.g.dart\";\n\nclass User {}\n";
+        fs::write(&model_path, source).unwrap();
+        add_part_directive(&output, "part of 'user.dart';\n").unwrap();
+        add_part_directive(&temp_dir.path().join("user.freezed.dart"), "part of 'user.dart';\n").unwrap();
+        assert_eq!(fs::read_to_string(&model_path).unwrap(), source);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_cycle_does_not_loop_generation() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        fs::create_dir_all(lib_dir.join("models")).unwrap();
+        fs::write(lib_dir.join("models/user.dart"), "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n\n  factory User.fromJson(Map<String, dynamic> json) => _$UserFromJson(json);\n}\n").unwrap();
+        // lib/models/loop -> lib
+        std::os::unix::fs::symlink(&lib_dir, lib_dir.join("models/loop")).unwrap();
+
+        let input_path = lib_dir.to_string_lossy().to_string();
+        assert_eq!(find_dart_files(&input_path, &GenOptions::default()), vec![lib_dir.join("models/user.dart")]);
+        let outputs = collect_generated_outputs(&input_path, &input_path, true, true, false, false, &GenOptions::default()).unwrap();
+        // The freezed and json passes each produce the same two files
+        let mut paths: Vec<_> = outputs.iter().map(|(path, _)| path.clone()).collect();
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths, vec![lib_dir.join("models/user.freezed.dart"), lib_dir.join("models/user.g.dart")]);
+    }
 } 
\ No newline at end of file
@@ -1,4 +1,7 @@
 pub mod generate;
 pub mod assets;
 pub mod provider_gen;
-pub mod freezed_gen; 
\ No newline at end of file
+pub mod freezed_gen;
+
+/// Comment emitted in every generated file to identify the tool version that produced it
+pub const GENERATOR_SIGNATURE: &str = concat!("// SuperFastGen v", env!("CARGO_PKG_VERSION"));
@@ -1,11 +1,17 @@
 // Provider code generation logic for Riverpod
 
 use std::path::{Path, PathBuf};
+use log::debug;
+use sha1::{Digest, Sha1};
+use super::freezed_gen::{annotation_name, DartClass, GenerationResult};
+use super::generate::{convert_dart_class_to_provider_class, dart_uri_path, DartGenerator, GenOptions};
 
 #[derive(Clone, Debug)]
 pub struct ProviderClass {
     pub name: String,
     pub return_type: String,
+    pub provider_type: ProviderType,
+    pub auto_dispose: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -14,14 +20,24 @@ pub struct DartField {
     pub ty: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ProviderType {
     Provider,
     FutureProvider,
     StreamProvider,
+    StateProvider,
+    StateNotifierProvider,
+    NotifierProvider,
     AsyncNotifierProvider,
 }
 
+impl ProviderType {
+    /// Whether the provider is built from a notifier class rather than a function
+    pub fn is_class_based(&self) -> bool {
+        matches!(self, ProviderType::StateNotifierProvider | ProviderType::NotifierProvider | ProviderType::AsyncNotifierProvider)
+    }
+}
+
 pub struct ProviderGenerationResult {
     pub provider_code: String,
     pub part_directive: String,
@@ -34,6 +50,41 @@ pub fn generate_provider_code(class: &ProviderClass) -> String {
 fn generate_single_provider(class: &ProviderClass) -> String {
     let mut code = String::new();
     let provider_name = format!("{}Provider", to_lower_camel_case(&class.name.replace("Notifier", "")));
+    let auto_dispose = if class.auto_dispose { ".autoDispose" } else { "" };
+    
+    match class.provider_type {
+        ProviderType::Provider => {}
+        ProviderType::FutureProvider => {
+            code.push_str(&format!(
+                "final {} = FutureProvider{}<{}>((ref) async {{\n  return await {}(ref);\n}});\n\n",
+                provider_name, auto_dispose, class.return_type, class.name
+            ));
+            return code;
+        }
+        ProviderType::StreamProvider | ProviderType::StateProvider => {
+            let provider_kind = if class.provider_type == ProviderType::StreamProvider { "StreamProvider" } else { "StateProvider" };
+            code.push_str(&format!(
+                "final {} = {}{}<{}>((ref) {{\n  return {}(ref);\n}});\n\n",
+                provider_name, provider_kind, auto_dispose, class.return_type, class.name
+            ));
+            return code;
+        }
+        ProviderType::StateNotifierProvider => {
+            code.push_str(&format!(
+                "final {} = StateNotifierProvider{}<{}, {}>((ref) {{\n  return {}();\n}});\n\n",
+                provider_name, auto_dispose, class.name, class.return_type, class.name
+            ));
+            return code;
+        }
+        ProviderType::NotifierProvider | ProviderType::AsyncNotifierProvider => {
+            let provider_kind = if class.provider_type == ProviderType::NotifierProvider { "NotifierProvider" } else { "AsyncNotifierProvider" };
+            code.push_str(&format!(
+                "final {} = {}{}<{}, {}>(() {{\n  return {}();\n}});\n\n",
+                provider_name, provider_kind, auto_dispose, class.name, class.return_type, class.name
+            ));
+            return code;
+        }
+    }
     
     // Skip @riverpod classes - let the official generator handle them
     if class.name.ends_with("Notifier") {
@@ -68,14 +119,38 @@ fn generate_single_provider(class: &ProviderClass) -> String {
 }
 
 pub fn generate_provider_file(provider_classes: &[ProviderClass], output_path: &Path) -> Result<(), std::io::Error> {
-    generate_enhanced_provider_file(provider_classes, &[], output_path)
+    generate_enhanced_provider_file(provider_classes, &[], output_path, Path::new(""))
 }
 
 pub fn generate_enhanced_provider_file(
     provider_classes: &[ProviderClass], 
     provider_functions: &[super::freezed_gen::DartFunction], 
-    output_path: &Path
+    output_path: &Path,
+    project_root: &Path
 ) -> Result<(), std::io::Error> {
+    let code = generate_enhanced_provider_code(provider_classes, provider_functions, output_path, project_root);
+    std::fs::write(output_path, code)
+}
+
+pub fn generate_enhanced_provider_code(
+    provider_classes: &[ProviderClass], 
+    provider_functions: &[super::freezed_gen::DartFunction], 
+    output_path: &Path,
+    project_root: &Path
+) -> String {
+    let class_code: String = provider_classes.iter().map(generate_provider_class_code).collect();
+    generate_provider_file_code(&class_code, provider_functions, output_path, project_root)
+}
+
+/// The provider `.g.dart` for one source file: the header, the hash helper, the class providers'
+/// code (see generate_provider_class_code) and the providers for the @riverpod functions.
+/// The functions' source hashes use their path relative to project_root.
+pub fn generate_provider_file_code(
+    class_code: &str,
+    provider_functions: &[super::freezed_gen::DartFunction],
+    output_path: &Path,
+    project_root: &Path
+) -> String {
     let mut code = String::new();
     // Extract the file stem for the part directive
     let file_stem = output_path.file_stem().unwrap_or_default().to_string_lossy();
@@ -87,45 +162,12 @@ pub fn generate_enhanced_provider_file(
     };
     // Header - part files cannot have imports, so we only include the part directive
     code.push_str("// GENERATED CODE - DO NOT MODIFY BY HAND\n");
+    code.push_str(&format!("{}\n", super::GENERATOR_SIGNATURE));
     code.push_str(&format!("part of '{}.dart';\n\n", part_of));
     code.push_str("// **************************************************************************\n");
     code.push_str("// RiverpodGenerator\n");
     code.push_str("// **************************************************************************\n\n");
     
-    // Generate base classes for @riverpod classes
-    for class in provider_classes {
-        if class.name.ends_with("Notifier") {
-            // Generate the base class like _$AuthNotifier
-            let base_class_name = format!("_${}", class.name);
-            let return_type = if class.return_type == "String" {
-                // Try to extract actual return type from class name
-                if class.name.contains("Auth") {
-                    "AuthState".to_string()
-                } else if class.name.contains("Counter") {
-                    "int".to_string()
-                } else {
-                    "dynamic".to_string()
-                }
-            } else {
-                class.return_type.clone()
-            };
-            
-            // For Notifier classes, the build method should return the actual type, not Future<type>
-            let build_return_type = if class.name.contains("Counter") {
-                "int".to_string()
-            } else if class.name.contains("Auth") {
-                "AuthState".to_string()
-            } else {
-                return_type.clone()
-            };
-            
-            code.push_str(&format!("abstract class {} extends AsyncNotifier<{}> {{\n", base_class_name, return_type));
-            code.push_str(&format!("  @override\n"));
-            code.push_str(&format!("  Future<{}> build();\n", build_return_type));
-            code.push_str("}\n\n");
-        }
-    }
-    
     // Copied from Dart SDK (hash helper)
     code.push_str("/// Copied from Dart SDK\n");
     code.push_str("class _SystemHash {\n");
@@ -146,13 +188,7 @@ pub fn generate_enhanced_provider_file(
     code.push_str("  }\n");
     code.push_str("}\n\n");
     
-    // Generate providers for classes
-    for class in provider_classes {
-        let provider_code = generate_single_provider(class);
-        if !provider_code.is_empty() {
-            code.push_str(&provider_code);
-        }
-    }
+    code.push_str(class_code);
     
     // Generate providers for @riverpod functions
     let mut processed_functions = std::collections::HashSet::new();
@@ -164,36 +200,117 @@ pub fn generate_enhanced_provider_file(
         let unique_key = format!("{}({})", function.name, param_signature.join(","));
         
         if processed_functions.insert(unique_key.clone()) {
-            eprintln!("[DEBUG] Generating provider for function: {} with signature: {}", function.name, unique_key);
-            let function_code = generate_riverpod_function_provider(function);
+            debug!("Generating provider for function: {} with signature: {}", function.name, unique_key);
+            let function_code = generate_riverpod_function_provider(function, project_root);
             if !function_code.is_empty() {
                 code.push_str(&function_code);
             }
         } else {
-            eprintln!("[DEBUG] Skipping duplicate function: {}", unique_key);
+            debug!("Skipping duplicate function: {}", unique_key);
         }
     }
     
     // Add the standard footer (only once per file)
     code.push_str("// ignore_for_file: type=lint\n");
     code.push_str("// ignore_for_file: subtype_of_sealed_class, invalid_use_of_internal_member, invalid_use_of_visible_for_testing_member, deprecated_member_use_from_same_package\n");
-    // Write the generated code to the output file
-    std::fs::write(output_path, code)?;
-    Ok(())
+    code
 }
 
-pub fn extract_provider_annotations(annotations: &[String]) -> Vec<ProviderType> {
-    let mut provider_types = Vec::new();
-    
-    for annotation in annotations {
-        let annotation = annotation.trim();
+/// The code for one provider class: the notifier base class it extends (if any) and its provider
+pub fn generate_provider_class_code(class: &ProviderClass) -> String {
+    let mut code = String::new();
+    if class.provider_type == ProviderType::Provider && class.name.ends_with("Notifier") {
+        // Generate the base class like _$AuthNotifier
+        let base_class_name = format!("_${}", class.name);
+        let return_type = if class.return_type == "String" {
+            // Try to extract actual return type from class name
+            if class.name.contains("Auth") {
+                "AuthState".to_string()
+            } else if class.name.contains("Counter") {
+                "int".to_string()
+            } else {
+                "dynamic".to_string()
+            }
+        } else {
+            class.return_type.clone()
+        };
         
-        if annotation.contains("@riverpod") {
-            provider_types.push(ProviderType::Provider);
+        // For Notifier classes, the build method should return the actual type, not Future<type>
+        let build_return_type = if class.name.contains("Counter") {
+            "int".to_string()
+        } else if class.name.contains("Auth") {
+            "AuthState".to_string()
+        } else {
+            return_type.clone()
+        };
+        
+        code.push_str(&format!("abstract class {} extends AsyncNotifier<{}> {{\n", base_class_name, return_type));
+        code.push_str(&format!("  @override\n"));
+        code.push_str(&format!("  Future<{}> build();\n", build_return_type));
+        code.push_str("}\n\n");
+    }
+    code.push_str(&generate_single_provider(class));
+    code
+}
+
+/// The built-in generator for `@riverpod` classes and the `@...Provider` annotations. Its code goes
+/// into the provider `.g.dart`, which wraps it with the per-file parts (generate_provider_file_code).
+pub struct RiverpodGenerator;
+
+impl DartGenerator for RiverpodGenerator {
+    fn annotation(&self) -> &str {
+        "@riverpod"
+    }
+
+    fn generate(&self, class: &DartClass, source: &str, _fields: &[super::freezed_gen::DartField], _options: &GenOptions) -> GenerationResult {
+        GenerationResult {
+            freezed_code: String::new(),
+            g_dart_code: convert_dart_class_to_provider_class(class, source).map(|provider_class| generate_provider_class_code(&provider_class)).unwrap_or_default(),
         }
     }
+
+    fn matches(&self, class: &DartClass) -> bool {
+        !extract_provider_annotations(&class.annotations).is_empty()
+    }
+}
+
+pub fn extract_provider_annotations(annotations: &[String]) -> Vec<ProviderType> {
+    annotations.iter()
+        .filter_map(|annotation| provider_type_from_annotation(annotation))
+        .collect()
+}
+
+/// Map a provider annotation (e.g. `@AutoDisposeFutureProvider`) to its provider kind
+pub fn provider_type_from_annotation(annotation: &str) -> Option<ProviderType> {
+    if is_riverpod_annotation(annotation) {
+        return Some(ProviderType::Provider);
+    }
+    let annotation = annotation_name(annotation);
     
-    provider_types
+    let name = annotation.strip_prefix('@')?;
+    let name = name.strip_prefix("AutoDispose").unwrap_or(name);
+    match name {
+        "Provider" => Some(ProviderType::Provider),
+        "FutureProvider" => Some(ProviderType::FutureProvider),
+        "StreamProvider" => Some(ProviderType::StreamProvider),
+        "StateProvider" => Some(ProviderType::StateProvider),
+        "StateNotifierProvider" => Some(ProviderType::StateNotifierProvider),
+        "NotifierProvider" => Some(ProviderType::NotifierProvider),
+        "AsyncNotifierProvider" => Some(ProviderType::AsyncNotifierProvider),
+        _ => None,
+    }
+}
+
+/// Whether the annotation is `@riverpod` or `@Riverpod(...)`
+pub fn is_riverpod_annotation(annotation: &str) -> bool {
+    matches!(annotation_name(annotation), "@riverpod" | "@Riverpod")
+}
+
+/// Whether any of the annotations is an `@AutoDispose...Provider` annotation
+pub fn is_auto_dispose_annotation(annotations: &[String]) -> bool {
+    annotations.iter().any(|annotation| {
+        annotation.trim().starts_with("@AutoDispose") && provider_type_from_annotation(annotation).is_some()
+    })
 }
 
 pub fn get_provider_output_paths(file_path: &Path) -> (PathBuf, PathBuf) {
@@ -206,7 +323,14 @@ pub fn get_provider_output_paths(file_path: &Path) -> (PathBuf, PathBuf) {
     (provider_file, g_dart_file)
 }
 
-fn generate_riverpod_function_provider(function: &super::freezed_gen::DartFunction) -> String {
+/// Whether a function parameter is the provider's ref (`Ref`, `AutoDisposeRef`, generated `FooRef`, ...),
+/// recognized by type wherever it appears in the parameter list
+pub(crate) fn is_ref_parameter(param: &super::freezed_gen::DartField) -> bool {
+    let ty = param.ty.split('<').next().unwrap_or_default().trim();
+    ty.ends_with("Ref") && ty.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+fn generate_riverpod_function_provider(function: &super::freezed_gen::DartFunction, project_root: &Path) -> String {
     let mut code = String::new();
     
     // Determine provider type based on return type
@@ -214,78 +338,87 @@ fn generate_riverpod_function_provider(function: &super::freezed_gen::DartFuncti
     let provider_name = format!("{}Provider", to_lower_camel_case(&function.name));
     
     // Extract the actual return type (remove Future<>, Stream<>, etc.)
-    let actual_return_type = if return_type.starts_with("Stream<") {
-        return_type.strip_prefix("Stream<").unwrap_or(return_type).trim_end_matches('>').to_string()
-    } else if return_type.starts_with("Future<") {
-        return_type.strip_prefix("Future<").unwrap_or(return_type).trim_end_matches('>').to_string()
+    let actual_return_type = return_type.strip_prefix("Stream<")
+        .or_else(|| return_type.strip_prefix("Future<"))
+        .and_then(|inner| inner.strip_suffix('>'))
+        .unwrap_or(return_type)
+        .to_string();
+    
+    // Every parameter other than the ref is a family argument, in declaration order.
+    // The call passes the ref where the function declares it (or not at all when it has none).
+    let family_params: Vec<&super::freezed_gen::DartField> = function.parameters.iter()
+        .filter(|p| !is_ref_parameter(p))
+        .collect();
+    let call_args: Vec<&str> = function.parameters.iter()
+        .map(|p| if is_ref_parameter(p) { "ref" } else { p.name.as_str() })
+        .collect();
+    let call = if function.is_getter {
+        function.name.clone()
     } else {
-        return_type.clone()
+        format!("{}({})", function.name, call_args.join(", "))
     };
+    let param_types: Vec<String> = family_params.iter().map(|p| p.ty.clone()).collect();
+    let param_names: Vec<String> = family_params.iter().map(|p| p.name.clone()).collect();
     
+    // Like riverpod_generator, the provider reports the hash of its source so hot reload can tell
+    // whether it changed (the family constructors take no hash)
+    let source_hash = if family_params.is_empty() {
+        let hash_function = format!("_${}Hash", to_lower_camel_case(&function.name));
+        code.push_str(&format!("String {}() => r'{}';\n\n", hash_function, provider_source_hash(function, project_root)));
+        format!(", debugGetCreateSourceHash: const bool.fromEnvironment('dart.vm.product') ? null : {}", hash_function)
+    } else {
+        String::new()
+    };
     if return_type.starts_with("Stream<") {
         // StreamProvider
         code.push_str(&format!(
-            "final {} = StreamProvider<{}>((ref) {{\n  return {}(ref);\n}});\n\n",
+            "final {} = StreamProvider<{}>((ref) {{\n  return {};\n}}{});\n\n",
             provider_name,
             actual_return_type,
-            function.name
+            call,
+            source_hash
         ));
     } else if return_type.starts_with("Future<") {
         // Check if function has parameters (family provider)
-        if function.parameters.len() > 1 {
-            // Family provider - first parameter is usually the ref, others are parameters
-            let param_types: Vec<String> = function.parameters[1..].iter()
-                .map(|p| p.ty.clone())
-                .collect();
-            let param_names: Vec<String> = function.parameters[1..].iter()
-                .map(|p| p.name.clone())
-                .collect();
-            
+        if !family_params.is_empty() {
             code.push_str(&format!(
-                "final {} = FutureProvider.family<{}, {}>((ref, {}) async {{\n  return await {}(ref, {});\n}});\n\n",
+                "final {} = FutureProvider.family<{}, {}>((ref, {}) async {{\n  return await {};\n}});\n\n",
                 provider_name,
                 actual_return_type,
                 param_types.join(", "),
                 param_names.join(", "),
-                function.name,
-                param_names.join(", ")
+                call
             ));
         } else {
             // Simple FutureProvider
             code.push_str(&format!(
-                "final {} = FutureProvider<{}>((ref) async {{\n  return await {}(ref);\n}});\n\n",
+                "final {} = FutureProvider<{}>((ref) async {{\n  return await {};\n}}{});\n\n",
                 provider_name,
                 actual_return_type,
-                function.name
+                call,
+                source_hash
             ));
         }
     } else {
         // Regular Provider
-        if function.parameters.len() > 1 {
+        if !family_params.is_empty() {
             // Family provider
-            let param_types: Vec<String> = function.parameters[1..].iter()
-                .map(|p| p.ty.clone())
-                .collect();
-            let param_names: Vec<String> = function.parameters[1..].iter()
-                .map(|p| p.name.clone())
-                .collect();
-            
             code.push_str(&format!(
-                "final {} = Provider.family<{}, {}>((ref, {}) {{\n  return {}(ref, {});\n}});\n\n",
+                "final {} = Provider.family<{}, {}>((ref, {}) {{\n  return {};\n}});\n\n",
                 provider_name,
                 actual_return_type,
                 param_types.join(", "),
                 param_names.join(", "),
-                function.name,
-                param_names.join(", ")
+                call
             ));
         } else {
             // Simple Provider
             code.push_str(&format!(
-                "final {} = Provider<{}>((ref) {{\n  return {}(ref);\n}});\n\n",
+                "final {} = Provider<{}>((ref) {{\n  return {};\n}}{});\n\n",
                 provider_name,
                 actual_return_type,
-                function.name
+                call,
+                source_hash
             ));
         }
     }
@@ -293,6 +426,18 @@ fn generate_riverpod_function_provider(function: &super::freezed_gen::DartFuncti
     code
 }
 
+/// Hash of the function's path relative to the project root and its source, so the same code
+/// hashes the same on every machine and the hash only changes when the function does
+fn provider_source_hash(function: &super::freezed_gen::DartFunction, project_root: &Path) -> String {
+    let relative_path = function.file_path.strip_prefix(project_root).unwrap_or(&function.file_path);
+    
+    let mut hasher = Sha1::new();
+    hasher.update(dart_uri_path(&relative_path.to_string_lossy()).as_bytes());
+    hasher.update(b"\n");
+    hasher.update(function.source.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
 fn to_lower_camel_case(s: &str) -> String {
     if s.is_empty() {
         return String::new();
@@ -325,6 +470,7 @@ fn to_lower_camel_case(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::freezed_gen::{DartField, DartFunction};
     
     #[test]
     fn test_to_lower_camel_case() {
@@ -338,4 +484,159 @@ mod tests {
         let result = extract_provider_annotations(&annotations);
         assert_eq!(result.len(), 1);
     }
+
+    #[test]
+    fn test_extract_provider_annotations_by_kind() {
+        let test_cases = vec![
+            ("@riverpod", ProviderType::Provider),
+            ("@Provider", ProviderType::Provider),
+            ("@FutureProvider", ProviderType::FutureProvider),
+            ("@AutoDisposeFutureProvider", ProviderType::FutureProvider),
+            ("@StreamProvider", ProviderType::StreamProvider),
+            ("@AutoDisposeStreamProvider", ProviderType::StreamProvider),
+            ("@StateProvider", ProviderType::StateProvider),
+            ("@StateNotifierProvider", ProviderType::StateNotifierProvider),
+            ("@AutoDisposeStateNotifierProvider", ProviderType::StateNotifierProvider),
+            ("@NotifierProvider", ProviderType::NotifierProvider),
+            ("@AsyncNotifierProvider", ProviderType::AsyncNotifierProvider),
+        ];
+        
+        for (annotation, expected) in test_cases {
+            let result = extract_provider_annotations(&[annotation.to_string()]);
+            assert_eq!(result, vec![expected], "annotation {}", annotation);
+        }
+        assert!(extract_provider_annotations(&["@freezed".to_string()]).is_empty());
+        assert_eq!(
+            extract_provider_annotations(&["@Riverpod(keepAlive: true)".to_string()]),
+            vec![ProviderType::Provider]
+        );
+        assert!(is_auto_dispose_annotation(&["@AutoDisposeStreamProvider".to_string()]));
+        assert!(!is_auto_dispose_annotation(&["@StreamProvider".to_string()]));
+    }
+
+    #[test]
+    fn test_generate_provider_shape_by_kind() {
+        let provider = |name: &str, return_type: &str, provider_type: ProviderType, auto_dispose: bool| ProviderClass {
+            name: name.to_string(),
+            return_type: return_type.to_string(),
+            provider_type,
+            auto_dispose,
+        };
+        
+        let code = generate_provider_code(&provider("fetchUser", "User", ProviderType::FutureProvider, false));
+        assert!(code.contains("final fetchUserProvider = FutureProvider<User>((ref) async {"));
+        
+        let code = generate_provider_code(&provider("ticks", "int", ProviderType::StreamProvider, true));
+        assert!(code.contains("final ticksProvider = StreamProvider.autoDispose<int>((ref) {"));
+        
+        let code = generate_provider_code(&provider("CounterNotifier", "int", ProviderType::StateNotifierProvider, false));
+        assert!(code.contains("final counterProvider = StateNotifierProvider<CounterNotifier, int>((ref) {"));
+        assert!(code.contains("return CounterNotifier();"));
+        
+        let code = generate_provider_code(&provider("TodoNotifier", "List<Todo>", ProviderType::NotifierProvider, false));
+        assert!(code.contains("final todoProvider = NotifierProvider<TodoNotifier, List<Todo>>(() {"));
+    }
+
+    #[test]
+    fn test_function_provider_family_params_skip_ref_by_type() {
+        let param = |ty: &str, name: &str| DartField {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            is_named: false,
+            has_default: false,
+            default_value: None,
+            omit_if_null: false,
+            converter: None,
+        };
+        let function = |parameters| DartFunction {
+            name: "fetchUser".to_string(),
+            return_type: "Future<User>".to_string(),
+            parameters,
+            annotations: vec!["@riverpod".to_string()],
+            file_path: PathBuf::from("user.dart"),
+            is_getter: false,
+            source: String::new(),
+        };
+        let family = "final fetchUserProvider = FutureProvider.family<User, int, String>((ref, id, locale) async {\n";
+        
+        let ref_first = generate_riverpod_function_provider(&function(vec![param("FetchUserRef", "ref"), param("int", "id"), param("String", "locale")]), Path::new(""));
+        assert!(ref_first.contains(family));
+        assert!(ref_first.contains("return await fetchUser(ref, id, locale);"));
+        
+        let ref_last = generate_riverpod_function_provider(&function(vec![param("int", "id"), param("String", "locale"), param("Ref", "r")]), Path::new(""));
+        assert!(ref_last.contains(family));
+        assert!(ref_last.contains("return await fetchUser(id, locale, ref);"));
+        
+        let implicit_ref = generate_riverpod_function_provider(&function(vec![param("int", "id")]), Path::new(""));
+        assert!(implicit_ref.contains("FutureProvider.family<User, int>((ref, id) async {"));
+        assert!(implicit_ref.contains("return await fetchUser(id);"));
+        // Family constructors take no source hash, so none is emitted for them
+        assert!(!implicit_ref.contains("_$fetchUserHash"));
+        
+        let ref_only = generate_riverpod_function_provider(&function(vec![param("AutoDisposeRef<User>", "ref")]), Path::new(""));
+        assert!(ref_only.contains("final fetchUserProvider = FutureProvider<User>((ref) async {\n  return await fetchUser(ref);"));
+    }
+
+    #[test]
+    fn test_getter_and_nested_generic_providers() {
+        let function = |name: &str, return_type: &str, is_getter| DartFunction {
+            name: name.to_string(),
+            return_type: return_type.to_string(),
+            parameters: Vec::new(),
+            annotations: vec!["@riverpod".to_string()],
+            file_path: PathBuf::from("counter.dart"),
+            is_getter,
+            source: String::new(),
+        };
+        
+        let getter = generate_riverpod_function_provider(&function("count", "int", true), Path::new(""));
+        assert!(getter.contains("final countProvider = Provider<int>((ref) {\n  return count;\n}, debugGetCreateSourceHash: "));
+        
+        let nested = generate_riverpod_function_provider(&function("names", "Future<List<String>>", false), Path::new(""));
+        assert!(nested.contains("final namesProvider = FutureProvider<List<String>>((ref) async {\n  return await names();"));
+    }
+
+    #[test]
+    fn test_function_provider_hash_is_stable_across_checkouts() {
+        let function_in = |base: &Path, source: &str| DartFunction {
+            name: "count".to_string(),
+            return_type: "int".to_string(),
+            parameters: Vec::new(),
+            annotations: vec!["@riverpod".to_string()],
+            file_path: base.join("lib/counter.dart"),
+            is_getter: true,
+            source: source.to_string(),
+        };
+        let (first, second) = (Path::new("/home/dev/app"), Path::new("/ci/build/app"));
+        let source = "@riverpod\nint get count => 0;";
+        
+        let provider = generate_riverpod_function_provider(&function_in(first, source), first);
+        assert!(provider.starts_with("String _$countHash() => r'"));
+        assert!(provider.contains("}, debugGetCreateSourceHash: const bool.fromEnvironment('dart.vm.product') ? null : _$countHash);"));
+        assert_eq!(provider, generate_riverpod_function_provider(&function_in(second, source), second));
+        assert_ne!(provider, generate_riverpod_function_provider(&function_in(first, "@riverpod\nint get count => 1;"), first));
+    }
+
+    #[test]
+    fn test_riverpod_generator_dispatches_provider_classes() {
+        let class = |annotation: &str| DartClass {
+            name: "counter".to_string(),
+            annotations: vec![annotation.to_string()],
+            file_path: PathBuf::from("counter.dart"),
+        };
+        let source = "@FutureProvider\nFuture<int> counter(Ref ref) async => 0;\n";
+        
+        assert!(RiverpodGenerator.matches(&class("@FutureProvider")));
+        assert!(!RiverpodGenerator.matches(&class("@freezed")));
+        let result = RiverpodGenerator.generate(&class("@FutureProvider"), source, &[], &GenOptions::default());
+        assert!(result.g_dart_code.contains("final counterProvider = FutureProvider<int>((ref) async {"));
+        assert!(result.freezed_code.is_empty());
+    }
+
+    #[test]
+    fn test_enhanced_provider_code_includes_version_signature() {
+        let code = generate_enhanced_provider_code(&[], &[], Path::new("lib/counter.g.dart"), Path::new(""));
+        assert!(code.contains(&format!("// SuperFastGen v{}", env!("CARGO_PKG_VERSION"))));
+        assert!(code.contains("part of 'counter.dart';"));
+    }
 } 
\ No newline at end of file
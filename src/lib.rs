@@ -0,0 +1,94 @@
+//! SuperFastGen as a library, for build scripts and other Rust tools that want to run the
+//! generator without shelling out to the CLI. The `superfastgen` binary is a wrapper around it.
+
+pub mod commands;
+pub mod utils;
+
+pub use commands::freezed_gen::{DartClass, DartField, GenerationResult, OutputLayout};
+pub use commands::generate::{generate_freezed_to_string, register_generator, unregister_generator, DartGenerator, GenError, GenOptions, GenStats};
+pub use utils::yaml::{AssetsConfig, GenerateConfig, SuperfastgenConfig};
+
+/// What `generate` should run, mirroring the `generate` CLI subcommand
+#[derive(Debug, Clone)]
+pub struct GenConfig {
+    /// Directory (or single file) scanned for annotated Dart code
+    pub input: String,
+    /// Output directory for generated files
+    pub output: String,
+    /// Delete existing generated files under `input` before generating
+    pub delete_conflicting_outputs: bool,
+    /// Where .freezed.dart/.g.dart files are written
+    pub output_layout: OutputLayout,
+    /// Run `dart format` on each generated file (skipped with a warning if dart isn't on PATH)
+    pub format: bool,
+    /// Add a `copyWith` extension to the .g.dart of plain @JsonSerializable classes
+    pub copywith: bool,
+    /// Convert fast_immutable_collections' IList/ISet/IMap fields to and from JSON
+    pub immutable_collections: bool,
+    /// Spaces per indentation level in generated files (2 or 4)
+    pub indent: usize,
+    /// Globs of Dart files to skip, relative to `input` (e.g. `**/legacy/*.dart`)
+    pub exclude: Vec<String>,
+    pub freezed: bool,
+    pub json: bool,
+    pub riverpod: bool,
+    pub provider: bool,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        GenConfig {
+            input: "lib".to_string(),
+            output: "lib".to_string(),
+            delete_conflicting_outputs: false,
+            output_layout: OutputLayout::Sibling,
+            format: false,
+            copywith: false,
+            immutable_collections: false,
+            indent: commands::generate::DEFAULT_INDENT,
+            exclude: Vec::new(),
+            freezed: true,
+            json: true,
+            riverpod: true,
+            provider: true,
+        }
+    }
+}
+
+/// Run the enabled code generators, cleaning conflicting outputs at most once, the same way the
+/// CLI does. The returned stats count every file written once, even if several generators wrote it.
+///
+/// ```
+/// let dir = tempfile::TempDir::new().unwrap();
+/// std::fs::write(
+///     dir.path().join("user.dart"),
+///     "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n}\n",
+/// ).unwrap();
+///
+/// let input = dir.path().to_string_lossy().to_string();
+/// let stats = superfastgen::generate(superfastgen::GenConfig {
+///     input: input.clone(),
+///     output: input,
+///     riverpod: false,
+///     provider: false,
+///     ..Default::default()
+/// }).unwrap();
+///
+/// assert!(stats.files > 0);
+/// assert!(dir.path().join("user.freezed.dart").exists());
+/// ```
+pub fn generate(config: GenConfig) -> Result<GenStats, GenError> {
+    use commands::generate;
+
+    let options = GenOptions {
+        output_layout: config.output_layout,
+        format: config.format,
+        exclude: config.exclude,
+        copywith: config.copywith,
+        immutable_collections: config.immutable_collections,
+        indent: generate::supported_indent(config.indent),
+        ..GenOptions::default()
+    };
+    generate::run_code_generators(&config.input, &config.output, config.delete_conflicting_outputs, config.freezed, config.json, config.riverpod, config.provider, &options)
+        .map(generate::CodeGenStats::total)
+}
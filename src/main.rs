@@ -1,22 +1,26 @@
 use clap::{Parser, Subcommand, ValueEnum};
 
-mod commands;
-mod utils;
-
+use superfastgen::{commands, utils};
 use commands::{generate, assets, provider_gen};
+use commands::generate::GenError;
+use commands::freezed_gen::OutputLayout;
 use utils::{parser, yaml};
 
 use notify::{Watcher, RecursiveMode, RecommendedWatcher, Event, EventKind, Config};
-use std::sync::mpsc::channel;
-use std::time::Duration;
-use std::path::Path;
-use log::info;
+use std::sync::mpsc::{channel, Receiver};
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::io::{Read, Write};
+use log::{info, debug, error, warn};
+use utils::output::SUCCESS;
 
 // Constants for default paths (compatible with Dart build_runner)
 const DEFAULT_LIB_DIR: &str = "lib";
 const DEFAULT_OUTPUT_DIR: &str = "generated";
 const DEFAULT_ASSETS_DIR: &str = "assets";
 const DEFAULT_PUBSPEC_FILE: &str = "pubspec.yaml";
+const DEFAULT_STDIN_FILENAME: &str = "input.dart";
 
 // Computed constants - Use same directory as input for generated files
 const DEFAULT_OUTPUT_PATH: &str = "lib";
@@ -33,8 +37,8 @@ struct Cli {
     /// Assets directory
     #[arg(long, default_value = DEFAULT_ASSETS_DIR)]
     assets: String,
-    /// Watch mode for file changes
-    #[arg(long)]
+    /// Watch mode for file changes (also applies to the generate and assets subcommands)
+    #[arg(long, global = true)]
     watch: bool,
     /// Delete conflicting outputs before generation
     #[arg(long)]
@@ -42,6 +46,66 @@ struct Cli {
     /// Build filter for specific files (like Dart build_runner)
     #[arg(long)]
     build_filter: Option<String>,
+    /// Fail if generated files on disk are stale, without writing anything
+    #[arg(long)]
+    check: bool,
+    /// Show debug output (RUST_LOG takes precedence)
+    #[arg(long, short)]
+    verbose: bool,
+    /// Only print warnings, errors and the final summary line
+    #[arg(long, short, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Dump the parsed AST of every file to the given path (default: debug_ast.txt)
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "debug_ast.txt")]
+    dump_ast: Option<String>,
+    /// Delete generated files whose source no longer exists (otherwise they are only reported)
+    #[arg(long)]
+    prune_orphans: bool,
+    /// Where to write .freezed.dart/.g.dart files: sibling (next to the source) or mirror (under --output)
+    #[arg(long, value_name = "LAYOUT")]
+    output_layout: Option<OutputLayout>,
+    /// Run `dart format` on generated files after writing them (skipped with a warning if dart is missing)
+    #[arg(long)]
+    format: bool,
+    /// Also scan the .dart_tool, build, ios/.symlinks and macos/.symlinks directories of the input directory
+    #[arg(long)]
+    include_hidden: bool,
+    /// Skip Dart files matching this glob, relative to the input directory (repeatable, e.g. "**/legacy/*.dart")
+    #[arg(long, value_name = "PATTERN")]
+    exclude_glob: Vec<String>,
+    /// Name of the top-level class in assets.gen.dart (default: Assets)
+    #[arg(long, value_name = "NAME")]
+    assets_class_name: Option<String>,
+    /// Write a Dart file exporting every generated file to the given path
+    #[arg(long, value_name = "PATH")]
+    barrel: Option<String>,
+    /// Also write a JSON Lines manifest of the assets (key, category, type and size per line) to the given path
+    #[arg(long, value_name = "PATH")]
+    asset_manifest: Option<String>,
+    /// Fail on class names declared in several files instead of only warning
+    #[arg(long)]
+    strict: bool,
+    /// Insert missing `part` directives for generated files into their sources
+    #[arg(long)]
+    add_parts: bool,
+    /// Exit non-zero when the input has no Dart files or no annotated classes or functions
+    #[arg(long)]
+    fail_on_empty: bool,
+    /// Project root (the directory with pubspec.yaml); relative paths and config files are resolved from it
+    #[arg(long, global = true, value_name = "DIR")]
+    root: Option<String>,
+    /// Write a JSON report of each run (model, asset and provider counts and per-phase timings) to the given path
+    #[arg(long, value_name = "PATH")]
+    report: Option<String>,
+    /// Read one Dart source from stdin and print the generated code to stdout without touching any files
+    #[arg(long)]
+    stdin: bool,
+    /// Generator to run with --stdin
+    #[arg(long = "type", value_enum, value_name = "TYPE", requires = "stdin")]
+    stdin_type: Option<GenType>,
+    /// File name of the --stdin source, used for its `part of` directives
+    #[arg(long, requires = "stdin")]
+    filename: Option<String>,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -51,7 +115,7 @@ enum Commands {
         /// Type of code to generate
         #[arg(long, value_enum, default_value = "all")]
         r#type: GenType,
-        /// Input directories to process (like Dart build_runner)
+        /// Input directories to process (like Dart build_runner), or a single Dart file to generate for
         #[arg(default_value = DEFAULT_LIB_DIR)]
         directories: Vec<String>,
         /// Output directory for generated files (overrides global --output)
@@ -60,6 +124,9 @@ enum Commands {
         /// Delete conflicting outputs before generation
         #[arg(long)]
         delete_conflicting_outputs: bool,
+        /// Print the generated code to stdout instead of writing it
+        #[arg(long)]
+        print: bool,
     },
     /// Generate only assets
     Assets {
@@ -85,6 +152,39 @@ enum Commands {
         #[arg(long)]
         output: Option<String>,
     },
+    /// List annotated classes and functions without generating anything
+    List {
+        /// Input directory to scan
+        #[arg(default_value = DEFAULT_LIB_DIR)]
+        directory: String,
+        /// Print the listing as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Move existing generated files to where another output layout puts them
+    Migrate {
+        /// Layout the generated files are currently in
+        #[arg(long, value_name = "LAYOUT")]
+        from: OutputLayout,
+        /// Layout to move them to
+        #[arg(long, value_name = "LAYOUT")]
+        to: OutputLayout,
+        /// Input directory the sources are in
+        #[arg(default_value = DEFAULT_LIB_DIR)]
+        directory: String,
+        /// Output directory of the mirror layout (overrides global --output)
+        #[arg(long)]
+        output: Option<String>,
+    },
+    /// Print a JSON Schema of superfastgen.yaml (for YAML language servers)
+    #[command(hide = true)]
+    ConfigSchema,
+    /// Write a starter superfastgen.yaml
+    Init {
+        /// Overwrite an existing superfastgen.yaml
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -102,35 +202,67 @@ struct EffectiveConfig {
     assets: String,
     watch: bool,
     delete_conflicting_outputs: bool,
-    build_filter: Option<String>,
+    // Source file or directory to generate from (see resolve_input_path), used by every subcommand
+    input: String,
+    check: bool,
+    prune_orphans: bool,
+    fail_on_empty: bool,
+    // Asset globs excluded in superfastgen.yaml
+    asset_exclude: Vec<String>,
+    // Top-level class of assets.gen.dart
+    assets_class_name: String,
+    // Generators enabled in superfastgen.yaml (all enabled by default)
+    freezed: bool,
+    json: bool,
+    riverpod: bool,
+    provider: bool,
+    // Dart file exporting every generated file, rewritten after each run (--barrel)
+    barrel: Option<PathBuf>,
+    // Where report_run writes the JSON report (--report)
+    report: Option<PathBuf>,
+    // Settings passed down to the code and asset generators
+    options: generate::GenOptions,
 }
 
 fn main() {
-    env_logger::init();
-    info!("SuperFastGen - Code Generator");
     let cli = Cli::parse();
-    let yaml_config = yaml::parse_superfastgen_yaml("superfastgen.yaml");
+    let default_level = if cli.verbose {
+        "debug"
+    } else if cli.quiet {
+        "warn"
+    } else {
+        "info"
+    };
+    utils::output::init_logger(default_level);
+    info!("SuperFastGen - Code Generator");
+    if let Some(root) = cli.root.as_ref().filter(|root| !Path::new(root).is_dir()) {
+        error!("Cannot use {} as the project root: not a directory", root);
+        std::process::exit(1);
+    }
+    let yaml_config = load_yaml_config(cli.root.as_ref().map(Path::new));
     let effective = merge_config(&cli, yaml_config);
 
+    if cli.stdin {
+        let gen_type = cli.stdin_type.clone().unwrap_or(GenType::All);
+        let filename = cli.filename.as_deref().unwrap_or(DEFAULT_STDIN_FILENAME);
+        if let Err(e) = run_stdin(std::io::stdin().lock(), std::io::stdout().lock(), filename, &gen_type, &effective) {
+            error!("Generation failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     match &cli.command {
-        Some(Commands::Generate { r#type, directories, output, delete_conflicting_outputs }) => {
-            let effective_output = output.as_ref().cloned().unwrap_or(effective.output.clone());
+        Some(Commands::Generate { r#type, directories, output, delete_conflicting_outputs, print }) => {
+            let effective_output = output.as_ref().map(|output| effective.in_root(output)).unwrap_or(effective.output.clone());
             let effective_delete_conflicting = *delete_conflicting_outputs || effective.delete_conflicting_outputs;
 
-            // Use the first directory as input path, or fallback to build_filter logic
+            // Use the first directory as input path, or fall back to the configured input
             let input_path = if !directories.is_empty() {
-                eprintln!("[DEBUG] Using directories[0]: {}", directories[0]);
-                directories[0].clone()
-            } else if let Some(ref filter) = effective.build_filter {
-                let path = std::path::Path::new(filter);
-                if let Some(parent) = path.parent() {
-                    parent.to_string_lossy().to_string()
-                } else {
-                    DEFAULT_LIB_DIR.to_string()
-                }
+                debug!("Using directories[0]: {}", directories[0]);
+                effective.in_root(&directories[0])
             } else {
-                eprintln!("[DEBUG] Using DEFAULT_LIB_DIR: {}", DEFAULT_LIB_DIR);
-                DEFAULT_LIB_DIR.to_string()
+                effective.input.clone()
             };
             
             // If output path is the same as input path, use the same directory for generated files
@@ -140,74 +272,146 @@ fn main() {
                 effective_output
             };
             
-            eprintln!("[DEBUG] input_path: {}", input_path);
-            eprintln!("[DEBUG] final_output_path: {}", final_output_path);
-            eprintln!("[DEBUG] effective_delete_conflicting: {}", effective_delete_conflicting);
+            debug!("input_path: {}", input_path);
+            debug!("final_output_path: {}", final_output_path);
+            debug!("effective_delete_conflicting: {}", effective_delete_conflicting);
             
-            eprintln!("[DEBUG] r#type: {:?}", r#type);
-            match r#type {
-                GenType::Freezed => {
-                    eprintln!("[DEBUG] GenType::Freezed - Calling generate_freezed_with_paths_and_clean");
-                    generate::generate_freezed_with_paths_and_clean(&input_path, &final_output_path, effective_delete_conflicting)
-                },
-                GenType::Json => {
-                    eprintln!("[DEBUG] GenType::Json - Calling generate_json_with_paths_and_clean");
-                    generate::generate_json_with_paths_and_clean(&input_path, &final_output_path, effective_delete_conflicting)
-                },
-                GenType::Riverpod => {
-                    eprintln!("[DEBUG] GenType::Riverpod - Calling generate_riverpod_with_paths_and_clean");
-                    generate::generate_riverpod_with_paths_and_clean(&input_path, &final_output_path, effective_delete_conflicting)
-                },
-                GenType::Provider => {
-                    eprintln!("[DEBUG] GenType::Provider - Calling generate_provider_with_paths_and_clean");
-                    generate::generate_provider_with_paths_and_clean(&input_path, &final_output_path, effective_delete_conflicting)
-                },
-                GenType::All => {
-                    eprintln!("[DEBUG] GenType::All - Calling all generators");
-                    generate::generate_freezed_with_paths_and_clean(&input_path, &final_output_path, effective_delete_conflicting);
-                    generate::generate_json_with_paths_and_clean(&input_path, &final_output_path, false);
-                    generate::generate_riverpod_with_paths_and_clean(&input_path, &final_output_path, false);
-                    generate::generate_provider_with_paths_and_clean(&input_path, &final_output_path, false);
-                },
+            let (freezed, json, riverpod, provider) = selected_generators(r#type, &effective);
+            if effective.check {
+                exit_with_check_result(generate::collect_generated_outputs(&input_path, &final_output_path, freezed, json, riverpod, provider, &effective.options), &effective.options);
+            }
+            if *print {
+                let printed = generate::collect_generated_outputs(&input_path, &final_output_path, freezed, json, riverpod, provider, &effective.options)
+                    .and_then(|outputs| generate::print_generated_outputs(&outputs, std::io::stdout().lock()).map_err(|e| GenError::io("<stdout>", e)));
+                if let Err(e) = printed {
+                    error!("Generation failed: {}", e);
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            debug!("r#type: {:?} (freezed: {}, json: {}, riverpod: {}, provider: {})", r#type, freezed, json, riverpod, provider);
+            let session = SessionOutputs::default();
+            let run = || {
+                let started = Instant::now();
+                let result = check_input_not_empty(&effective, &input_path)
+                    .and_then(|_| run_code_generators(&input_path, &final_output_path, effective_delete_conflicting, effective.prune_orphans, freezed, json, riverpod, provider, &effective.options));
+                report_run(session.full_run(result), started, &effective);
+            };
+            if effective.watch {
+                let run_files = |files: &[PathBuf]| {
+                    let started = Instant::now();
+                    let result = run_code_generators_for_files(files, &input_path, &final_output_path, freezed, json, riverpod, provider, &effective.options);
+                    report_run(session.file_run(result), started, &effective);
+                };
+                watch_mode(&[input_path.clone()], run, run_files);
+            } else {
+                run();
             }
         }
         Some(Commands::Assets { assets, output }) => {
-            let effective_assets = assets.as_ref().cloned().unwrap_or(effective.assets.clone());
-            let effective_output = output.as_ref().cloned().unwrap_or(effective.output.clone());
-            assets::generate_assets_with_paths(&effective_assets, &effective_output);
+            let effective_assets = assets.as_ref().map(|assets| effective.in_root(assets)).unwrap_or(effective.assets.clone());
+            let effective_output = output.as_ref().map(|output| effective.in_root(output)).unwrap_or(effective.output.clone());
+            let run = || {
+                let started = Instant::now();
+                let result = assets::generate_assets_with_paths(&effective_assets, &effective_output, &effective.asset_exclude, &effective.assets_class_name, &effective.options)
+                    .map(|stats| RunSummary { assets: stats.assets, outputs: stats.outputs, ..RunSummary::default() });
+                report_run(result, started, &effective);
+            };
+            if effective.watch {
+                watch_mode(&[effective_assets.clone(), effective.in_root(DEFAULT_PUBSPEC_FILE)], run, |_: &[PathBuf]| run());
+            } else {
+                run();
+            }
         }
         Some(Commands::All { output, assets }) => {
-            let effective_output = output.as_ref().cloned().unwrap_or(effective.output.clone());
-            let effective_assets = assets.as_ref().cloned().unwrap_or(effective.assets.clone());
-            run_generators(&EffectiveConfig {
+            let effective_output = output.as_ref().map(|output| effective.in_root(output)).unwrap_or(effective.output.clone());
+            let effective_assets = assets.as_ref().map(|assets| effective.in_root(assets)).unwrap_or(effective.assets.clone());
+            run_generators_with_summary(&EffectiveConfig {
                 output: effective_output,
                 assets: effective_assets,
-                watch: effective.watch,
-                delete_conflicting_outputs: effective.delete_conflicting_outputs,
-                build_filter: effective.build_filter.clone(),
+                ..effective.clone()
             });
         }
         Some(Commands::Clean { output }) => {
-            let effective_output = output.as_ref().cloned().unwrap_or(effective.output.clone());
+            let effective_output = output.as_ref().map(|output| effective.in_root(output)).unwrap_or(effective.output.clone());
             clean_generated_files(&EffectiveConfig {
                 output: effective_output,
-                assets: effective.assets.clone(),
-                watch: effective.watch,
-                delete_conflicting_outputs: effective.delete_conflicting_outputs,
-                build_filter: effective.build_filter.clone(),
+                ..effective.clone()
             });
         }
+        Some(Commands::List { directory, json }) => {
+            let files = generate::list_annotated_declarations(&effective.in_root(directory), &effective.options);
+            if *json {
+                match serde_json::to_string_pretty(&files) {
+                    Ok(listing) => println!("{}", listing),
+                    Err(e) => error!("{}", e),
+                }
+            } else {
+                print!("{}", generate::format_declaration_listing(&files));
+            }
+        }
+        Some(Commands::Migrate { from, to, directory, output }) => {
+            let effective_output = output.as_ref().map(|output| effective.in_root(output)).unwrap_or(effective.output.clone());
+            match generate::migrate_output_layout(&effective.in_root(directory), &effective_output, *from, *to, &effective.options) {
+                Ok(stats) => info!(target: SUCCESS, "Moved {} generated files", stats.files),
+                Err(e) => {
+                    error!("Migration failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(Commands::ConfigSchema) => {
+            println!("{}", serde_json::to_string_pretty(&yaml::config_schema()).unwrap_or_default());
+        }
+        Some(Commands::Init { force }) => {
+            match yaml::write_starter_config(&effective.in_root("superfastgen.yaml"), *force) {
+                Ok(()) => info!(target: SUCCESS, "Created superfastgen.yaml"),
+                Err(e) => {
+                    error!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
         None => {
             // If --watch is specified, run in watch mode
             if effective.watch {
-                watch_mode(&effective);
+                let watch_paths = [effective.input.clone(), effective.in_root(DEFAULT_PUBSPEC_FILE)];
+                let session = SessionOutputs::default();
+                let run = || {
+                    let started = Instant::now();
+                    report_run(session.full_run(run_generators(&effective)), started, &effective);
+                };
+                watch_mode(&watch_paths, run, |files: &[PathBuf]| {
+                    let started = Instant::now();
+                    report_run(session.file_run(run_generators_for_files(&effective, files)), started, &effective);
+                });
             } else {
-                run_generators(&effective);
+                run_generators_with_summary(&effective);
             }
         }
     }
 }
 
+/// superfastgen.yaml, or the `superfastgen:` section of pubspec.yaml, in `root` (the current directory without --root)
+fn load_yaml_config(root: Option<&Path>) -> Option<yaml::SuperfastgenConfig> {
+    yaml::load_config(&in_root(root, "superfastgen.yaml"), &in_root(root, DEFAULT_PUBSPEC_FILE))
+}
+
+/// `path` resolved against the --root project root; unchanged without --root or when absolute
+fn in_root(root: Option<&Path>, path: &str) -> String {
+    match root {
+        Some(root) => root.join(path).to_string_lossy().to_string(),
+        None => path.to_string(),
+    }
+}
+
+impl EffectiveConfig {
+    fn in_root(&self, path: &str) -> String {
+        in_root(self.options.project_root.as_deref(), path)
+    }
+}
+
 fn merge_config(cli: &Cli, yaml_config: Option<yaml::SuperfastgenConfig>) -> EffectiveConfig {
     let (yaml_gen, yaml_assets) = if let Some(cfg) = yaml_config {
         (cfg.generate.unwrap_or_default(), cfg.assets.unwrap_or_default())
@@ -216,117 +420,415 @@ fn merge_config(cli: &Cli, yaml_config: Option<yaml::SuperfastgenConfig>) -> Eff
     };
     
     // Use configuration fields to determine behavior
-    let _freezed_enabled = yaml_gen.freezed.unwrap_or(true);
-    let _json_enabled = yaml_gen.json.unwrap_or(true);
-    let _riverpod_enabled = yaml_gen.riverpod.unwrap_or(true);
     let _images_enabled = yaml_assets.include_images.unwrap_or(true);
     let _fonts_enabled = yaml_assets.include_fonts.unwrap_or(true);
     let _icons_enabled = yaml_assets.include_icons.unwrap_or(true);
     
+    let root = cli.root.as_ref().map(Path::new);
+    let input = in_root(root, &resolve_input_path(cli.build_filter.as_ref(), &yaml_gen));
     EffectiveConfig {
         // Prioritize CLI arguments if they differ from defaults
-        output: if cli.output != DEFAULT_OUTPUT_PATH {
+        output: in_root(root, &if cli.output != DEFAULT_OUTPUT_PATH {
             cli.output.clone()
         } else {
             yaml_gen.output.unwrap_or(DEFAULT_OUTPUT_PATH.to_string())
-        },
-        build_filter: cli.build_filter.clone(),
-        assets: if cli.assets != DEFAULT_ASSETS_DIR {
+        }),
+        input,
+        assets: in_root(root, &if cli.assets != DEFAULT_ASSETS_DIR {
             cli.assets.clone()
         } else {
             yaml_assets.input.unwrap_or(DEFAULT_ASSETS_DIR.to_string())
-        },
+        }),
         // Watch mode controlled only by CLI --watch flag
         watch: cli.watch,
         // Delete conflicting outputs flag
         delete_conflicting_outputs: cli.delete_conflicting_outputs,
+        check: cli.check,
+        prune_orphans: cli.prune_orphans,
+        fail_on_empty: cli.fail_on_empty,
+        asset_exclude: yaml_assets.exclude.unwrap_or_default(),
+        assets_class_name: cli.assets_class_name.clone().or(yaml_assets.class_name).unwrap_or_else(|| assets::DEFAULT_ASSETS_CLASS_NAME.to_string()),
+        freezed: yaml_gen.freezed.unwrap_or(true),
+        json: yaml_gen.json.unwrap_or(true),
+        riverpod: yaml_gen.riverpod.unwrap_or(true),
+        provider: yaml_gen.provider.unwrap_or(true),
+        barrel: cli.barrel.as_ref().map(PathBuf::from),
+        report: cli.report.as_ref().map(PathBuf::from),
+        options: generate::GenOptions {
+            output_layout: cli.output_layout.or(yaml_gen.output_layout).unwrap_or_default(),
+            format: cli.format,
+            include_hidden: cli.include_hidden,
+            // Source globs from --exclude-glob and generate.exclude
+            exclude: cli.exclude_glob.iter().cloned().chain(yaml_gen.exclude.unwrap_or_default()).collect(),
+            copywith: yaml_gen.copywith.unwrap_or(false),
+            immutable_collections: yaml_gen.immutable_collections.unwrap_or(false),
+            indent: generate::supported_indent(yaml_gen.indent.unwrap_or(generate::DEFAULT_INDENT)),
+            strict: cli.strict,
+            add_parts: cli.add_parts,
+            asset_manifest: cli.asset_manifest.as_ref().map(PathBuf::from),
+            project_root: root.map(Path::to_path_buf),
+            dump_ast: cli.dump_ast.as_ref().map(PathBuf::from),
+        },
     }
 }
 
-/// Run all code and asset generators
-fn run_generators(cfg: &EffectiveConfig) {
-    let yaml_config = yaml::parse_superfastgen_yaml("superfastgen.yaml");
-    let (yaml_gen, yaml_assets) = if let Some(config) = yaml_config {
-        (config.generate.unwrap_or_default(), config.assets.unwrap_or_default())
-    } else {
-        (yaml::GenerateConfig::default(), yaml::AssetsConfig::default())
+/// Generators to run for `generate --type`, restricted to the ones enabled in the config
+fn selected_generators(gen_type: &GenType, cfg: &EffectiveConfig) -> (bool, bool, bool, bool) {
+    let (freezed, json, riverpod, provider) = match gen_type {
+        GenType::Freezed => (true, false, false, false),
+        GenType::Json => (false, true, false, false),
+        GenType::Riverpod => (false, false, true, false),
+        GenType::Provider => (false, false, false, true),
+        GenType::All => (true, true, true, true),
     };
-    
-    // Use build_filter if specified, otherwise use default lib directory
-    let input_path = if let Some(ref filter) = cfg.build_filter {
-        filter.clone()
-    } else {
-        DEFAULT_LIB_DIR.to_string()
+    (freezed && cfg.freezed, json && cfg.json, riverpod && cfg.riverpod, provider && cfg.provider)
+}
+
+/// Generate code for the Dart source read from `input` and write it to `output`,
+/// each generated file preceded by a `// ==> <file name>` line
+fn run_stdin(mut input: impl Read, mut output: impl Write, filename: &str, gen_type: &GenType, cfg: &EffectiveConfig) -> Result<(), GenError> {
+    let mut source = String::new();
+    input.read_to_string(&mut source).map_err(|e| GenError::io("<stdin>", e))?;
+    let (freezed, json, riverpod, provider) = selected_generators(gen_type, cfg);
+    for (path, content) in generate::generate_source(&source, filename, freezed, json, riverpod, provider, &cfg.options)? {
+        writeln!(output, "// ==> {}", path.display())
+            .and_then(|_| output.write_all(content.as_bytes()))
+            .map_err(|e| GenError::io("<stdout>", e))?;
+    }
+    Ok(())
+}
+
+/// What one run generated, reported in the summary line
+#[derive(Debug, Default, Clone, PartialEq)]
+struct RunSummary {
+    // Annotated model classes code was generated for
+    models: usize,
+    // Asset constants in assets.gen.dart
+    assets: usize,
+    // Annotated provider classes and functions code was generated for
+    providers: usize,
+    timings: PhaseTimings,
+    // Every file written, exported by the --barrel file
+    outputs: Vec<PathBuf>,
+}
+
+/// How long each phase of a run took. Parsing happens inside the generator phases, so their
+/// durations include their share of `parse`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct PhaseTimings {
+    parse: Duration,
+    freezed: Duration,
+    json: Duration,
+    provider: Duration,
+    assets: Duration,
+}
+
+impl PhaseTimings {
+    fn phases(&self) -> [(&'static str, Duration); 5] {
+        [
+            ("parse", self.parse),
+            ("freezed", self.freezed),
+            ("json", self.json),
+            ("provider", self.provider),
+            ("assets", self.assets),
+        ]
+    }
+
+    fn line(&self) -> String {
+        let phases: Vec<String> = self.phases().iter().map(|(phase, took)| format!("{} {} ms", phase, took.as_millis())).collect();
+        format!("Phase timings: {}", phases.join(", "))
+    }
+}
+
+impl RunSummary {
+    /// Remember files written by one of the generators; the json pass rewrites the freezed pass's files
+    fn add_outputs(&mut self, outputs: Vec<PathBuf>) {
+        for output in outputs {
+            if !self.outputs.contains(&output) {
+                self.outputs.push(output);
+            }
+        }
+    }
+
+    fn line(&self, elapsed: Duration) -> String {
+        format!(
+            "SuperFastGen: {} models, {} assets, {} providers generated in {} ms",
+            self.models,
+            self.assets,
+            self.providers,
+            elapsed.as_millis()
+        )
+    }
+
+    /// The run as a JSON report, written with --report
+    fn report_json(&self, elapsed: Duration) -> serde_json::Value {
+        let phases: serde_json::Map<String, serde_json::Value> = self
+            .timings
+            .phases()
+            .iter()
+            .map(|(phase, took)| (format!("{}_ms", phase), serde_json::Value::from(took.as_millis() as u64)))
+            .collect();
+        serde_json::json!({
+            "models": self.models,
+            "assets": self.assets,
+            "providers": self.providers,
+            "elapsed_ms": elapsed.as_millis() as u64,
+            "phases": phases,
+        })
+    }
+}
+
+/// Run the enabled code generators (generate::run_code_generators, like the library) and
+/// report orphaned outputs
+#[allow(clippy::too_many_arguments)]
+fn run_code_generators(input_path: &str, output_path: &str, delete_conflicting_outputs: bool, prune_orphans: bool, freezed: bool, json: bool, riverpod: bool, provider: bool, options: &generate::GenOptions) -> Result<RunSummary, GenError> {
+    let stats = generate::run_code_generators(input_path, output_path, delete_conflicting_outputs, freezed, json, riverpod, provider, options)?;
+    let summary = RunSummary {
+        models: stats.models.declarations.len(),
+        providers: stats.providers.declarations.len(),
+        timings: PhaseTimings {
+            parse: stats.models.parse_time + stats.providers.parse_time,
+            freezed: stats.freezed_time,
+            json: stats.json_time,
+            provider: stats.provider_time,
+            ..PhaseTimings::default()
+        },
+        outputs: stats.total().outputs,
+        ..RunSummary::default()
     };
     
-    // Track if we've already cleaned up in this run
-    let mut has_cleaned = false;
-    
-    // Generate code based on configuration
-    if yaml_gen.freezed.unwrap_or(true) {
-        generate::generate_freezed_with_paths_and_clean(&input_path, &cfg.output, cfg.delete_conflicting_outputs && !has_cleaned);
-        has_cleaned = true;
+    // Generated files left behind by deleted or renamed sources
+    generate::handle_orphaned_outputs(input_path, prune_orphans, options);
+    Ok(summary)
+}
+
+/// Regenerate only the given source files (watch mode), with the same generators as run_code_generators
+#[allow(clippy::too_many_arguments)]
+fn run_code_generators_for_files(files: &[PathBuf], input_path: &str, output_path: &str, freezed: bool, json: bool, riverpod: bool, provider: bool, options: &generate::GenOptions) -> Result<RunSummary, GenError> {
+    let mut summary = RunSummary::default();
+    for file in &generate::source_files_among(files, input_path, options) {
+        let stats = generate::generate_file(file, input_path, output_path, freezed, json, false, false, options)?;
+        summary.models += stats.declarations.len();
+        summary.timings.parse += stats.parse_time;
+        summary.add_outputs(stats.outputs);
+        let stats = generate::generate_file(file, input_path, output_path, false, false, riverpod, provider, options)?;
+        summary.providers += stats.declarations.len();
+        summary.timings.parse += stats.parse_time;
+        summary.add_outputs(stats.outputs);
     }
-    
-    if yaml_gen.json.unwrap_or(true) {
-        generate::generate_json_with_paths_and_clean(&input_path, &cfg.output, cfg.delete_conflicting_outputs && !has_cleaned);
-        has_cleaned = true;
+    Ok(summary)
+}
+
+/// Print the summary line of a finished run and write its barrel file (--barrel).
+/// Errors exit non-zero unless watching.
+fn report_run(result: Result<RunSummary, GenError>, started: Instant, cfg: &EffectiveConfig) {
+    match result {
+        Ok(summary) => {
+            let elapsed = started.elapsed();
+            println!("{}", summary.line(elapsed));
+            info!("{}", summary.timings.line());
+            if let Some(barrel) = &cfg.barrel {
+                generate::write_barrel_file(barrel, &summary.outputs);
+            }
+            if let Some(report_path) = &cfg.report {
+                let report = serde_json::to_string_pretty(&summary.report_json(elapsed)).unwrap_or_default();
+                if let Err(e) = std::fs::write(report_path, report) {
+                    error!("Failed to write report {}: {}", report_path.display(), e);
+                }
+            }
+        }
+        Err(e) => {
+            error!("Generation failed: {}", e);
+            if !cfg.watch {
+                std::process::exit(1);
+            }
+        }
     }
-    
-    if yaml_gen.riverpod.unwrap_or(true) {
-        generate::generate_riverpod_with_paths_and_clean(&input_path, &cfg.output, cfg.delete_conflicting_outputs && !has_cleaned);
-        has_cleaned = true;
+}
+
+/// Run all code and asset generators and report the result
+fn run_generators_with_summary(cfg: &EffectiveConfig) {
+    let started = Instant::now();
+    report_run(run_generators(cfg), started, cfg);
+}
+
+/// Files generated so far in a watch session. A per-file re-run only writes the changed files,
+/// so its barrel file has to export them together with everything generated before.
+#[derive(Default)]
+struct SessionOutputs(RefCell<Vec<PathBuf>>);
+
+impl SessionOutputs {
+    /// A full run's outputs replace the session's
+    fn full_run(&self, result: Result<RunSummary, GenError>) -> Result<RunSummary, GenError> {
+        if let Ok(summary) = &result {
+            *self.0.borrow_mut() = summary.outputs.clone();
+        }
+        result
+    }
+
+    /// A per-file re-run's outputs are added to the session's, which become the summary's outputs
+    fn file_run(&self, result: Result<RunSummary, GenError>) -> Result<RunSummary, GenError> {
+        result.map(|mut summary| {
+            let mut outputs = self.0.borrow_mut();
+            for output in summary.outputs.drain(..) {
+                if !outputs.contains(&output) {
+                    outputs.push(output);
+                }
+            }
+            summary.outputs = outputs.clone();
+            summary
+        })
     }
+}
+
+/// Run all code and asset generators
+fn run_generators(cfg: &EffectiveConfig) -> Result<RunSummary, GenError> {
+    let yaml_assets = load_yaml_config(cfg.options.project_root.as_deref())
+        .and_then(|config| config.assets)
+        .unwrap_or_default();
+    
+    let input_path = cfg.input.as_str();
     
-    if yaml_gen.provider.unwrap_or(true) {
-        generate::generate_provider_with_paths_and_clean(&input_path, &cfg.output, cfg.delete_conflicting_outputs && !has_cleaned);
-        has_cleaned = true;
+    // In check mode, compare against disk instead of generating
+    if cfg.check {
+        exit_with_check_result(generate::collect_generated_outputs(input_path, &cfg.output, cfg.freezed, cfg.json, cfg.riverpod, cfg.provider, &cfg.options), &cfg.options);
     }
     
+    check_input_not_empty(cfg, input_path)?;
+    
+    // Generate code based on configuration
+    let mut summary = run_code_generators(input_path, &cfg.output, cfg.delete_conflicting_outputs, cfg.prune_orphans, cfg.freezed, cfg.json, cfg.riverpod, cfg.provider, &cfg.options)?;
+    
     // Use configuration for assets
     if yaml_assets.include_images.unwrap_or(true) || 
        yaml_assets.include_fonts.unwrap_or(true) || 
        yaml_assets.include_icons.unwrap_or(true) {
-        let assets_output = yaml_assets.output.unwrap_or(cfg.output.clone());
-        assets::generate_assets_with_paths(&cfg.assets, &assets_output);
+        let assets_output = yaml_assets.output.map(|output| cfg.in_root(&output)).unwrap_or(cfg.output.clone());
+        let started = Instant::now();
+        let stats = assets::generate_assets_with_paths(&cfg.assets, &assets_output, &cfg.asset_exclude, &cfg.assets_class_name, &cfg.options)?;
+        summary.assets = stats.assets;
+        summary.add_outputs(stats.outputs);
+        summary.timings.assets = started.elapsed();
     }
     
     let _ = parser::parse_code("example code");
     let _ = yaml::parse_pubspec_yaml("example yaml");
+    Ok(summary)
+}
+
+/// With --fail-on-empty, an input without Dart files or annotated declarations is an error
+fn check_input_not_empty(cfg: &EffectiveConfig, input_path: &str) -> Result<(), GenError> {
+    if cfg.fail_on_empty {
+        generate::check_not_empty(input_path, &cfg.options)?;
+    }
+    Ok(())
 }
 
-/// Watch for file changes and rerun generators
-fn watch_mode(cfg: &EffectiveConfig) {
-    let input_path = if let Some(ref filter) = cfg.build_filter {
-        let path = std::path::Path::new(filter);
-        if let Some(parent) = path.parent() {
-            parent.to_string_lossy().to_string()
+/// Regenerate only the given source files with the generators enabled in cfg (watch mode)
+fn run_generators_for_files(cfg: &EffectiveConfig, files: &[PathBuf]) -> Result<RunSummary, GenError> {
+    run_code_generators_for_files(files, &cfg.input, &cfg.output, cfg.freezed, cfg.json, cfg.riverpod, cfg.provider, &cfg.options)
+}
+
+/// Input file or directory for every subcommand (generate, watch, clean); --build-filter is used as is.
+/// Precedence: --build-filter, then `generate.input` from superfastgen.yaml, then DEFAULT_LIB_DIR.
+fn resolve_input_path(build_filter: Option<&String>, yaml_gen: &yaml::GenerateConfig) -> String {
+    build_filter
+        .or(yaml_gen.input.as_ref())
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_LIB_DIR.to_string())
+}
+
+/// Exit with code 1 if any generated output differs from disk (or couldn't be generated), 0 otherwise
+fn exit_with_check_result(outputs: Result<Vec<(std::path::PathBuf, String)>, GenError>, options: &generate::GenOptions) -> ! {
+    let outputs = outputs.unwrap_or_else(|e| {
+        error!("Generation failed: {}", e);
+        std::process::exit(1);
+    });
+    let stale_count = generate::check_generated_outputs(&outputs, options);
+    std::process::exit(if stale_count == 0 { 0 } else { 1 });
+}
+
+/// Start watching the given paths recursively. Paths that don't exist are skipped.
+fn create_watcher(watch_paths: &[String]) -> notify::Result<(RecommendedWatcher, Receiver<notify::Result<Event>>)> {
+    let (tx, rx) = channel();
+    let config = Config::default().with_poll_interval(Duration::from_secs(1));
+    let mut watcher: RecommendedWatcher = Watcher::new(tx, config)?;
+    for path in watch_paths {
+        if Path::new(path).exists() {
+            watcher.watch(Path::new(path), RecursiveMode::Recursive)?;
         } else {
-            DEFAULT_LIB_DIR.to_string()
+            warn!("Not watching {} (not found)", path);
         }
+    }
+    Ok((watcher, rx))
+}
+
+fn is_change_event(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_))
+}
+
+/// What a watch event calls for
+#[derive(Debug, PartialEq)]
+enum WatchAction {
+    /// Nothing relevant changed (including our own generated files being written)
+    Ignore,
+    /// Only these Dart sources changed
+    Files(Vec<PathBuf>),
+    /// pubspec.yaml, a removed file or a non-Dart file changed
+    Full,
+}
+
+fn watch_action(event: &Event) -> WatchAction {
+    if !is_change_event(event) {
+        return WatchAction::Ignore;
+    }
+    let is_generated = |path: &PathBuf| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.ends_with(".g.dart") || name.ends_with(".freezed.dart") || name.ends_with(".gen.dart")
+    };
+    let paths: Vec<PathBuf> = event.paths.iter().filter(|path| !is_generated(path)).cloned().collect();
+    if paths.is_empty() {
+        return WatchAction::Ignore;
+    }
+    // Removed sources leave orphaned outputs behind, which only a full run reports
+    let only_dart_sources = !matches!(event.kind, EventKind::Remove(_))
+        && paths.iter().all(|path| path.extension().is_some_and(|ext| ext == "dart"));
+    if only_dart_sources {
+        WatchAction::Files(paths)
     } else {
-        DEFAULT_LIB_DIR.to_string()
+        WatchAction::Full
+    }
+}
+
+/// Watch for file changes in `watch_paths`: changed Dart sources are passed to `run_files`,
+/// anything else (e.g. pubspec.yaml) reruns `run`
+fn watch_mode<F: Fn(), G: Fn(&[PathBuf])>(watch_paths: &[String], run: F, run_files: G) {
+    info!("Watching for changes in {}...", watch_paths.join(", "));
+    let (_watcher, rx) = match create_watcher(watch_paths) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            error!("Failed to start watcher: {:?}", e);
+            return;
+        }
     };
-    
-    println!("Watching for changes in {} and pubspec.yaml...", input_path);
-    let (tx, rx) = channel();
-    let config = Config::default().with_poll_interval(Duration::from_secs(1));
-    let mut watcher: RecommendedWatcher = Watcher::new(tx, config).unwrap();
-    watcher.watch(Path::new(&input_path), RecursiveMode::Recursive).unwrap();
-    watcher.watch(Path::new("pubspec.yaml"), RecursiveMode::NonRecursive).unwrap();
 
-    run_generators(cfg);
+    run();
 
     loop {
         match rx.recv() {
-            Ok(Ok(event)) => {
-                if let Event { kind: EventKind::Modify(_), .. } | Event { kind: EventKind::Create(_), .. } | Event { kind: EventKind::Remove(_), .. } = event {
-                    println!("Change detected! Regenerating...");
-                    run_generators(cfg);
+            Ok(Ok(event)) => match watch_action(&event) {
+                WatchAction::Ignore => {}
+                WatchAction::Files(files) => {
+                    info!("Change detected! Regenerating {} file(s)...", files.len());
+                    run_files(&files);
                 }
-            }
-            Ok(Err(e)) => println!("watch error: {:?}", e),
-            Err(e) => println!("channel error: {:?}", e),
+                WatchAction::Full => {
+                    info!("Change detected! Regenerating...");
+                    run();
+                }
+            },
+            Ok(Err(e)) => error!("watch error: {:?}", e),
+            Err(e) => error!("channel error: {:?}", e),
         }
     }
 }
@@ -336,23 +838,14 @@ fn clean_generated_files(cfg: &EffectiveConfig) {
     use std::fs;
     use walkdir::WalkDir;
     
-    let input_path = if let Some(ref filter) = cfg.build_filter {
-        let path = std::path::Path::new(filter);
-        if let Some(parent) = path.parent() {
-            parent.to_string_lossy().to_string()
-        } else {
-            DEFAULT_LIB_DIR.to_string()
-        }
-    } else {
-        DEFAULT_LIB_DIR.to_string()
-    };
+    let input_path = &cfg.input;
     
-    println!("Cleaning generated files in {}...", input_path);
+    info!("Cleaning generated files in {}...", input_path);
     
     let mut cleaned_count = 0;
     
     // Walk through the input directory and find generated files
-    for entry in WalkDir::new(&input_path).into_iter().filter_map(|e| e.ok()) {
+    for entry in WalkDir::new(input_path).into_iter().filter_map(|e| e.ok()) {
         if entry.file_type().is_file() {
             let path = entry.path();
             if let Some(file_name) = path.file_name() {
@@ -365,11 +858,11 @@ fn clean_generated_files(cfg: &EffectiveConfig) {
                     
                     match fs::remove_file(path) {
                         Ok(_) => {
-                            println!("Removed: {}", path.display());
+                            info!(target: SUCCESS, "Removed: {}", path.display());
                             cleaned_count += 1;
                         }
                         Err(e) => {
-                            eprintln!("Failed to remove {}: {}", path.display(), e);
+                            error!("Failed to remove {}: {}", path.display(), e);
                         }
                     }
                 }
@@ -377,12 +870,25 @@ fn clean_generated_files(cfg: &EffectiveConfig) {
         }
     }
     
-    println!("Cleaned {} generated files", cleaned_count);
+    info!(target: SUCCESS, "Cleaned {} generated files", cleaned_count);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn test_stdin_generates_to_stdout() {
+        let cli = Cli::parse_from(["superfastgen", "--stdin", "--type", "freezed", "--filename", "user.dart"]);
+        let cfg = merge_config(&cli, None);
+        let source = "@freezed\nclass User with _$User {\n  const factory User({required String id}) = _User;\n}\n";
+        let mut output = Vec::new();
+        run_stdin(source.as_bytes(), &mut output, cli.filename.as_deref().unwrap(), cli.stdin_type.as_ref().unwrap(), &cfg).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("// ==> user.freezed.dart\n"));
+        assert!(output.contains("mixin _$User {"));
+        assert!(output.contains("part of 'user.dart';"));
+    }
+
     #[test]
     fn test_run_generators() {
         let cfg = EffectiveConfig {
@@ -390,8 +896,269 @@ mod tests {
             assets: DEFAULT_ASSETS_DIR.to_string(),
             watch: false,
             delete_conflicting_outputs: false,
-            build_filter: None,
+            input: DEFAULT_LIB_DIR.to_string(),
+            check: false,
+            prune_orphans: false,
+            fail_on_empty: false,
+            asset_exclude: Vec::new(),
+            assets_class_name: assets::DEFAULT_ASSETS_CLASS_NAME.to_string(),
+            freezed: true,
+            json: true,
+            riverpod: true,
+            provider: true,
+            barrel: None,
+            report: None,
+            options: generate::GenOptions::default(),
+        };
+        let _ = run_generators(&cfg);
+    }
+
+    #[test]
+    fn test_fail_on_empty_input() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let input = temp_dir.path().to_string_lossy().to_string();
+        let cli = Cli::parse_from(["superfastgen", "--fail-on-empty", "--build-filter", &input]);
+        let cfg = merge_config(&cli, None);
+        
+        let err = run_generators(&cfg).unwrap_err();
+        assert!(matches!(err, GenError::NothingToGenerate { dart_files: 0, .. }));
+        assert!(err.to_string().contains(&input));
+        
+        // Dart files without annotated declarations fail too
+        std::fs::write(temp_dir.path().join("plain.dart"), "class Plain {}\n").unwrap();
+        assert!(matches!(run_generators(&cfg), Err(GenError::NothingToGenerate { dart_files: 1, .. })));
+        assert!(check_input_not_empty(&merge_config(&Cli::parse_from(["superfastgen"]), None), &input).is_ok());
+    }
+
+    #[test]
+    fn test_root_resolves_project_paths() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("assets/images")).unwrap();
+        std::fs::write(root.join("assets/images/logo.png"), "fake image").unwrap();
+        std::fs::write(root.join("pubspec.yaml"), "name: app\nflutter:\n  assets:\n    - assets/images/\n").unwrap();
+        std::fs::write(root.join("superfastgen.yaml"), "generate:\n  output: lib/gen\n").unwrap();
+        let cli = Cli::parse_from(["superfastgen", "--root", &root.to_string_lossy()]);
+        let cfg = merge_config(&cli, load_yaml_config(cli.root.as_ref().map(Path::new)));
+
+        assert_eq!(cfg.output, root.join("lib/gen").to_string_lossy());
+        assert_eq!(cfg.in_root(DEFAULT_LIB_DIR), root.join("lib").to_string_lossy());
+        let stats = assets::generate_assets_with_paths(&cfg.assets, &cfg.output, &cfg.asset_exclude, &cfg.assets_class_name, &cfg.options).unwrap();
+        assert_eq!(stats.assets, 1);
+        assert!(root.join("lib/gen/assets.gen.dart").exists());
+    }
+
+    // A @freezed model with JSON support per name, e.g. lib_dir/user.dart for "User"
+    fn write_models(lib_dir: &Path, names: &[&str]) {
+        for name in names {
+            let model = format!(
+                "part '{0}.freezed.dart';\npart '{0}.g.dart';\n\n@freezed\nclass {1} with _${1} {{\n  const factory {1}({{required String id}}) = _{1};\n\n  factory {1}.fromJson(Map<String, dynamic> json) => _${1}FromJson(json);\n}}\n",
+                name.to_lowercase(), name
+            );
+            std::fs::write(lib_dir.join(format!("{}.dart", name.to_lowercase())), model).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_run_summary_counts_generated_models() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        std::fs::create_dir_all(&lib_dir).unwrap();
+        write_models(&lib_dir, &["User", "Post"]);
+        let input_path = lib_dir.to_string_lossy().to_string();
+
+        let summary = run_code_generators(&input_path, &input_path, false, false, true, true, false, false, &generate::GenOptions::default()).unwrap();
+        // Both the freezed and the json pass generate for each model, which counts once
+        assert_eq!(summary, RunSummary { models: 2, assets: 0, providers: 0, timings: summary.timings, outputs: summary.outputs.clone() });
+        // A .freezed.dart and a .g.dart per model, listed once although the json pass rewrites them
+        assert_eq!(summary.outputs.len(), 4);
+        assert_eq!(
+            summary.line(Duration::from_millis(12)),
+            "SuperFastGen: 2 models, 0 assets, 0 providers generated in 12 ms"
+        );
+    }
+
+    #[test]
+    fn test_report_json_includes_phase_durations() {
+        let summary = RunSummary {
+            models: 2,
+            assets: 1,
+            providers: 0,
+            timings: PhaseTimings {
+                parse: Duration::from_millis(3),
+                freezed: Duration::from_millis(7),
+                json: Duration::from_millis(5),
+                provider: Duration::ZERO,
+                assets: Duration::from_millis(1),
+            },
+            outputs: Vec::new(),
         };
-        run_generators(&cfg);
+        let report = summary.report_json(Duration::from_millis(20));
+        assert_eq!(report["models"], 2);
+        assert_eq!(report["elapsed_ms"], 20);
+        assert_eq!(
+            report["phases"],
+            serde_json::json!({ "parse_ms": 3, "freezed_ms": 7, "json_ms": 5, "provider_ms": 0, "assets_ms": 1 })
+        );
+        assert_eq!(summary.timings.line(), "Phase timings: parse 3 ms, freezed 7 ms, json 5 ms, provider 0 ms, assets 1 ms");
+    }
+
+    #[test]
+    fn test_generate_single_file_argument() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        std::fs::create_dir_all(&lib_dir).unwrap();
+        write_models(&lib_dir, &["User", "Post"]);
+        let user_path = lib_dir.join("user.dart").to_string_lossy().to_string();
+        let cli = Cli::parse_from(["superfastgen", "generate", "--type", "freezed", user_path.as_str()]);
+        let Some(Commands::Generate { directories, .. }) = cli.command else { panic!("expected the generate subcommand") };
+        assert_eq!(directories, vec![user_path.clone()]);
+
+        let summary = run_code_generators(&directories[0], &directories[0], false, false, true, false, false, false, &generate::GenOptions::default()).unwrap();
+        let mut generated: Vec<String> = std::fs::read_dir(&lib_dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".freezed.dart") || name.ends_with(".g.dart"))
+            .collect();
+        generated.sort();
+        assert_eq!(generated, ["user.freezed.dart", "user.g.dart"]);
+        // Like for a directory, the summary counts the models generated for
+        assert_eq!(summary.models, 1);
+    }
+
+    #[test]
+    fn test_watch_regenerates_only_the_changed_file() {
+        use notify::event::{CreateKind, ModifyKind, RemoveKind};
+        
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        std::fs::create_dir_all(&lib_dir).unwrap();
+        write_models(&lib_dir, &["User", "Post"]);
+        let input_path = lib_dir.to_string_lossy().to_string();
+        
+        let event = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(lib_dir.join("user.dart"));
+        let WatchAction::Files(files) = watch_action(&event) else { panic!("expected a per-file action") };
+        assert_eq!(files, vec![lib_dir.join("user.dart")]);
+        
+        let summary = run_code_generators_for_files(&files, &input_path, &input_path, true, true, false, false, &generate::GenOptions::default()).unwrap();
+        assert_eq!(summary.models, 1);
+        assert_eq!(summary.outputs, vec![lib_dir.join("user.freezed.dart"), lib_dir.join("user.g.dart")]);
+        assert!(lib_dir.join("user.freezed.dart").exists());
+        assert!(lib_dir.join("user.g.dart").exists());
+        assert!(!lib_dir.join("post.freezed.dart").exists());
+        assert!(!lib_dir.join("post.g.dart").exists());
+        
+        // Files a full run skips are skipped in watch mode too
+        write_models(&lib_dir, &["Legacy"]);
+        std::fs::write(lib_dir.join(".superfastgenignore"), "legacy.dart\n").unwrap();
+        let summary = run_code_generators_for_files(&[lib_dir.join("legacy.dart")], &input_path, &input_path, true, true, false, false, &generate::GenOptions::default()).unwrap();
+        assert_eq!(summary.models, 0);
+        assert!(!lib_dir.join("legacy.freezed.dart").exists());
+        
+        // Our own writes don't retrigger generation; pubspec.yaml and removals need a full run
+        let written = Event::new(EventKind::Create(CreateKind::File)).add_path(lib_dir.join("user.g.dart"));
+        assert_eq!(watch_action(&written), WatchAction::Ignore);
+        let pubspec = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(temp_dir.path().join("pubspec.yaml"));
+        assert_eq!(watch_action(&pubspec), WatchAction::Full);
+        let removed = Event::new(EventKind::Remove(RemoveKind::File)).add_path(lib_dir.join("post.dart"));
+        assert_eq!(watch_action(&removed), WatchAction::Full);
+    }
+
+    #[test]
+    fn test_barrel_written_on_every_watch_run() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let lib_dir = temp_dir.path().join("lib");
+        std::fs::create_dir_all(&lib_dir).unwrap();
+        write_models(&lib_dir, &["User", "Post"]);
+        let input = lib_dir.to_string_lossy().to_string();
+        let barrel = lib_dir.join("generated.dart");
+        let cli = Cli::parse_from(["superfastgen", "--watch", "--build-filter", &input, "--output", &input, "--barrel", &barrel.to_string_lossy()]);
+        let cfg = EffectiveConfig { riverpod: false, provider: false, ..merge_config(&cli, None) };
+        let session = SessionOutputs::default();
+
+        report_run(session.full_run(run_code_generators(&input, &input, false, false, true, true, false, false, &cfg.options)), Instant::now(), &cfg);
+        assert_eq!(std::fs::read_to_string(&barrel).unwrap().matches("export '").count(), 4);
+
+        // A per-file re-run still exports the files generated before it
+        std::fs::remove_file(&barrel).unwrap();
+        write_models(&lib_dir, &["Comment"]);
+        let files = [lib_dir.join("comment.dart")];
+        report_run(session.file_run(run_generators_for_files(&cfg, &files)), Instant::now(), &cfg);
+        let content = std::fs::read_to_string(&barrel).unwrap();
+        assert_eq!(content.matches("export '").count(), 6);
+        assert!(content.contains("export 'user.freezed.dart';"));
+        assert!(content.contains("export 'comment.g.dart';"));
+    }
+
+    #[test]
+    fn test_assets_watcher_detects_asset_change() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let assets_dir = temp_dir.path().join("assets");
+        std::fs::create_dir_all(&assets_dir).unwrap();
+        
+        let (_watcher, rx) = create_watcher(&[assets_dir.to_string_lossy().to_string()]).unwrap();
+        std::fs::write(assets_dir.join("logo.png"), "fake image").unwrap();
+        
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        let mut changed = false;
+        while let Some(remaining) = deadline.checked_duration_since(std::time::Instant::now()) {
+            match rx.recv_timeout(remaining) {
+                Ok(Ok(event)) if is_change_event(&event) => {
+                    changed = true;
+                    break;
+                }
+                Ok(_) => continue,
+                Err(_) => break,
+            }
+        }
+        assert!(changed);
+    }
+
+    #[test]
+    fn test_yaml_disables_freezed_generation() {
+        let cli = Cli::parse_from(["superfastgen", "generate", "--type", "all"]);
+        let yaml_config: yaml::SuperfastgenConfig = serde_yaml::from_str("generate:\n  freezed: false\n").unwrap();
+        let effective = merge_config(&cli, Some(yaml_config));
+        assert!(!effective.freezed);
+        
+        assert_eq!(selected_generators(&GenType::All, &effective), (false, true, true, true));
+        assert_eq!(selected_generators(&GenType::Freezed, &effective), (false, false, false, false));
+        assert_eq!(selected_generators(&GenType::Json, &effective), (false, true, false, false));
+        
+        let effective = merge_config(&cli, None);
+        assert_eq!(selected_generators(&GenType::All, &effective), (true, true, true, true));
+    }
+
+    #[test]
+    fn test_resolve_input_path_uses_yaml_input() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let src_dir = temp_dir.path().join("lib/src");
+        let other_dir = temp_dir.path().join("lib/other");
+        std::fs::create_dir_all(&src_dir).unwrap();
+        std::fs::create_dir_all(&other_dir).unwrap();
+        let model = |name: &str| format!(
+            "part '{0}.freezed.dart';\n\n@freezed\nclass {1} with _${1} {{\n  const factory {1}({{required String id}}) = _{1};\n}}\n",
+            name.to_lowercase(), name
+        );
+        std::fs::write(src_dir.join("user.dart"), model("User")).unwrap();
+        std::fs::write(other_dir.join("post.dart"), model("Post")).unwrap();
+        
+        let config_path = temp_dir.path().join("superfastgen.yaml");
+        std::fs::write(&config_path, format!("generate:\n  input: {}\n", src_dir.display())).unwrap();
+        let yaml_gen = yaml::parse_superfastgen_yaml(config_path.to_str().unwrap()).unwrap().generate.unwrap();
+        
+        let input_path = resolve_input_path(None, &yaml_gen);
+        assert_eq!(input_path, src_dir.to_string_lossy());
+        let outputs = generate::collect_generated_outputs(&input_path, &input_path, true, false, false, false, &generate::GenOptions::default()).unwrap();
+        assert!(!outputs.is_empty());
+        assert!(outputs.iter().all(|(path, _)| path.starts_with(&src_dir)));
+        
+        // --build-filter wins over the config, and the default applies without either
+        let filter = "lib/feature".to_string();
+        assert_eq!(resolve_input_path(Some(&filter), &yaml_gen), "lib/feature");
+        assert_eq!(resolve_input_path(None, &yaml::GenerateConfig::default()), DEFAULT_LIB_DIR);
+        
+        // Every subcommand takes its input from the same resolved path
+        let cli = Cli::parse_from(["superfastgen", "--build-filter", "lib/feature"]);
+        assert_eq!(merge_config(&cli, None).input, "lib/feature");
     }
 }
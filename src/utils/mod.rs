@@ -1,2 +1,3 @@
+pub mod output;
 pub mod parser;
 pub mod yaml; 
\ No newline at end of file
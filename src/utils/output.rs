@@ -0,0 +1,62 @@
+//! Terminal output of CLI runs. Progress, warnings and errors all go through the `log` macros;
+//! the logger set up here prints them, colored by level when stderr is a terminal.
+
+use anstyle::{AnsiColor, Style};
+use log::Level;
+use std::io::{IsTerminal, Write};
+
+/// Log target for messages reporting a finished step (`Generated: ...`), printed in green
+pub const SUCCESS: &str = "superfastgen::success";
+
+/// Install the logger, defaulting to `default_level` unless `RUST_LOG` says otherwise
+pub fn init_logger(default_level: &str) {
+    let color = color_enabled(std::io::stderr().is_terminal(), std::env::var_os("NO_COLOR").is_some());
+    let write_style = if color { env_logger::WriteStyle::Always } else { env_logger::WriteStyle::Never };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_level))
+        .write_style(write_style)
+        .format(move |buf, record| {
+            writeln!(buf, "{}", format_message(record.level(), record.target(), &record.args().to_string(), color))
+        })
+        .init();
+}
+
+/// Colors only make sense on a terminal, and https://no-color.org turns them off there too
+pub fn color_enabled(is_terminal: bool, no_color: bool) -> bool {
+    is_terminal && !no_color
+}
+
+/// One output line: errors in red, warnings in yellow, successes in green, other info as is
+pub fn format_message(level: Level, target: &str, message: &str, color: bool) -> String {
+    let (text, style) = match level {
+        Level::Error => (format!("error: {}", message), AnsiColor::Red.on_default().bold()),
+        Level::Warn => (format!("warning: {}", message), AnsiColor::Yellow.on_default()),
+        Level::Info if target == SUCCESS => (message.to_string(), AnsiColor::Green.on_default()),
+        Level::Info => (message.to_string(), Style::new()),
+        Level::Debug | Level::Trace => (format!("{}: {}", level.as_str().to_lowercase(), message), Style::new().dimmed()),
+    };
+    if color {
+        format!("{}{}{}", style.render(), text, style.render_reset())
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_color_codes_when_piped() {
+        let color = color_enabled(false, false);
+        assert!(!color);
+        for (level, target) in [(Level::Error, "superfastgen"), (Level::Warn, "superfastgen"), (Level::Info, SUCCESS)] {
+            assert!(!format_message(level, target, "lib/user.g.dart", color).contains('\x1b'));
+        }
+        assert_eq!(format_message(Level::Error, "superfastgen", "boom", color), "error: boom");
+        assert_eq!(format_message(Level::Info, SUCCESS, "Generated: lib/user.g.dart", color), "Generated: lib/user.g.dart");
+
+        assert!(!color_enabled(true, true));
+        assert!(format_message(Level::Error, "superfastgen", "boom", color_enabled(true, false)).starts_with("\x1b["));
+        assert!(format_message(Level::Info, SUCCESS, "done", true).contains("\x1b[32m"));
+    }
+}
@@ -1,10 +1,12 @@
+use log::debug;
+
 #[allow(dead_code)]
 pub fn parse_ast(_input: &str) -> Result<(), String> {
-    println!("Parsing AST...");
+    debug!("Parsing AST...");
     Ok(())
 }
 
 pub fn parse_code(_input: &str) -> Result<(), String> {
-    println!("Parsing code with tree-sitter...");
+    debug!("Parsing code with tree-sitter...");
     Ok(())
 } 
\ No newline at end of file
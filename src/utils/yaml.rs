@@ -1,5 +1,7 @@
 use serde::Deserialize;
 use std::fs;
+use log::{debug, warn};
+use crate::commands::freezed_gen::OutputLayout;
 
 #[derive(Debug, Deserialize, Default, Clone)]
 pub struct GenerateConfig {
@@ -9,6 +11,16 @@ pub struct GenerateConfig {
     pub json: Option<bool>,
     pub riverpod: Option<bool>,
     pub provider: Option<bool>,
+    // sibling (next to the source) or mirror (under `output`, preserving the path from `input`)
+    pub output_layout: Option<OutputLayout>,
+    // Also generate a copyWith extension for plain (non-freezed) @JsonSerializable classes
+    pub copywith: Option<bool>,
+    // Convert fast_immutable_collections' IList/ISet/IMap fields to and from JSON
+    pub immutable_collections: Option<bool>,
+    // Spaces per indentation level in generated files (2 or 4)
+    pub indent: Option<usize>,
+    // Glob patterns for source files to skip, relative to `input` (e.g. "**/legacy/*.dart")
+    pub exclude: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
@@ -18,6 +30,10 @@ pub struct AssetsConfig {
     pub include_images: Option<bool>,
     pub include_fonts: Option<bool>,
     pub include_icons: Option<bool>,
+    // Glob patterns for asset files to leave out (e.g. "*.psd", "assets/raw/**")
+    pub exclude: Option<Vec<String>>,
+    // Name of the top-level class in assets.gen.dart (default: Assets)
+    pub class_name: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
@@ -28,17 +44,227 @@ pub struct SuperfastgenConfig {
 
 pub fn parse_superfastgen_yaml(path: &str) -> Option<SuperfastgenConfig> {
     let content = fs::read_to_string(path).ok()?;
-    serde_yaml::from_str(&content).ok()
+    serde_yaml::from_str::<SuperfastgenConfig>(&content).ok().map(SuperfastgenConfig::expand_env)
+}
+
+/// Replace `${VAR}` and `$VAR` with the variable's value; unset variables become empty with a warning
+pub fn expand_env_vars(value: &str) -> String {
+    let pattern = regex::Regex::new(r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)\}|([A-Za-z_][A-Za-z0-9_]*))").unwrap();
+    pattern.replace_all(value, |caps: &regex::Captures| {
+        let name = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+        std::env::var(name).unwrap_or_else(|_| {
+            warn!("Environment variable {} in config path \"{}\" is not set; using an empty value", name, value);
+            String::new()
+        })
+    }).into_owned()
+}
+
+// pubspec.yaml with an optional `superfastgen:` section (like flutter_gen's `flutter_gen:`)
+#[derive(Debug, Deserialize)]
+struct PubspecWithConfig {
+    superfastgen: Option<SuperfastgenConfig>,
+}
+
+/// The `superfastgen:` section of a pubspec.yaml, if there is one
+pub fn parse_config_from_pubspec(path: &str) -> Option<SuperfastgenConfig> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_yaml::from_str::<PubspecWithConfig>(&content).ok()?.superfastgen.map(SuperfastgenConfig::expand_env)
+}
+
+/// Config from superfastgen.yaml merged over the `superfastgen:` section of pubspec.yaml;
+/// each setting comes from the dedicated file when it's set there
+pub fn load_config(superfastgen_path: &str, pubspec_path: &str) -> Option<SuperfastgenConfig> {
+    match (parse_superfastgen_yaml(superfastgen_path), parse_config_from_pubspec(pubspec_path)) {
+        (Some(config), Some(fallback)) => Some(config.or(fallback)),
+        (config, fallback) => config.or(fallback),
+    }
+}
+
+impl SuperfastgenConfig {
+    /// Fill settings missing from self with the ones in fallback
+    pub fn or(self, fallback: SuperfastgenConfig) -> SuperfastgenConfig {
+        SuperfastgenConfig {
+            generate: match (self.generate, fallback.generate) {
+                (Some(generate), Some(fallback)) => Some(generate.or(fallback)),
+                (generate, fallback) => generate.or(fallback),
+            },
+            assets: match (self.assets, fallback.assets) {
+                (Some(assets), Some(fallback)) => Some(assets.or(fallback)),
+                (assets, fallback) => assets.or(fallback),
+            },
+        }
+    }
+
+    // Expand environment variables in the input/output paths
+    fn expand_env(self) -> SuperfastgenConfig {
+        let expand = |path: Option<String>| path.map(|path| expand_env_vars(&path));
+        SuperfastgenConfig {
+            generate: self.generate.map(|generate| GenerateConfig {
+                input: expand(generate.input),
+                output: expand(generate.output),
+                ..generate
+            }),
+            assets: self.assets.map(|assets| AssetsConfig {
+                input: expand(assets.input),
+                output: expand(assets.output),
+                ..assets
+            }),
+        }
+    }
+}
+
+impl GenerateConfig {
+    fn or(self, fallback: GenerateConfig) -> GenerateConfig {
+        GenerateConfig {
+            input: self.input.or(fallback.input),
+            output: self.output.or(fallback.output),
+            freezed: self.freezed.or(fallback.freezed),
+            json: self.json.or(fallback.json),
+            riverpod: self.riverpod.or(fallback.riverpod),
+            provider: self.provider.or(fallback.provider),
+            output_layout: self.output_layout.or(fallback.output_layout),
+            copywith: self.copywith.or(fallback.copywith),
+            immutable_collections: self.immutable_collections.or(fallback.immutable_collections),
+            indent: self.indent.or(fallback.indent),
+            exclude: self.exclude.or(fallback.exclude),
+        }
+    }
+}
+
+impl AssetsConfig {
+    fn or(self, fallback: AssetsConfig) -> AssetsConfig {
+        AssetsConfig {
+            input: self.input.or(fallback.input),
+            output: self.output.or(fallback.output),
+            include_images: self.include_images.or(fallback.include_images),
+            include_fonts: self.include_fonts.or(fallback.include_fonts),
+            include_icons: self.include_icons.or(fallback.include_icons),
+            exclude: self.exclude.or(fallback.exclude),
+            class_name: self.class_name.or(fallback.class_name),
+        }
+    }
+}
+
+/// Default superfastgen.yaml written by `superfastgen init`.
+/// Keys mirror the fields of GenerateConfig and AssetsConfig.
+pub const STARTER_CONFIG: &str = r#"# superfastgen configuration
+# CLI arguments take precedence over these settings.
+
+generate:
+  # Directory scanned for annotated Dart files
+  input: lib/
+  # Directory for generated files
+  output: lib/
+  # sibling: write next to each source file; mirror: write under `output`, preserving the path from `input`
+  output_layout: sibling
+  # Spaces per indentation level in generated files (2 or 4)
+  indent: 2
+  # Generate .freezed.dart files for @freezed classes
+  freezed: true
+  # Generate .g.dart JSON serialization
+  json: true
+  # Also generate a copyWith extension for plain @JsonSerializable classes
+  copywith: false
+  # Convert IList/ISet/IMap fields (fast_immutable_collections) to and from JSON
+  immutable_collections: false
+  # Generate providers for @riverpod declarations
+  riverpod: true
+  # Generate providers for explicit provider annotations (@FutureProvider, ...)
+  provider: true
+  # Glob patterns for Dart files to skip, relative to `input` (e.g. "**/legacy/*.dart")
+  exclude: []
+
+assets:
+  # Directory scanned for asset files
+  input: assets/
+  # Directory for assets.gen.dart
+  output: lib/gen/
+  include_images: true
+  include_fonts: true
+  include_icons: true
+  # Glob patterns for asset files to leave out of assets.gen.dart
+  exclude: []
+  # Name of the generated top-level class
+  class_name: Assets
+"#;
+
+/// Write STARTER_CONFIG to `path`, refusing to overwrite an existing file unless `force` is set
+pub fn write_starter_config(path: &str, force: bool) -> Result<(), String> {
+    if !force && std::path::Path::new(path).exists() {
+        return Err(format!("{} already exists (use --force to overwrite)", path));
+    }
+    fs::write(path, STARTER_CONFIG).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// JSON Schema of superfastgen.yaml (printed by `superfastgen config-schema`) for editor completion
+/// and validation. Keep it in sync with GenerateConfig and AssetsConfig.
+pub fn config_schema() -> serde_json::Value {
+    let string = |description: &str| serde_json::json!({ "type": "string", "description": description });
+    let boolean = |description: &str| serde_json::json!({ "type": "boolean", "description": description });
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "superfastgen.yaml",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "generate": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "input": string("Directory scanned for annotated Dart files"),
+                    "output": string("Directory for generated files"),
+                    "freezed": boolean("Generate .freezed.dart files for @freezed classes"),
+                    "json": boolean("Generate .g.dart JSON serialization"),
+                    "riverpod": boolean("Generate providers for @riverpod declarations"),
+                    "provider": boolean("Generate providers for explicit provider annotations (@FutureProvider, ...)"),
+                    "output_layout": {
+                        "type": "string",
+                        "enum": ["sibling", "mirror"],
+                        "description": "sibling: write next to each source file; mirror: write under `output`, preserving the path from `input`"
+                    },
+                    "copywith": boolean("Also generate a copyWith extension for plain @JsonSerializable classes"),
+                    "immutable_collections": boolean("Convert IList/ISet/IMap fields (fast_immutable_collections) to and from JSON"),
+                    "indent": {
+                        "type": "integer",
+                        "enum": [2, 4],
+                        "description": "Spaces per indentation level in generated files"
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Glob patterns for Dart files to skip, relative to `input`"
+                    }
+                }
+            },
+            "assets": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "input": string("Directory scanned for asset files"),
+                    "output": string("Directory for assets.gen.dart"),
+                    "include_images": boolean("Include image assets"),
+                    "include_fonts": boolean("Include font assets"),
+                    "include_icons": boolean("Include icon assets"),
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Glob patterns for asset files to leave out of assets.gen.dart"
+                    },
+                    "class_name": string("Name of the generated top-level class")
+                }
+            }
+        }
+    })
 }
 
 pub fn parse_pubspec_yaml(_content: &str) -> Result<(), String> {
-    println!("Parsing pubspec.yaml...");
+    debug!("Parsing pubspec.yaml...");
     Ok(())
 }
 
 #[allow(dead_code)]
 pub fn generate_pubspec_yaml() -> Result<String, String> {
-    println!("Generating pubspec.yaml...");
+    debug!("Generating pubspec.yaml...");
     Ok("name: superfastgen".to_string())
 }
 
@@ -46,6 +272,101 @@ pub fn generate_pubspec_yaml() -> Result<String, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_write_starter_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("superfastgen.yaml");
+        let path = path.to_str().unwrap();
+        
+        assert!(write_starter_config(path, false).is_ok());
+        let config = parse_superfastgen_yaml(path).unwrap();
+        
+        let generate = config.generate.unwrap();
+        assert_eq!(generate.input, Some("lib/".to_string()));
+        assert!(generate.output.is_some());
+        assert_eq!(generate.freezed, Some(true));
+        assert_eq!(generate.json, Some(true));
+        assert_eq!(generate.riverpod, Some(true));
+        assert_eq!(generate.provider, Some(true));
+        
+        let assets = config.assets.unwrap();
+        assert_eq!(assets.input, Some("assets/".to_string()));
+        assert!(assets.output.is_some());
+        assert_eq!(assets.include_images, Some(true));
+        assert_eq!(assets.include_fonts, Some(true));
+        assert_eq!(assets.include_icons, Some(true));
+        assert_eq!(assets.exclude, Some(Vec::new()));
+        
+        // Existing files are only replaced with force
+        fs::write(path, "generate: {}\n").unwrap();
+        assert!(write_starter_config(path, false).is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "generate: {}\n");
+        assert!(write_starter_config(path, true).is_ok());
+        assert_eq!(fs::read_to_string(path).unwrap(), STARTER_CONFIG);
+    }
+
+    #[test]
+    fn test_config_from_pubspec() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pubspec = temp_dir.path().join("pubspec.yaml");
+        fs::write(&pubspec, "name: app\nflutter:\n  assets:\n    - assets/\nsuperfastgen:\n  generate:\n    input: lib/src/\n    riverpod: false\n  assets:\n    exclude: [\"*.psd\"]\n").unwrap();
+        let pubspec = pubspec.to_str().unwrap();
+        
+        let config = parse_config_from_pubspec(pubspec).unwrap();
+        assert_eq!(config.generate.unwrap().riverpod, Some(false));
+        
+        // superfastgen.yaml wins where both set a value
+        let dedicated = temp_dir.path().join("superfastgen.yaml");
+        fs::write(&dedicated, "generate:\n  input: lib/\n  json: false\n").unwrap();
+        let config = load_config(dedicated.to_str().unwrap(), pubspec).unwrap();
+        let generate = config.generate.unwrap();
+        assert_eq!(generate.input, Some("lib/".to_string()));
+        assert_eq!(generate.json, Some(false));
+        assert_eq!(generate.riverpod, Some(false));
+        assert_eq!(config.assets.unwrap().exclude, Some(vec!["*.psd".to_string()]));
+        
+        // Either source works alone
+        let missing = temp_dir.path().join("missing.yaml");
+        let missing = missing.to_str().unwrap();
+        assert_eq!(load_config(missing, pubspec).unwrap().generate.unwrap().input, Some("lib/src/".to_string()));
+        assert!(load_config(missing, missing).is_none());
+        fs::write(temp_dir.path().join("plain.yaml"), "name: app\n").unwrap();
+        assert!(parse_config_from_pubspec(temp_dir.path().join("plain.yaml").to_str().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_config_schema() {
+        let schema = config_schema();
+        assert_eq!(schema["properties"]["generate"]["properties"]["freezed"]["type"], "boolean");
+        assert_eq!(schema["properties"]["assets"]["properties"]["exclude"]["type"], "array");
+        
+        // Every key of the starter config is described
+        let starter: serde_yaml::Value = serde_yaml::from_str(STARTER_CONFIG).unwrap();
+        for (section, settings) in starter.as_mapping().unwrap() {
+            let section = section.as_str().unwrap();
+            for key in settings.as_mapping().unwrap().keys() {
+                let key = key.as_str().unwrap();
+                assert!(schema["properties"][section]["properties"].get(key).is_some(), "{}.{} missing from the schema", section, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_env_vars_in_paths() {
+        std::env::set_var("SUPERFASTGEN_TEST_GEN_OUT", "/tmp/ci");
+        std::env::remove_var("SUPERFASTGEN_TEST_UNSET");
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("superfastgen.yaml");
+        fs::write(&path, "generate:\n  input: $SUPERFASTGEN_TEST_GEN_OUT/lib\n  output: ${SUPERFASTGEN_TEST_GEN_OUT}/lib/gen\nassets:\n  output: ${SUPERFASTGEN_TEST_UNSET}lib/gen\n").unwrap();
+        
+        let config = parse_superfastgen_yaml(path.to_str().unwrap()).unwrap();
+        let generate = config.generate.unwrap();
+        assert_eq!(generate.input, Some("/tmp/ci/lib".to_string()));
+        assert_eq!(generate.output, Some("/tmp/ci/lib/gen".to_string()));
+        assert_eq!(config.assets.unwrap().output, Some("lib/gen".to_string()));
+        assert_eq!(expand_env_vars("lib/gen/"), "lib/gen/");
+    }
+
     #[test]
     fn test_parse_superfastgen_yaml() {
         let config = parse_superfastgen_yaml("superfastgen.yaml");